@@ -142,6 +142,11 @@ impl<const MTU: usize> ChunkList<MTU> {
         !chunks.is_empty() && chunks.len() == chunks.first().unwrap().chunk[1]
     }
 
+    /// total bytes of data in list
+    pub fn data_len(&self) -> usize {
+        self.0.iter().map(|c| c.data.len()).sum()
+    }
+
     /// if list is completed, withdraw data, or return None
     pub fn try_withdraw(&self) -> Option<Bytes> {
         if !self.is_completed() {
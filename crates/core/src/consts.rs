@@ -10,3 +10,17 @@ pub const TRANSPORT_MTU: usize = 60000;
 /// 60M
 pub const TRANSPORT_MAX_SIZE: usize = TRANSPORT_MTU * 1000;
 pub const VNODE_DATA_MAX_LEN: usize = 1024;
+/// Interval of removing expired virtual nodes, in ms
+pub const VNODE_GC_INTERVAL_MS: u128 = 60 * 1000;
+/// Timeout of waiting a virtual node fetched from DHT, in ms
+pub const VNODE_FETCH_TIMEOUT_MS: u64 = 3000;
+/// Interval of checking the fetched virtual node in local cache, in ms
+pub const VNODE_FETCH_INTERVAL_MS: u64 = 200;
+/// Interval of saving routing state for warm restart, in ms
+pub const ROUTING_STATE_SAVE_INTERVAL_MS: u128 = 30 * 1000;
+/// Number of virtual nodes sent in one message when handing over storage to successor
+pub const VNODE_HANDOVER_BATCH_SIZE: usize = 64;
+/// Time a leaving neighbour is trusted to hand over its replicas after announcing, in ms
+pub const VNODE_HANDOVER_TIMEOUT_MS: u128 = 60 * 1000;
+/// Maximum number of nodes reported to the origin of an iterative lookup
+pub const LOOKUP_REPORT_SIZE: usize = 8;
@@ -6,11 +6,16 @@ use std::sync::Mutex;
 use std::sync::MutexGuard;
 
 use async_trait::async_trait;
+use dashmap::DashMap;
 use num_bigint::BigUint;
 use serde::Deserialize;
 use serde::Serialize;
 
 use super::did::BiasId;
+use super::expiry::ExpiryIndex;
+use super::expiry::ExpiryStore;
+use super::replica::ReplicaReads;
+use super::subring::SubringView;
 use super::successor::SuccessorSeq;
 use super::types::Chord;
 use super::types::ChordStorage;
@@ -18,16 +23,20 @@ use super::types::ChordStorageCache;
 use super::types::ChordStorageSync;
 use super::types::CorrectChord;
 use super::vnode::VNodeOperation;
+use super::vnode::VNodeType;
 use super::vnode::VirtualNode;
 use super::FingerTable;
+use crate::consts::VNODE_HANDOVER_TIMEOUT_MS;
 use crate::dht::Did;
 use crate::dht::LiveDid;
 use crate::dht::SuccessorReader;
 use crate::dht::SuccessorWriter;
 use crate::error::Error;
 use crate::error::Result;
+use crate::message::Encoded;
 use crate::storage::KvStorageInterface;
 use crate::storage::MemStorage;
+use crate::utils::get_epoch_ms;
 
 /// `VNodeStorage` is the type accepted by `PeerRing::new_with_storage`.
 /// It's used to store [VirtualNode]s in a storage media provided by user.
@@ -59,6 +68,14 @@ pub struct PeerRing {
     pub storage: VNodeStorage,
     /// Local cache for [ChordStorage].
     pub cache: VNodeStorage,
+    /// Local views of synced subrings, indexed by name, used to route messages within subring.
+    pub subrings: DashMap<String, SubringView>,
+    /// Replies of replicas collected for read repair.
+    pub replica_reads: ReplicaReads,
+    /// Neighbours announced leaving, with the time of announcement, see [PeerRing::depart].
+    pub departed: DashMap<Did, u128>,
+    /// Expiring vnodes in local storage and cache, for garbage collection.
+    pub expiries: ExpiryIndex,
 }
 
 /// Type alias is just for making the code easy to read.
@@ -94,10 +111,15 @@ pub enum RemoteAction {
     FindVNode(Did),
     /// Need `did_a` to find VirtualNode for operating.
     FindVNodeForOperate(VNodeOperation),
+    /// Need `did_a` to find the replica `did_b` of VirtualNode for operating.
+    FindReplicaForOperate(Did, VNodeOperation),
     /// Let `did_a` [notify](Chord::notify) `did_b`.
     Notify(Did),
     /// Let `did_a` sync data with it's successor.
-    SyncVNodeWithSuccessor(Vec<VirtualNode>),
+    /// Each VirtualNode is paired with the replica position it's stored at.
+    SyncVNodeWithSuccessor(Vec<(Did, VirtualNode)>),
+    /// Push new data of virtual node `did_b` to it's subscriber `did_a`.
+    PushToSubscriber(Did, Vec<Encoded>),
 
     /// Need `did_a` to find `did_b` then send back with `for connect` flag.
     FindSuccessorForConnect(Did),
@@ -200,6 +222,10 @@ impl PeerRing {
             finger: Arc::new(Mutex::new(FingerTable::new(did, 160))),
             storage,
             cache: Box::new(MemStorage::new()),
+            subrings: DashMap::new(),
+            replica_reads: ReplicaReads::default(),
+            departed: DashMap::new(),
+            expiries: ExpiryIndex::default(),
             did,
         }
     }
@@ -247,10 +273,201 @@ impl PeerRing {
         Ok(())
     }
 
+    /// Remove a neighbour announced leaving from DHT. It's still trusted to hand over its
+    /// replicas for [VNODE_HANDOVER_TIMEOUT_MS], see [PeerRing::is_replica_holder].
+    pub fn depart(&self, did: Did) -> Result<()> {
+        if self.is_replica_holder(did)? {
+            self.departed.insert(did, get_epoch_ms());
+        }
+        self.remove(did)
+    }
+
+    /// Returns `true` if did can store replicas on current node regardless of their owner:
+    /// current node itself, its predecessor and successors, or a neighbour just departed.
+    pub fn is_replica_holder(&self, did: Did) -> Result<bool> {
+        if did == self.did
+            || *self.lock_predecessor()? == Some(did)
+            || self.successors().contains(&did)?
+        {
+            return Ok(true);
+        }
+        let now = get_epoch_ms();
+        self.departed
+            .retain(|_, at| now.saturating_sub(*at) < VNODE_HANDOVER_TIMEOUT_MS);
+        Ok(self.departed.contains_key(&did))
+    }
+
     /// Calculate bias of the Did on the ring.
     pub fn bias(&self, did: Did) -> BiasId {
         BiasId::new(self.did, did)
     }
+
+    /// Record the measured RTT of a node, so fingers prefer the nearby nodes.
+    pub fn record_rtt(&self, did: Did, rtt_ms: u64) -> Result<()> {
+        if did == self.did {
+            return Ok(());
+        }
+        self.lock_finger()?.record_rtt(did, rtt_ms);
+        Ok(())
+    }
+
+    /// Nodes in finger table and successor list preceding did, closest to did first.
+    /// At most `count` nodes are returned. It's used to answer iterative lookups, so the origin
+    /// has other candidates to fall back on when the closest one doesn't respond.
+    pub fn closest_preceding_nodes(&self, did: Did, count: usize) -> Result<Vec<Did>> {
+        let bias = self.bias(did);
+        let mut nodes: Vec<Did> = self
+            .lock_finger()?
+            .list()
+            .iter()
+            .flatten()
+            .copied()
+            .chain(self.successors().list()?)
+            .filter(|n| *n != self.did && self.bias(*n) < bias)
+            .collect();
+        nodes.sort_by_key(|n| did.bias(*n).pos());
+        nodes.dedup();
+        nodes.truncate(count);
+        Ok(nodes)
+    }
+
+    /// Find the successor of did among members of subring, by the local view of it.
+    /// Returns [Error::SubringNotSynced] if there is no local view of the subring.
+    pub fn subring_find_successor(&self, name: &str, did: Did) -> Result<PeerRingAction> {
+        self.subrings
+            .get(name)
+            .map(|view| view.find_successor(did))
+            .ok_or_else(|| Error::SubringNotSynced(name.to_string()))
+    }
+
+    /// Find the successor of did for a message relayed within subring. Like
+    /// [PeerRing::subring_find_successor], but falls back on global routing if there is no local
+    /// view of the subring, which is the case of a member that hasn't synced it.
+    pub fn subring_relay_successor(&self, name: &str, did: Did) -> Result<PeerRingAction> {
+        match self.subring_find_successor(name, did) {
+            Err(Error::SubringNotSynced(_)) => {
+                tracing::warn!(
+                    "Subring {} is not synced, relay {} by global routing",
+                    name,
+                    did
+                );
+                self.find_successor(did)
+            }
+            act => act,
+        }
+    }
+
+    /// Remove expired [VirtualNode]s from local storage and cache.
+    /// Only the vnodes indexed as expired by [ExpiryIndex] are visited, except that the vnodes
+    /// persisted before starting are indexed the first time.
+    /// Returns the number of removed vnodes.
+    pub async fn gc_expired_vnodes(&self) -> Result<usize> {
+        if self.expiries.should_load() {
+            for (key, vnode) in self.storage.get_all().await? {
+                self.expiries.insert(ExpiryStore::Storage, &key, &vnode)?;
+            }
+        }
+        let mut count = 0;
+        for (store, key) in self.expiries.take_expired(get_epoch_ms() as u64)? {
+            let storage = match store {
+                ExpiryStore::Storage => &self.storage,
+                ExpiryStore::Cache => &self.cache,
+            };
+            // The vnode may be removed or renewed after indexed.
+            if let Some(vnode) = storage.get(&key).await? {
+                if vnode.is_expired() {
+                    storage.remove(&key).await?;
+                    count += 1;
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Get the mailbox of `did` from local storage. It's kept until cleared by
+    /// [PeerRing::clear_mailbox] or expired.
+    /// Returns None if current node doesn't keep any message for `did`.
+    pub async fn mailbox(&self, did: Did) -> Result<Option<VirtualNode>> {
+        let key = VirtualNode::mailbox_did(did).to_string();
+        match self.storage.get(&key).await? {
+            Some(vnode) if vnode.kind == VNodeType::RelayMessage => Ok(Some(vnode)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Remove the mailbox of `did` from local storage, if it's not extended since `version`,
+    /// which is delivered to `did`. Returns `true` if the mailbox is removed.
+    pub async fn clear_mailbox(&self, did: Did, version: u64) -> Result<bool> {
+        let key = VirtualNode::mailbox_did(did).to_string();
+        match self.storage.get(&key).await? {
+            Some(vnode) if vnode.kind == VNodeType::RelayMessage && vnode.version == version => {
+                self.storage.remove(&key).await?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Handle [VNodeOperation] on the replica at `vid`, which is one of the affine positions
+    /// of the target vnode. If `vid` is not between current node and its successor, return
+    /// an action to describe how to find the responsible node.
+    /// The `signer` is the verified signer of operation, which is checked by
+    /// [VirtualNode::authorize] before operating. A [VNodeOperation::Repair] from a replica
+    /// holder (see [PeerRing::is_replica_holder]) is checked against the signer carried by
+    /// vnode instead, and isn't checked at all if the vnode is unowned.
+    pub async fn vnode_operate_replica(
+        &self,
+        vid: Did,
+        op: VNodeOperation,
+        signer: Did,
+    ) -> Result<PeerRingAction> {
+        match self.find_successor(vid)? {
+            // `vnode` should be on current node.
+            PeerRingAction::Some(_) => {
+                let this = match self.storage.get(&vid.to_string()).await {
+                    Ok(Some(this)) if !this.is_expired() => Ok(this),
+                    _ => op.clone().gen_default_vnode(),
+                }?;
+                match &op {
+                    // Replicas handed over by neighbours are not written by them. Owned ones
+                    // still have to be written by owner or writers.
+                    VNodeOperation::Repair(vnode) if self.is_replica_holder(signer)? => {
+                        if this.owner.is_some() || vnode.owner.is_some() {
+                            this.authorize(&op, vnode.signer.unwrap_or(signer))?;
+                        }
+                    }
+                    _ => this.authorize(&op, signer)?,
+                }
+                let op = op.signed_by(signer);
+                let vnode = this.operate(op.clone())?;
+                self.storage.put(&vid.to_string(), &vnode).await?;
+                self.expiries
+                    .insert(ExpiryStore::Storage, &vid.to_string(), &vnode)?;
+                // Only the primary replica pushes data to subscribers, to avoid duplication.
+                if vid == vnode.did {
+                    Ok(push_to_subscribers(&vnode, &op))
+                } else {
+                    Ok(PeerRingAction::None)
+                }
+            }
+            // `vnode` should be on other nodes.
+            // Return an action to describe how to store it.
+            PeerRingAction::RemoteAction(n, RemoteAction::FindSuccessor(_)) => {
+                if vid == op.did()? {
+                    Ok(PeerRingAction::RemoteAction(
+                        n,
+                        RemoteAction::FindVNodeForOperate(op),
+                    ))
+                } else {
+                    Ok(PeerRingAction::RemoteAction(
+                        n,
+                        RemoteAction::FindReplicaForOperate(vid, op),
+                    ))
+                }
+            }
+            a => Err(Error::PeerRingUnexpectedAction(a)),
+        }
+    }
 }
 
 impl Chord<PeerRingAction> for PeerRing {
@@ -389,14 +606,15 @@ impl<const REDUNDANT: u16> ChordStorage<PeerRingAction, REDUNDANT> for PeerRing
     /// Always finds resource by finger table, ignoring the local cache.
     /// If the `vid` is between current node and its successor, its resource should be
     /// stored in current node.
+    /// All the `REDUNDANT` replicas are queried, so that the caller can pick the latest one.
     async fn vnode_lookup(&self, vid: Did) -> Result<PeerRingAction> {
         let mut ret = vec![];
         for vid in vid.rotate_affine(REDUNDANT) {
             let maybe_act = match self.find_successor(vid) {
                 // Resource should be stored in current node.
                 Ok(PeerRingAction::Some(succ)) => match self.storage.get(&vid.to_string()).await {
-                    Ok(Some(v)) => Ok(PeerRingAction::SomeVNode(v)),
-                    Ok(None) => {
+                    Ok(Some(v)) if !v.is_expired() => Ok(PeerRingAction::SomeVNode(v)),
+                    Ok(_) => {
                         tracing::debug!(
                             "Cannot find vnode in local storage, try to query from successor"
                         );
@@ -422,13 +640,8 @@ impl<const REDUNDANT: u16> ChordStorage<PeerRingAction, REDUNDANT> for PeerRing
                 Err(e) => Err(e),
             };
             if let Ok(act) = maybe_act {
-                if act.is_remote() {
-                    ret.push(act.clone());
-                } else {
-                    // If found vnode, break and return directly
-                    if act.is_some_vnode() {
-                        return Ok(act);
-                    }
+                if act.is_remote() || act.is_some_vnode() {
+                    ret.push(act);
                 }
             }
         }
@@ -438,40 +651,51 @@ impl<const REDUNDANT: u16> ChordStorage<PeerRingAction, REDUNDANT> for PeerRing
     /// Handle [VNodeOperation] if the target vnode between current node and the
     /// successor of current node, otherwise find the responsible node and return
     /// as Action.
+    /// The operation is applied to all the `REDUNDANT` replicas, except that
+    /// [VNodeOperation::CompareAndSwap] is decided by the primary replica only. The caller
+    /// should repair other replicas with the result of it, to avoid partial writes.
+    /// The operation is applied by current node, see [VirtualNode::authorize].
+    /// Returns [Error::VNodeVersionConflict] if any local replica rejected a
+    /// [VNodeOperation::CompareAndSwap], or [Error::VNodePermissionDenied] if the
+    /// operation is not allowed.
     async fn vnode_operate(&self, op: VNodeOperation) -> Result<PeerRingAction> {
         let vid = op.did()?;
         let mut ret = vec![];
-        for vid in vid.rotate_affine(REDUNDANT) {
-            let maybe_act = match self.find_successor(vid) {
-                // `vnode` should be on current node.
-                Ok(PeerRingAction::Some(_)) => {
-                    let this = if let Ok(Some(this)) = self.storage.get(&vid.to_string()).await {
-                        Ok(this)
-                    } else {
-                        op.clone().gen_default_vnode()
-                    }?;
-                    let vnode = this.operate(op.clone())?;
-                    self.storage.put(&vid.to_string(), &vnode).await?;
-                    Ok(PeerRingAction::None)
-                }
-                // `vnode` should be on other nodes.
-                // Return an action to describe how to store it.
-                Ok(PeerRingAction::RemoteAction(n, RemoteAction::FindSuccessor(_))) => Ok(
-                    PeerRingAction::RemoteAction(n, RemoteAction::FindVNodeForOperate(op.clone())),
-                ),
-                Ok(a) => Err(Error::PeerRingUnexpectedAction(a)),
-                Err(e) => Err(e),
-            };
-            if let Ok(act) = maybe_act {
-                if act.is_remote() {
-                    ret.push(act);
-                }
+        let replicas = match op {
+            VNodeOperation::CompareAndSwap(..) => vec![vid],
+            _ => vid.rotate_affine(REDUNDANT),
+        };
+        for vid in replicas {
+            match self.vnode_operate_replica(vid, op.clone(), self.did).await {
+                Ok(act) if act.is_remote() || act.is_multi() => ret.push(act),
+                Err(e @ Error::VNodeVersionConflict(..)) => return Err(e),
+                Err(e @ Error::VNodePermissionDenied(..)) => return Err(e),
+                _ => {}
             }
         }
         Ok(ret.into())
     }
 }
 
+/// Generate actions for pushing new data of an extend or touch operation to subscribers.
+fn push_to_subscribers(vnode: &VirtualNode, op: &VNodeOperation) -> PeerRingAction {
+    let data = match op {
+        VNodeOperation::Extend(other) | VNodeOperation::Touch(other) => &other.data,
+        _ => return PeerRingAction::None,
+    };
+    vnode
+        .subscribers
+        .iter()
+        .map(|sub| {
+            PeerRingAction::RemoteAction(
+                *sub,
+                RemoteAction::PushToSubscriber(vnode.did, data.clone()),
+            )
+        })
+        .collect::<Vec<_>>()
+        .into()
+}
+
 #[cfg_attr(feature = "wasm", async_trait(?Send))]
 #[cfg_attr(not(feature = "wasm"), async_trait)]
 impl ChordStorageSync<PeerRingAction> for PeerRing {
@@ -479,7 +703,7 @@ impl ChordStorageSync<PeerRingAction> for PeerRing {
     /// `VirtualNode`s that are no longer between current node and `new_successor`,
     /// and sync them to the new successor.
     async fn sync_vnode_with_successor(&self, new_successor: Did) -> Result<PeerRingAction> {
-        let mut data = Vec::<VirtualNode>::new();
+        let mut data = Vec::<(Did, VirtualNode)>::new();
         let all_items: Vec<(String, VirtualNode)> = self.storage.get_all().await?;
 
         // Pop out all items that are not between current node and `new_successor`.
@@ -488,7 +712,7 @@ impl ChordStorageSync<PeerRingAction> for PeerRing {
             if self.bias(vid) > self.bias(new_successor)
                 && self.storage.remove(vid_str).await.is_ok()
             {
-                data.push(vnode.clone());
+                data.push((vid, vnode.clone()));
             }
         }
 
@@ -507,13 +731,22 @@ impl ChordStorageSync<PeerRingAction> for PeerRing {
 #[cfg_attr(not(feature = "wasm"), async_trait)]
 impl ChordStorageCache<PeerRingAction> for PeerRing {
     /// Cache fetched `vnode` locally.
+    /// If a newer version of the vnode is already cached, keep the cached one.
     async fn local_cache_put(&self, vnode: VirtualNode) -> Result<()> {
-        self.cache.put(&vnode.did.to_string(), &vnode).await
+        let key = vnode.did.to_string();
+        if let Some(cached) = self.cache.get(&key).await? {
+            if cached.is_newer_than(&vnode) {
+                return Ok(());
+            }
+        }
+        self.cache.put(&key, &vnode).await?;
+        self.expiries.insert(ExpiryStore::Cache, &key, &vnode)
     }
 
-    /// Get vnode from local cache.
+    /// Get vnode from local cache, expired vnode is ignored.
     async fn local_cache_get(&self, vid: Did) -> Result<Option<VirtualNode>> {
-        self.cache.get(&vid.to_string()).await
+        let vnode: Option<VirtualNode> = self.cache.get(&vid.to_string()).await?;
+        Ok(vnode.filter(|v| !v.is_expired()))
     }
 }
 
@@ -635,9 +868,145 @@ mod tests {
     use std::str::FromStr;
 
     use super::*;
+    use crate::dht::subring::Subring;
     use crate::ecc::SecretKey;
     use crate::tests::default::gen_sorted_dht;
 
+    #[tokio::test]
+    async fn test_gc_expired_vnodes() -> Result<()> {
+        let did = SecretKey::random().address().into();
+        let node = PeerRing::new_with_storage(did, 3, Box::new(MemStorage::new()));
+
+        let alive: VirtualNode = "alive".to_string().try_into()?;
+        let expired = VirtualNode {
+            expires_at_ms: Some(1),
+            ..VirtualNode::try_from("expired".to_string())?
+        };
+        for vnode in [&alive, &expired] {
+            <PeerRing as ChordStorage<_, 1>>::vnode_operate(
+                &node,
+                VNodeOperation::Overwrite(vnode.clone()),
+            )
+            .await?;
+            node.local_cache_put(vnode.clone()).await?;
+        }
+
+        // Expired vnode is invisible even before gc.
+        let act = <PeerRing as ChordStorage<_, 1>>::vnode_lookup(&node, expired.did).await?;
+        assert_eq!(act, PeerRingAction::None);
+        assert!(node.local_cache_get(expired.did).await?.is_none());
+
+        assert_eq!(node.gc_expired_vnodes().await?, 2);
+        assert_eq!(node.storage.count().await?, 1);
+        assert_eq!(node.cache.count().await?, 1);
+        assert!(node.local_cache_get(alive.did).await?.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_closest_preceding_nodes() -> Result<()> {
+        let a = Did::from_str("0x00E807fcc88dD319270493fB2e822e388Fe36ab0").unwrap();
+        let b = Did::from_str("0x119999cf1046e68e36E1aA2E0E07105eDDD1f08E").unwrap();
+        let c = Did::from_str("0xccffee254729296a45a3885639AC7E10F9d54979").unwrap();
+        let d = Did::from_str("0xffffee254729296a45a3885639AC7E10F9d54979").unwrap();
+
+        let node_a = PeerRing::new_with_storage(a, 3, Box::new(MemStorage::new()));
+        node_a.join(b)?;
+        node_a.join(c)?;
+
+        // b and c both precede d, c is closer to it.
+        assert_eq!(node_a.closest_preceding_nodes(d, 3)?, vec![c, b]);
+        assert_eq!(node_a.closest_preceding_nodes(d, 1)?, vec![c]);
+        // Only b precedes c, and nothing precedes b.
+        assert_eq!(node_a.closest_preceding_nodes(c, 3)?, vec![b]);
+        assert!(node_a.closest_preceding_nodes(b, 3)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_subring_relay_successor() -> Result<()> {
+        let a = Did::from_str("0x00E807fcc88dD319270493fB2e822e388Fe36ab0").unwrap();
+        let b = Did::from_str("0x119999cf1046e68e36E1aA2E0E07105eDDD1f08E").unwrap();
+        let c = Did::from_str("0xccffee254729296a45a3885639AC7E10F9d54979").unwrap();
+
+        let node_a = PeerRing::new_with_storage(a, 3, Box::new(MemStorage::new()));
+        node_a.join(b)?;
+        node_a.join(c)?;
+
+        // Without local view, the message is relayed by global routing.
+        let name = "subring";
+        assert!(node_a.subring_find_successor(name, b).is_err());
+        assert_eq!(
+            node_a.subring_relay_successor(name, b)?,
+            PeerRingAction::Some(b)
+        );
+
+        // With local view, only members are chosen.
+        let mut subring = Subring::new(name, a)?;
+        subring.members = vec![a, c];
+        node_a
+            .subrings
+            .insert(name.to_string(), SubringView::new(&subring, a));
+        assert_eq!(
+            node_a.subring_relay_successor(name, b)?,
+            PeerRingAction::Some(c)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_repair_by_replica_holder() -> Result<()> {
+        let did = SecretKey::random().address().into();
+        let node = PeerRing::new_with_storage(did, 3, Box::new(MemStorage::new()));
+        let owner = SecretKey::random().address().into();
+        let holder = SecretKey::random().address().into();
+        *node.lock_predecessor()? = Some(holder);
+        assert!(node.is_replica_holder(holder)?);
+
+        let vnode: VirtualNode = "test0".to_string().try_into()?;
+        let vid = vnode.did;
+        let owned = VirtualNode {
+            owner: Some(owner),
+            ..vnode.clone()
+        };
+        node.vnode_operate_replica(vid, VNodeOperation::Overwrite(owned.clone()), owner)
+            .await?;
+
+        // A holder can't overwrite an owned vnode by repair.
+        let forged = VirtualNode {
+            version: 10,
+            ..owned.clone()
+        };
+        let op = VNodeOperation::Repair(forged.clone());
+        assert!(matches!(
+            node.vnode_operate_replica(vid, op, holder).await,
+            Err(Error::VNodePermissionDenied(_))
+        ));
+
+        // But it can hand over the one written by owner.
+        let op = VNodeOperation::Repair(VirtualNode {
+            signer: Some(owner),
+            ..forged
+        });
+        node.vnode_operate_replica(vid, op, holder).await?;
+        let stored: VirtualNode = node.storage.get(&vid.to_string()).await?.unwrap();
+        assert_eq!(stored.version, 10);
+
+        // And an unowned one.
+        let vid = VirtualNode::gen_did("test1")?;
+        let op = VNodeOperation::Repair(VirtualNode {
+            did: vid,
+            version: 10,
+            ..vnode
+        });
+        node.vnode_operate_replica(vid, op, holder).await?;
+        assert!(node.storage.get(&vid.to_string()).await?.is_some());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_chord_finger() -> Result<()> {
         // Setup did a, b, c, d in a clockwise order.
@@ -0,0 +1,89 @@
+#![warn(missing_docs)]
+//! Index of expiring virtual nodes.
+//!
+//! A [VirtualNode] with [VirtualNode::expires_at_ms] is indexed by its expiry when it's put into
+//! local storage or cache by [PeerRing](super::PeerRing), so that garbage collection only visits
+//! the expired ones instead of scanning all. A vnode is indexed again when its expiry changes,
+//! so it should be checked before removing.
+use std::collections::BTreeSet;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+use crate::dht::vnode::VirtualNode;
+use crate::error::Error;
+use crate::error::Result;
+
+/// Where an indexed virtual node is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExpiryStore {
+    /// Local storage of [PeerRing](super::PeerRing).
+    Storage,
+    /// Local cache of [PeerRing](super::PeerRing).
+    Cache,
+}
+
+/// Keys of virtual nodes indexed by expiry.
+#[derive(Default)]
+pub struct ExpiryIndex {
+    entries: Mutex<BTreeSet<(u64, ExpiryStore, String)>>,
+    loaded: AtomicBool,
+}
+
+impl ExpiryIndex {
+    /// Index vnode put into store under key, if it expires.
+    pub fn insert(&self, store: ExpiryStore, key: &str, vnode: &VirtualNode) -> Result<()> {
+        let Some(expires_at_ms) = vnode.expires_at_ms else {
+            return Ok(());
+        };
+        self.entries
+            .lock()
+            .map_err(|_| Error::DHTSyncLockError)?
+            .insert((expires_at_ms, store, key.to_string()));
+        Ok(())
+    }
+
+    /// Remove and return the keys indexed as expired at `now`.
+    pub fn take_expired(&self, now: u64) -> Result<Vec<(ExpiryStore, String)>> {
+        let mut entries = self.entries.lock().map_err(|_| Error::DHTSyncLockError)?;
+        let alive = entries.split_off(&(now, ExpiryStore::Storage, String::new()));
+        let expired = std::mem::replace(&mut *entries, alive);
+        Ok(expired.into_iter().map(|(_, s, key)| (s, key)).collect())
+    }
+
+    /// Returns `true` only the first time it's called, when the virtual nodes persisted
+    /// before starting should be indexed.
+    pub fn should_load(&self) -> bool {
+        !self.loaded.swap(true, Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_expired() -> Result<()> {
+        let index = ExpiryIndex::default();
+        for (key, expires_at_ms) in [("a", Some(1)), ("b", Some(3)), ("c", None)] {
+            let vnode = VirtualNode {
+                expires_at_ms,
+                ..VirtualNode::try_from(key.to_string())?
+            };
+            index.insert(ExpiryStore::Storage, key, &vnode)?;
+        }
+
+        assert_eq!(index.take_expired(2)?, vec![(
+            ExpiryStore::Storage,
+            "a".to_string()
+        )]);
+        assert!(index.take_expired(2)?.is_empty());
+        // Not expired until the time passed.
+        assert!(index.take_expired(3)?.is_empty());
+        assert_eq!(index.take_expired(4)?, vec![(
+            ExpiryStore::Storage,
+            "b".to_string()
+        )]);
+        Ok(())
+    }
+}
@@ -1,4 +1,11 @@
 #![warn(missing_docs)]
+//! Finger table with proximity neighbor selection.
+//!
+//! Any node in range `[did + 2^k, did + 2^(k+1))` is a valid finger `k`, routing still takes
+//! `O(log n)` hops whichever is chosen. When several nodes fall in the range, the one with
+//! the lowest measured RTT is preferred, so lookups don't route through far away nodes for
+//! nearby targets. Without RTT measured, the closest one is chosen like classic Chord.
+use std::collections::HashMap;
 use std::ops::Index;
 
 use derivative::Derivative;
@@ -18,6 +25,10 @@ pub struct FingerTable {
     finger: Vec<Option<Did>>,
     #[derivative(PartialEq = "ignore")]
     pub(super) fix_finger_index: u8,
+    /// Measured RTT of nodes in milliseconds, which is local to current node.
+    #[serde(skip)]
+    #[derivative(PartialEq = "ignore")]
+    rtt_ms: HashMap<Did, u64>,
 }
 
 impl FingerTable {
@@ -28,6 +39,7 @@ impl FingerTable {
             size,
             finger: vec![None; size],
             fix_finger_index: 0,
+            rtt_ms: HashMap::new(),
         }
     }
 
@@ -78,14 +90,54 @@ impl FingerTable {
         self.finger[index] = Some(did);
     }
 
-    /// setter for fix_finger_index
+    /// setter for fix_finger_index.
+    /// A finger in range with lower RTT than did is kept.
     pub fn set_fix(&mut self, did: Did) {
         let index = self.fix_finger_index as usize;
+        if let Some(current) = self.get(index) {
+            if self.in_range(index, current) && !self.prefer(index, did, current) {
+                return;
+            }
+        }
         self.set(index, did)
     }
 
+    /// Record the measured RTT of a node, then reconsider it for the fingers it falls in.
+    pub fn record_rtt(&mut self, did: Did, rtt_ms: u64) {
+        self.rtt_ms.insert(did, rtt_ms);
+        self.join(did);
+    }
+
+    /// Get the measured RTT of a node.
+    pub fn rtt(&self, did: Did) -> Option<u64> {
+        self.rtt_ms.get(&did).copied()
+    }
+
+    /// Returns `true` if did is in range `[self + 2^k, self + 2^(k+1))` of finger `k`.
+    /// The did is assumed to be after `self + 2^k`.
+    fn in_range(&self, k: usize, did: Did) -> bool {
+        k + 1 >= self.size
+            || did.bias(self.did).pos() < Did::from(BigUint::from(2u16).pow(k as u32 + 1))
+    }
+
+    /// Returns `true` if did is preferred to current for finger `k`. Nodes in range are
+    /// preferred, and among them the one with lowest RTT. Otherwise the closest is preferred.
+    fn prefer(&self, k: usize, did: Did, current: Did) -> bool {
+        let (bias, current_bias) = (did.bias(self.did), current.bias(self.did));
+        match (self.in_range(k, did), self.in_range(k, current)) {
+            (true, true) => {
+                let rtt = |v: Did| self.rtt(v).unwrap_or(u64::MAX);
+                (rtt(did), bias) < (rtt(current), current_bias)
+            }
+            (true, false) => true,
+            (false, true) => false,
+            (false, false) => bias < current_bias,
+        }
+    }
+
     /// remove a node from dht finger table
     pub fn remove(&mut self, did: Did) {
+        self.rtt_ms.remove(&did);
         let indexes: Vec<usize> = self
             .finger
             .iter()
@@ -128,7 +180,7 @@ impl FingerTable {
             }
 
             if let Some(v) = self.finger[k as usize] {
-                if bias > v.bias(self.did) {
+                if v != did && !self.prefer(k as usize, did, v) {
                     continue;
                 }
             }
@@ -309,6 +361,36 @@ mod test {
         assert_eq!(table.finger.len(), 3);
     }
 
+    #[test]
+    fn test_finger_table_prefer_lower_rtt() {
+        let did = Did::from(BigUint::from(0u16));
+        let near = Did::from(BigUint::from(2u16).pow(100) + BigUint::from(1u16));
+        let far = Did::from(BigUint::from(2u16).pow(100) + BigUint::from(2u16).pow(99));
+
+        // Both fall in range of finger 100 only, the closer one is chosen without RTT.
+        let mut table = FingerTable::new(did, 160);
+        table.join(far);
+        table.join(near);
+        assert_eq!(table.get(99), Some(near));
+        assert_eq!(table.get(100), Some(near));
+
+        // The node with lower RTT is preferred in range, but not out of range.
+        table.record_rtt(near, 50);
+        table.record_rtt(far, 10);
+        assert_eq!(table.get(99), Some(near));
+        assert_eq!(table.get(100), Some(far));
+        assert_eq!(table.get(101), None);
+
+        // Fixing finger doesn't replace the nearer one in range.
+        table.fix_finger_index = 100;
+        table.set_fix(near);
+        assert_eq!(table.get(100), Some(far));
+
+        // Prefer the closer one again if it gets faster.
+        table.record_rtt(near, 5);
+        assert_eq!(table.get(100), Some(near));
+    }
+
     #[test]
     fn test_finger_table_remove_then_fill() {
         let dids = gen_ordered_dids(6);
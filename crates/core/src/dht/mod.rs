@@ -5,8 +5,10 @@
 
 mod chord;
 pub mod did;
+mod expiry;
 /// Finger table for Rings
 pub mod finger;
+mod replica;
 mod stabilization;
 /// Implement Subring with VNode
 pub mod subring;
@@ -21,7 +23,11 @@ pub use chord::RemoteAction as PeerRingRemoteAction;
 pub use chord::TopoInfo;
 pub use chord::VNodeStorage;
 pub use did::Did;
+pub use expiry::ExpiryIndex;
+pub use expiry::ExpiryStore;
 pub use finger::FingerTable;
+pub use replica::ReplicaReads;
+pub use replica::StaleReplica;
 pub use stabilization::Stabilization;
 pub use stabilization::TStabilize;
 pub use successor::SuccessorReader;
@@ -0,0 +1,234 @@
+#![warn(missing_docs)]
+//! Read repair of replicated virtual nodes.
+//!
+//! A virtual node is stored at each of its affine positions, see [Did::rotate_affine].
+//! When fetching it, every replica is searched separately, and the replies are collected
+//! by [ReplicaReads]. Once a newer version is reported, every replica that reported an older
+//! one is stale, and should be repaired with the latest one by [VNodeOperation::Repair].
+//!
+//! A [VNodeOperation::CompareAndSwap] is decided by the primary replica alone, so that
+//! replicas can't accept different writes. The writer tracks it by [ReplicaReads::track_swap],
+//! and repairs the other replicas with the result reported by the primary.
+//!
+//! [VNodeOperation::Repair]: crate::dht::vnode::VNodeOperation::Repair
+//! [VNodeOperation::CompareAndSwap]: crate::dht::vnode::VNodeOperation::CompareAndSwap
+use dashmap::DashMap;
+use uuid::Uuid;
+
+use crate::consts::VNODE_FETCH_TIMEOUT_MS;
+use crate::dht::vnode::VirtualNode;
+use crate::dht::Did;
+use crate::utils::get_epoch_ms;
+
+/// Replies collected for fetching a virtual node.
+struct ReplicaRead {
+    started_at_ms: u128,
+    /// The latest virtual node reported, with the node reported it.
+    latest: Option<(VirtualNode, Did)>,
+    /// The replica positions that reported.
+    replicas: Vec<Did>,
+}
+
+/// A stale replica found by [ReplicaReads::record].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleReplica {
+    /// The replica position.
+    pub vid: Did,
+    /// The latest virtual node to repair it with.
+    pub latest: VirtualNode,
+    /// The node reported the latest virtual node, which signs the repair of a local replica.
+    pub reporter: Did,
+}
+
+/// A compare-and-swap sent to a remote primary replica.
+struct ReplicaSwap {
+    started_at_ms: u128,
+    /// The virtual node written.
+    vnode: VirtualNode,
+    /// The other replica positions to repair once the primary accepted it.
+    replicas: Vec<Did>,
+}
+
+/// Replies of replicas collected for the virtual nodes being fetched or swapped.
+/// Reads, searches and swaps are dropped after [VNODE_FETCH_TIMEOUT_MS].
+#[derive(Default)]
+pub struct ReplicaReads {
+    /// The replica position searched by each `SearchVNode`, indexed by tx_id.
+    searches: DashMap<Uuid, (Did, u128)>,
+    /// Reads in progress, indexed by did of virtual node.
+    reads: DashMap<Did, ReplicaRead>,
+    /// Swaps waiting for the primary replica, indexed by tx_id.
+    swaps: DashMap<Uuid, ReplicaSwap>,
+}
+
+impl ReplicaReads {
+    /// Drop the reads, searches and swaps timed out.
+    fn purge(&self) {
+        let now = get_epoch_ms();
+        let timeout = VNODE_FETCH_TIMEOUT_MS as u128;
+        self.searches
+            .retain(|_, (_, started_at_ms)| now.saturating_sub(*started_at_ms) < timeout);
+        self.reads
+            .retain(|_, read| now.saturating_sub(read.started_at_ms) < timeout);
+        self.swaps
+            .retain(|_, swap| now.saturating_sub(swap.started_at_ms) < timeout);
+    }
+
+    /// Start collecting replies of replicas for virtual node `did`.
+    /// The replies collected by previous read of it are dropped.
+    pub fn start(&self, did: Did) {
+        self.purge();
+        self.reads.insert(did, ReplicaRead {
+            started_at_ms: get_epoch_ms(),
+            latest: None,
+            replicas: vec![],
+        });
+    }
+
+    /// Remember that the search with tx_id is sent to the replica at `vid`.
+    pub fn track_search(&self, tx_id: Uuid, vid: Did) {
+        self.searches.insert(tx_id, (vid, get_epoch_ms()));
+    }
+
+    /// Returns the replica position searched by tx_id, if it's tracked.
+    pub fn take_search(&self, tx_id: &Uuid) -> Option<Did> {
+        self.searches.remove(tx_id).map(|(_, (vid, _))| vid)
+    }
+
+    /// Remember that the compare-and-swap with tx_id writes vnode to the primary replica, and
+    /// the other `replicas` should be repaired once it's accepted.
+    pub fn track_swap(&self, tx_id: Uuid, vnode: VirtualNode, replicas: Vec<Did>) {
+        self.purge();
+        self.swaps.insert(tx_id, ReplicaSwap {
+            started_at_ms: get_epoch_ms(),
+            vnode,
+            replicas,
+        });
+    }
+
+    /// Returns the other replica positions to repair with vnode, if it's reported by the
+    /// primary replica as the result of the compare-and-swap with tx_id.
+    pub fn take_swap(&self, tx_id: &Uuid, vnode: &VirtualNode) -> Vec<Did> {
+        // The result keeps what's written, with version and subscribers of the primary.
+        self.swaps
+            .remove_if(tx_id, |_, swap| {
+                swap.vnode.did == vnode.did && swap.vnode.data == vnode.data
+            })
+            .map(|(_, swap)| swap.replicas)
+            .unwrap_or_default()
+    }
+
+    /// Record vnode reported by `reporter` for the replica at `vid`.
+    /// Returns the replicas found stale by now.
+    /// Nothing is returned if the read of vnode is not started or already timed out.
+    pub fn record(&self, vid: Did, vnode: VirtualNode, reporter: Did) -> Vec<StaleReplica> {
+        let Some(mut read) = self.reads.get_mut(&vnode.did) else {
+            return vec![];
+        };
+        let stale = match &read.latest {
+            Some((latest, by)) if latest.is_newer_than(&vnode) => vec![StaleReplica {
+                vid,
+                latest: latest.clone(),
+                reporter: *by,
+            }],
+            Some((latest, _)) if vnode.is_newer_than(latest) => read
+                .replicas
+                .iter()
+                .map(|replica| StaleReplica {
+                    vid: *replica,
+                    latest: vnode.clone(),
+                    reporter,
+                })
+                .collect(),
+            _ => vec![],
+        };
+        if read
+            .latest
+            .as_ref()
+            .map_or(true, |(l, _)| vnode.is_newer_than(l))
+        {
+            read.latest = Some((vnode, reporter));
+        }
+        read.replicas.push(vid);
+        stale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dht::vnode::VNodeType;
+    use crate::ecc::SecretKey;
+    use crate::message::Encoder;
+
+    fn vnode(did: Did, data: &str, version: u64) -> VirtualNode {
+        VirtualNode {
+            did,
+            data: vec![data.encode().unwrap()],
+            kind: VNodeType::Data,
+            subscribers: vec![],
+            version,
+            signer: None,
+            owner: None,
+            writers: vec![],
+            expires_at_ms: None,
+        }
+    }
+
+    fn stale(vid: Did, latest: VirtualNode, reporter: Did) -> StaleReplica {
+        StaleReplica {
+            vid,
+            latest,
+            reporter,
+        }
+    }
+
+    #[test]
+    fn test_record_replicas() {
+        let did: Did = SecretKey::random().address().into();
+        let replicas = did.rotate_affine(3);
+        let reporters = [Did::from(1u32), Did::from(2u32), Did::from(3u32)];
+        let reads = ReplicaReads::default();
+
+        // Not started.
+        assert!(reads
+            .record(replicas[0], vnode(did, "a", 1), reporters[0])
+            .is_empty());
+
+        reads.start(did);
+        assert!(reads
+            .record(replicas[0], vnode(did, "a", 1), reporters[0])
+            .is_empty());
+        // An older replica is repaired with the latest one.
+        let found = reads.record(replicas[1], vnode(did, "a", 0), reporters[1]);
+        assert_eq!(found, vec![stale(
+            replicas[1],
+            vnode(did, "a", 1),
+            reporters[0]
+        )]);
+        // A newer one makes all replicas reported before stale.
+        let found = reads.record(replicas[2], vnode(did, "b", 2), reporters[2]);
+        assert_eq!(found, vec![
+            stale(replicas[0], vnode(did, "b", 2), reporters[2]),
+            stale(replicas[1], vnode(did, "b", 2), reporters[2]),
+        ]);
+    }
+
+    #[test]
+    fn test_record_replicas_of_same_version() {
+        let did: Did = SecretKey::random().address().into();
+        let replicas = did.rotate_affine(2);
+        let (a, b) = (vnode(did, "a", 1), vnode(did, "b", 1));
+        let (newer, older) = if a.is_newer_than(&b) { (a, b) } else { (b, a) };
+
+        // Replicas diverged at the same version converge to the same one, whichever replies first.
+        for (first, second) in [(&newer, &older), (&older, &newer)] {
+            let reads = ReplicaReads::default();
+            reads.start(did);
+            let mut found = reads.record(replicas[0], first.clone(), replicas[0]);
+            found.extend(reads.record(replicas[1], second.clone(), replicas[1]));
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].latest, newer);
+        }
+    }
+}
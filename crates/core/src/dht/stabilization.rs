@@ -1,9 +1,13 @@
 //! Stabilization wait to notify predecessors and update fingersTable.
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use rings_transport::core::transport::ConnectionInterface;
 
+use crate::consts::ROUTING_STATE_SAVE_INTERVAL_MS;
+use crate::consts::VNODE_GC_INTERVAL_MS;
 use crate::dht::successor::SuccessorReader;
 use crate::dht::types::CorrectChord;
 use crate::dht::Chord;
@@ -20,7 +24,9 @@ use crate::message::MessagePayload;
 use crate::message::NotifyPredecessorSend;
 use crate::message::PayloadSender;
 use crate::message::QueryForTopoInfoSend;
+use crate::swarm::LookupMode;
 use crate::swarm::Swarm;
+use crate::utils::get_epoch_ms;
 
 /// A combination contains chord and swarm, use to run stabilize.
 /// - swarm: transports communicate with each others.
@@ -30,6 +36,8 @@ pub struct Stabilization {
     chord: Arc<PeerRing>,
     swarm: Arc<Swarm>,
     timeout: u64,
+    last_gc_ms: Arc<AtomicU64>,
+    last_save_ms: Arc<AtomicU64>,
 }
 
 /// A trait with `wait` method.
@@ -54,6 +62,46 @@ impl Stabilization {
 
         Ok(())
     }
+
+    /// Record RTT of connections to DHT, so fingers prefer the nearby nodes.
+    pub async fn record_connection_rtt(&self) -> Result<()> {
+        for (did, conn) in self.swarm.get_connections() {
+            if let Some(rtt_ms) = conn.get_stats().await.rtt_ms {
+                self.chord.record_rtt(did, rtt_ms.round() as u64)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Stabilization {
+    /// Remove expired virtual nodes from storage, at most once per [VNODE_GC_INTERVAL_MS].
+    pub async fn gc_expired_vnodes(&self) -> Result<()> {
+        let now = get_epoch_ms();
+        let last = self.last_gc_ms.load(Ordering::SeqCst) as u128;
+        if now < last + VNODE_GC_INTERVAL_MS {
+            return Ok(());
+        }
+        self.last_gc_ms.store(now as u64, Ordering::SeqCst);
+
+        let count = self.chord.gc_expired_vnodes().await?;
+        if count > 0 {
+            tracing::info!("STABILIZATION gc_expired_vnodes: {} removed", count);
+        }
+        Ok(())
+    }
+
+    /// Save routing state of swarm, at most once per [ROUTING_STATE_SAVE_INTERVAL_MS].
+    pub async fn save_routing_state(&self) -> Result<()> {
+        let now = get_epoch_ms();
+        let last = self.last_save_ms.load(Ordering::SeqCst) as u128;
+        if now < last + ROUTING_STATE_SAVE_INTERVAL_MS {
+            return Ok(());
+        }
+        self.last_save_ms.store(now as u64, Ordering::SeqCst);
+        self.swarm.save_routing_state().await
+    }
 }
 
 impl Stabilization {
@@ -63,6 +111,8 @@ impl Stabilization {
             chord: swarm.dht(),
             swarm,
             timeout,
+            last_gc_ms: Arc::new(AtomicU64::new(0)),
+            last_save_ms: Arc::new(AtomicU64::new(0)),
         }
     }
 
@@ -101,10 +151,20 @@ impl Stabilization {
     }
 
     /// Fix fingers from finger table, this is a DHT operation.
+    /// With [LookupMode::Iterative], the successor of finger is looked up by current node.
     async fn fix_fingers(&self) -> Result<()> {
         match self.chord.fix_fingers() {
             Ok(action) => match action {
                 PeerRingAction::None => Ok(()),
+                PeerRingAction::RemoteAction(
+                    _,
+                    PeerRingRemoteAction::FindSuccessorForFix(finger_did),
+                ) if self.swarm.lookup_policy().mode == LookupMode::Iterative => {
+                    tracing::debug!("STABILIZATION fix_fingers iteratively: {:?}", finger_did);
+                    let route = self.swarm.lookup_route(finger_did).await?;
+                    let evs = vec![self.swarm.connect_route(route)];
+                    self.swarm.handle_message_handler_events(&evs).await
+                }
                 PeerRingAction::RemoteAction(
                     closest_predecessor,
                     PeerRingRemoteAction::FindSuccessorForFix(finger_did),
@@ -163,6 +223,11 @@ impl Stabilization {
             tracing::error!("[stabilize] Failed on notify predecessor {:?}", e);
         }
         tracing::debug!("STABILIZATION notify_predecessor end");
+        tracing::debug!("STABILIZATION record_connection_rtt start");
+        if let Err(e) = self.record_connection_rtt().await {
+            tracing::error!("[stabilize] Failed on record connection rtt {:?}", e);
+        }
+        tracing::debug!("STABILIZATION record_connection_rtt end");
         tracing::debug!("STABILIZATION fix_fingers start");
         if let Err(e) = self.fix_fingers().await {
             tracing::error!("[stabilize] Failed on fix_finger {:?}", e);
@@ -176,6 +241,22 @@ impl Stabilization {
             );
         }
         tracing::debug!("STABILIZATION clean_unavailable_connections end");
+        tracing::debug!("STABILIZATION reconnect_peers start");
+        self.swarm.reconnect_peers().await;
+        tracing::debug!("STABILIZATION reconnect_peers end");
+        tracing::debug!("STABILIZATION lookup_joined_peers start");
+        self.swarm.lookup_joined_peers().await;
+        tracing::debug!("STABILIZATION lookup_joined_peers end");
+        tracing::debug!("STABILIZATION gc_expired_vnodes start");
+        if let Err(e) = self.gc_expired_vnodes().await {
+            tracing::error!("[stabilize] Failed on gc expired vnodes {:?}", e);
+        }
+        tracing::debug!("STABILIZATION gc_expired_vnodes end");
+        tracing::debug!("STABILIZATION save_routing_state start");
+        if let Err(e) = self.save_routing_state().await {
+            tracing::error!("[stabilize] Failed on save routing state {:?}", e);
+        }
+        tracing::debug!("STABILIZATION save_routing_state end");
         #[cfg(feature = "experimental")]
         {
             tracing::debug!("STABILIZATION correct_stabilize start");
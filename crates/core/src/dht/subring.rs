@@ -6,12 +6,14 @@ use serde::Serialize;
 use super::vnode::VNodeType;
 use super::vnode::VirtualNode;
 use super::FingerTable;
+use super::PeerRingAction;
+use super::PeerRingRemoteAction;
 use crate::dht::Did;
 use crate::error::Error;
 use crate::error::Result;
 
 /// A Subring is like a [super::PeerRing] without storage functional.
-/// Subring also have two extra fields: `name` and `creator`.
+/// Subring also have extra fields: `name`, `creator` and `members`.
 /// Subring can be stored on the a [super::PeerRing].
 /// The did of a subring is the hash of its name.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -22,6 +24,9 @@ pub struct Subring {
     pub finger: FingerTable,
     /// creator
     pub creator: Did,
+    /// dids of all members, in the order of joining
+    #[serde(default)]
+    pub members: Vec<Did>,
 }
 
 impl Subring {
@@ -32,8 +37,72 @@ impl Subring {
             name: name.to_string(),
             finger: FingerTable::new(did, 1),
             creator,
+            members: vec![],
         })
     }
+
+    /// Add did to members. Nothing will be done if it's already a member.
+    pub fn join(&mut self, did: Did) {
+        self.finger.join(did);
+        if !self.members.contains(&did) {
+            self.members.push(did);
+        }
+    }
+
+    /// Remove did from members.
+    pub fn leave(&mut self, did: Did) {
+        self.finger.remove(did);
+        self.members.retain(|m| m != &did);
+    }
+}
+
+/// The view of a [Subring] from one of its members.
+/// Like [super::PeerRing], it helps the member to route messages on a ring, but the ring
+/// only consists of members of the subring. The finger table is built on the member list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubringView {
+    /// name of subring
+    pub name: String,
+    /// did of current member
+    pub did: Did,
+    /// finger table of current member, only other members are in it
+    pub finger: FingerTable,
+}
+
+impl SubringView {
+    /// Build the view of subring for member `did`.
+    pub fn new(subring: &Subring, did: Did) -> Self {
+        let mut finger = FingerTable::new(did, 160);
+        for member in subring.members.iter().filter(|m| **m != did) {
+            finger.join(*member);
+        }
+        Self {
+            name: subring.name.clone(),
+            did,
+            finger,
+        }
+    }
+
+    /// The next member of current member on the subring.
+    pub fn successor(&self) -> Option<Did> {
+        self.finger.first()
+    }
+
+    /// Find the successor of did among members, the same as
+    /// [Chord::find_successor](super::Chord::find_successor) of [super::PeerRing].
+    pub fn find_successor(&self, did: Did) -> PeerRingAction {
+        let Some(successor) = self.successor() else {
+            return PeerRingAction::Some(self.did);
+        };
+        if did.bias(self.did) <= successor.bias(self.did) {
+            PeerRingAction::Some(successor)
+        } else {
+            PeerRingAction::RemoteAction(
+                self.finger.closest_predecessor(did),
+                PeerRingRemoteAction::FindSuccessor(did),
+            )
+        }
+    }
 }
 
 impl TryFrom<Subring> for VirtualNode {
@@ -44,6 +113,12 @@ impl TryFrom<Subring> for VirtualNode {
             did: Self::gen_did(&ring.name)?,
             data: vec![data.into()],
             kind: VNodeType::Subring,
+            subscribers: vec![],
+            version: 0,
+            signer: None,
+            owner: None,
+            writers: vec![],
+            expires_at_ms: None,
         })
     }
 }
@@ -62,3 +137,40 @@ impl TryFrom<VirtualNode> for Subring {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dht::tests::gen_ordered_dids;
+
+    #[test]
+    fn test_subring_view_find_successor() -> Result<()> {
+        let dids = gen_ordered_dids(5);
+        let mut subring = Subring::new("subring", dids[0])?;
+        for did in [dids[0], dids[2], dids[4]] {
+            subring.join(did);
+        }
+
+        let view = SubringView::new(&subring, dids[0]);
+        assert_eq!(view.successor(), Some(dids[2]));
+        assert_eq!(view.find_successor(dids[1]), PeerRingAction::Some(dids[2]));
+        assert_eq!(
+            view.find_successor(dids[3]),
+            PeerRingAction::RemoteAction(dids[2], PeerRingRemoteAction::FindSuccessor(dids[3]))
+        );
+
+        let view = SubringView::new(&subring, dids[4]);
+        assert_eq!(view.successor(), Some(dids[0]));
+        assert_eq!(
+            view.find_successor(dids[1]),
+            PeerRingAction::RemoteAction(dids[0], PeerRingRemoteAction::FindSuccessor(dids[1]))
+        );
+
+        subring.leave(dids[2]);
+        subring.leave(dids[4]);
+        let view = SubringView::new(&subring, dids[0]);
+        assert_eq!(view.successor(), None);
+        assert_eq!(view.find_successor(dids[3]), PeerRingAction::Some(dids[0]));
+        Ok(())
+    }
+}
@@ -1,10 +1,16 @@
 #![warn(missing_docs)]
 use std::cmp::max;
+use std::fmt;
 use std::str::FromStr;
 
 use num_bigint::BigUint;
+use serde::de::SeqAccess;
+use serde::de::Visitor;
+use serde::ser::SerializeStruct;
 use serde::Deserialize;
+use serde::Deserializer;
 use serde::Serialize;
+use serde::Serializer;
 
 use super::subring::Subring;
 use crate::consts::VNODE_DATA_MAX_LEN;
@@ -12,10 +18,12 @@ use crate::dht::Did;
 use crate::ecc::HashStr;
 use crate::error::Error;
 use crate::error::Result;
+use crate::message::deserialize_appended;
 use crate::message::Encoded;
 use crate::message::Encoder;
 use crate::message::MessagePayload;
 use crate::message::MessageVerificationExt;
+use crate::utils::get_epoch_ms;
 
 /// VNode Types
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -34,7 +42,7 @@ pub enum VNodeType {
 pub enum VNodeOperation {
     /// Create or update a VirtualNode
     Overwrite(VirtualNode),
-    /// Extend data to a Data type VirtualNode.
+    /// Extend data to a Data or RelayMessage type VirtualNode.
     /// This operation will create VirtualNode if it's not existed.
     Extend(VirtualNode),
     /// Extend data to a Data type VirtualNode uniquely.
@@ -43,6 +51,21 @@ pub enum VNodeOperation {
     Touch(VirtualNode),
     /// Join subring.
     JoinSubring(String, Did),
+    /// Leave subring.
+    LeaveSubring(String, Did),
+    /// Subscribe a topic, new data extended or touched to the topic will be pushed to the did.
+    /// This operation will create VirtualNode if it's not existed.
+    Subscribe(String, Did),
+    /// Unsubscribe a topic.
+    Unsubscribe(String, Did),
+    /// Replace a stale replica with a newer version of VirtualNode.
+    /// The version of the VirtualNode is kept, and nothing will be done if the replica is
+    /// not older than it.
+    Repair(VirtualNode),
+    /// Overwrite a VirtualNode only if its current version equals to the expected one,
+    /// otherwise fail with [Error::VNodeVersionConflict].
+    /// Expect version 0 to create a VirtualNode that is not existed.
+    CompareAndSwap(u64, VirtualNode),
 }
 
 /// A `VirtualNode` is a piece of data with [VNodeType] and [Did]. You can save it to
@@ -54,7 +77,12 @@ pub enum VNodeOperation {
 /// * If type value is [VNodeType::RelayMessage], it's the destination Did of
 /// message plus 1 (to ensure that the message is sent to the successor of destination),
 /// thus while destination node going online, it will sync message from its successor.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Only `did`, `data` and `kind` are known by legacy nodes. In binary formats like bincode, which
+/// identify fields by order, the other fields are appended after them, so that legacy nodes and
+/// the storage written by them can still be read. Put a list of `VirtualNode` in a message by
+/// [vnode_list].
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct VirtualNode {
     /// The did of `VirtualNode` make it unique, and can be stored and retrieved on DHT.
     pub did: Did,
@@ -62,6 +90,202 @@ pub struct VirtualNode {
     pub data: Vec<Encoded>,
     /// The type indicates how the data is encoded and how the Did is generated.
     pub kind: VNodeType,
+    /// The dids that subscribe new data of a [VNodeType::Data] `VirtualNode`.
+    pub subscribers: Vec<Did>,
+    /// The version of `VirtualNode`, increased by every [VNodeOperation] except
+    /// [VNodeOperation::Repair]. It's used to pick the latest one among replicas.
+    pub version: u64,
+    /// The did of the node which wrote the data last time.
+    pub signer: Option<Did>,
+    /// The account that owns the `VirtualNode`. If set, only the owner can overwrite it,
+    /// and only the owner or [VirtualNode::writers] can extend or touch it.
+    /// It's set when the `VirtualNode` is created, and can only be changed by the owner.
+    pub owner: Option<Did>,
+    /// The accounts allowed to extend or touch an owned `VirtualNode`.
+    pub writers: Vec<Did>,
+    /// The unix timestamp in milliseconds when `VirtualNode` expires. It never expires if not
+    /// set. Expired `VirtualNode`s are ignored by lookup and removed by garbage collection.
+    /// Extending or touching data can't expire an unowned `VirtualNode` earlier, and can't
+    /// change the expiry of an owned one unless applied by owner.
+    pub expires_at_ms: Option<u64>,
+}
+
+/// Fields of [VirtualNode] unknown by legacy nodes.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct VNodeMeta {
+    subscribers: Vec<Did>,
+    version: u64,
+    signer: Option<Did>,
+    owner: Option<Did>,
+    writers: Vec<Did>,
+    expires_at_ms: Option<u64>,
+}
+
+/// [VirtualNode] known by legacy nodes.
+#[derive(Serialize, Deserialize)]
+struct LegacyVirtualNode<D> {
+    did: Did,
+    data: D,
+    kind: VNodeType,
+}
+
+/// [VirtualNode] in binary formats, whose [VNodeMeta] is missing if written by legacy nodes.
+#[derive(Deserialize)]
+struct AppendedVirtualNode {
+    did: Did,
+    data: Vec<Encoded>,
+    kind: VNodeType,
+    #[serde(default, deserialize_with = "deserialize_appended")]
+    meta: Option<VNodeMeta>,
+}
+
+/// [VirtualNode] in human readable formats like json.
+#[derive(Serialize, Deserialize)]
+struct FlatVirtualNode {
+    did: Did,
+    data: Vec<Encoded>,
+    kind: VNodeType,
+    #[serde(flatten)]
+    meta: VNodeMeta,
+}
+
+impl VirtualNode {
+    fn meta(&self) -> VNodeMeta {
+        VNodeMeta {
+            subscribers: self.subscribers.clone(),
+            version: self.version,
+            signer: self.signer,
+            owner: self.owner,
+            writers: self.writers.clone(),
+            expires_at_ms: self.expires_at_ms,
+        }
+    }
+
+    fn with_meta(did: Did, data: Vec<Encoded>, kind: VNodeType, meta: VNodeMeta) -> Self {
+        Self {
+            did,
+            data,
+            kind,
+            subscribers: meta.subscribers,
+            version: meta.version,
+            signer: meta.signer,
+            owner: meta.owner,
+            writers: meta.writers,
+            expires_at_ms: meta.expires_at_ms,
+        }
+    }
+}
+
+impl Serialize for VirtualNode {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where S: Serializer {
+        if serializer.is_human_readable() {
+            return FlatVirtualNode {
+                did: self.did,
+                data: self.data.clone(),
+                kind: self.kind,
+                meta: self.meta(),
+            }
+            .serialize(serializer);
+        }
+        let mut state = serializer.serialize_struct("VirtualNode", 4)?;
+        state.serialize_field("did", &self.did)?;
+        state.serialize_field("data", &self.data)?;
+        state.serialize_field("kind", &self.kind)?;
+        state.serialize_field("meta", &Some(self.meta()))?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for VirtualNode {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        if deserializer.is_human_readable() {
+            let v = FlatVirtualNode::deserialize(deserializer)?;
+            return Ok(Self::with_meta(v.did, v.data, v.kind, v.meta));
+        }
+        let v = AppendedVirtualNode::deserialize(deserializer)?;
+        Ok(Self::with_meta(
+            v.did,
+            v.data,
+            v.kind,
+            v.meta.unwrap_or_default(),
+        ))
+    }
+}
+
+/// (De)serialize a list of [VirtualNode] as the last field of a message, like
+/// [FoundVNode](crate::message::FoundVNode).
+///
+/// Appending the fields unknown by legacy nodes to each [VirtualNode] would break them decoding
+/// the next one. So in binary formats, the list is written as legacy nodes know it, followed by
+/// those fields of all elements, which are ignored by legacy nodes as trailing bytes.
+pub mod vnode_list {
+    use super::*;
+
+    /// Serialize a list of [VirtualNode].
+    pub fn serialize<S>(
+        vnodes: &[VirtualNode],
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            return vnodes.serialize(serializer);
+        }
+        let legacy = vnodes
+            .iter()
+            .map(|v| LegacyVirtualNode {
+                did: v.did,
+                data: &v.data,
+                kind: v.kind,
+            })
+            .collect::<Vec<_>>();
+        let meta = vnodes.iter().map(VirtualNode::meta).collect::<Vec<_>>();
+        (legacy, Some(meta)).serialize(serializer)
+    }
+
+    /// Deserialize a list of [VirtualNode], whose appended fields are missing if sent by legacy
+    /// nodes.
+    pub fn deserialize<'de, D>(deserializer: D) -> std::result::Result<Vec<VirtualNode>, D::Error>
+    where D: Deserializer<'de> {
+        if deserializer.is_human_readable() {
+            return Vec::<VirtualNode>::deserialize(deserializer);
+        }
+        deserializer.deserialize_tuple(2, VNodeListVisitor)
+    }
+
+    struct VNodeListVisitor;
+
+    impl<'de> Visitor<'de> for VNodeListVisitor {
+        type Value = Vec<VirtualNode>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a list of virtual nodes followed by their metadata")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error>
+        where A: SeqAccess<'de> {
+            let legacy: Vec<LegacyVirtualNode<Vec<Encoded>>> = seq
+                .next_element()?
+                .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+            let mut meta = seq
+                .next_element::<Option<Vec<VNodeMeta>>>()
+                .ok()
+                .flatten()
+                .flatten()
+                .unwrap_or_default()
+                .into_iter();
+            Ok(legacy
+                .into_iter()
+                .map(|v| {
+                    VirtualNode::with_meta(v.did, v.data, v.kind, meta.next().unwrap_or_default())
+                })
+                .collect())
+        }
+    }
 }
 
 impl VirtualNode {
@@ -72,6 +296,12 @@ impl VirtualNode {
         tracing::debug!("gen_did: topic: {}, did: {:?}", topic, did);
         did
     }
+
+    /// The did of [VNodeType::RelayMessage] `VirtualNode` which keeps the unreached
+    /// messages of `destination`.
+    pub fn mailbox_did(destination: Did) -> Did {
+        (BigUint::from(destination) + BigUint::from(1u16)).into()
+    }
 }
 
 impl VNodeOperation {
@@ -79,9 +309,14 @@ impl VNodeOperation {
     pub fn did(&self) -> Result<Did> {
         Ok(match self {
             VNodeOperation::Overwrite(vnode) => vnode.did,
+            VNodeOperation::CompareAndSwap(_, vnode) => vnode.did,
             VNodeOperation::Extend(vnode) => vnode.did,
             VNodeOperation::Touch(vnode) => vnode.did,
             VNodeOperation::JoinSubring(name, _) => VirtualNode::gen_did(name)?,
+            VNodeOperation::LeaveSubring(name, _) => VirtualNode::gen_did(name)?,
+            VNodeOperation::Subscribe(topic, _) => VirtualNode::gen_did(topic)?,
+            VNodeOperation::Unsubscribe(topic, _) => VirtualNode::gen_did(topic)?,
+            VNodeOperation::Repair(vnode) => vnode.did,
         })
     }
 
@@ -89,34 +324,86 @@ impl VNodeOperation {
     pub fn kind(&self) -> VNodeType {
         match self {
             VNodeOperation::Overwrite(vnode) => vnode.kind,
+            VNodeOperation::CompareAndSwap(_, vnode) => vnode.kind,
             VNodeOperation::Extend(vnode) => vnode.kind,
             VNodeOperation::Touch(vnode) => vnode.kind,
             VNodeOperation::JoinSubring(..) => VNodeType::Subring,
+            VNodeOperation::LeaveSubring(..) => VNodeType::Subring,
+            VNodeOperation::Subscribe(..) => VNodeType::Data,
+            VNodeOperation::Unsubscribe(..) => VNodeType::Data,
+            VNodeOperation::Repair(vnode) => vnode.kind,
         }
     }
 
     /// Generate a target VirtualNode when it is not existed.
+    /// The owner and writers of VirtualNode are taken from the data operations, while
+    /// [VNodeOperation::Repair] starts from an empty VirtualNode without owner.
     pub fn gen_default_vnode(self) -> Result<VirtualNode> {
         match self {
-            VNodeOperation::JoinSubring(name, did) => Subring::new(&name, did)?.try_into(),
+            VNodeOperation::JoinSubring(name, did) | VNodeOperation::LeaveSubring(name, did) => {
+                Subring::new(&name, did)?.try_into()
+            }
+            VNodeOperation::Overwrite(vnode)
+            | VNodeOperation::CompareAndSwap(_, vnode)
+            | VNodeOperation::Extend(vnode)
+            | VNodeOperation::Touch(vnode) => Ok(VirtualNode {
+                data: vec![],
+                subscribers: vec![],
+                version: 0,
+                signer: None,
+                ..vnode
+            }),
             _ => Ok(VirtualNode {
                 did: self.did()?,
                 data: vec![],
                 kind: self.kind(),
+                subscribers: vec![],
+                version: 0,
+                signer: None,
+                owner: None,
+                writers: vec![],
+                expires_at_ms: None,
             }),
         }
     }
+
+    /// Mark the data carried by operation as written by `signer`, the verified signer of
+    /// operation message, instead of trusting the signer claimed by data.
+    pub fn signed_by(self, signer: Did) -> Self {
+        let sign = |vnode: VirtualNode| VirtualNode {
+            signer: Some(signer),
+            ..vnode
+        };
+        match self {
+            VNodeOperation::Overwrite(vnode) => VNodeOperation::Overwrite(sign(vnode)),
+            VNodeOperation::CompareAndSwap(expected, vnode) => {
+                VNodeOperation::CompareAndSwap(expected, sign(vnode))
+            }
+            VNodeOperation::Extend(vnode) => VNodeOperation::Extend(sign(vnode)),
+            VNodeOperation::Touch(vnode) => VNodeOperation::Touch(sign(vnode)),
+            op => op,
+        }
+    }
 }
 
 impl TryFrom<MessagePayload> for VirtualNode {
     type Error = Error;
     fn try_from(msg: MessagePayload) -> Result<Self> {
-        let did = BigUint::from(msg.signer()) + BigUint::from(1u16);
         let data = msg.encode()?;
         Ok(Self {
-            did: did.into(),
+            did: Self::mailbox_did(msg.transaction.destination),
             data: vec![data],
             kind: VNodeType::RelayMessage,
+            subscribers: vec![],
+            version: 0,
+            signer: Some(msg.signer()),
+            owner: None,
+            writers: vec![],
+            // The relayed message is useless after it expired.
+            expires_at_ms: Some(
+                (msg.transaction.verification().ts_ms
+                    + msg.transaction.verification().ttl_ms as u128) as u64,
+            ),
         })
     }
 }
@@ -128,6 +415,12 @@ impl TryFrom<(String, Encoded)> for VirtualNode {
             did: Self::gen_did(&topic)?,
             data: vec![e],
             kind: VNodeType::Data,
+            subscribers: vec![],
+            version: 0,
+            signer: None,
+            owner: None,
+            writers: vec![],
+            expires_at_ms: None,
         })
     }
 }
@@ -157,6 +450,20 @@ impl VirtualNode {
             .collect()
     }
 
+    /// Set the time to live of `VirtualNode`, counting from now.
+    pub fn with_ttl(self, ttl_ms: u64) -> Self {
+        Self {
+            expires_at_ms: Some(get_epoch_ms() as u64 + ttl_ms),
+            ..self
+        }
+    }
+
+    /// Returns `true` if the `VirtualNode` is expired.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at_ms
+            .map_or(false, |t| get_epoch_ms() > t as u128)
+    }
+
     /// Clone and setup with new DID
     pub fn clone_with_did(&self, did: Did) -> Self {
         let mut vnode = self.clone();
@@ -166,13 +473,25 @@ impl VirtualNode {
 
     /// The entry point of [VNodeOperation].
     /// Will dispatch to different operation handlers according to the variant.
+    /// The version of result is increased, unless the operation is [VNodeOperation::Repair].
     pub fn operate(&self, op: VNodeOperation) -> Result<Self> {
-        match op {
+        let vnode = match op {
             VNodeOperation::Overwrite(vnode) => self.overwrite(vnode),
+            VNodeOperation::CompareAndSwap(expected, vnode) => {
+                self.compare_and_swap(expected, vnode)
+            }
             VNodeOperation::Extend(vnode) => self.extend(vnode),
             VNodeOperation::Touch(vnode) => self.touch(vnode),
             VNodeOperation::JoinSubring(_, did) => self.join_subring(did),
-        }
+            VNodeOperation::LeaveSubring(_, did) => self.leave_subring(did),
+            VNodeOperation::Subscribe(_, did) => self.subscribe(did),
+            VNodeOperation::Unsubscribe(_, did) => self.unsubscribe(did),
+            VNodeOperation::Repair(vnode) => return self.repair(vnode),
+        }?;
+        Ok(Self {
+            version: self.version + 1,
+            ..vnode
+        })
     }
 
     /// Overwrite current data with new data.
@@ -187,13 +506,34 @@ impl VirtualNode {
         if self.did != other.did {
             return Err(Error::VNodeDidNotEqual);
         }
-        Ok(other)
+        let subscribers = self.merge_subscribers(&other);
+        // Keep the access control unless a new owner is given.
+        let (owner, writers) = if other.owner.is_some() {
+            (other.owner, other.writers)
+        } else {
+            (self.owner, self.writers.clone())
+        };
+        Ok(Self {
+            subscribers,
+            owner,
+            writers,
+            ..other
+        })
+    }
+
+    /// Overwrite current data with new data if current version is the expected one.
+    /// The handler of [VNodeOperation::CompareAndSwap].
+    pub fn compare_and_swap(&self, expected: u64, other: Self) -> Result<Self> {
+        if self.version != expected {
+            return Err(Error::VNodeVersionConflict(expected, self.version));
+        }
+        self.overwrite(other)
     }
 
-    /// This method is used to extend data to a Data type VirtualNode.
+    /// This method is used to extend data to a Data or RelayMessage type VirtualNode.
     /// The handler of [VNodeOperation::Extend].
     pub fn extend(&self, other: Self) -> Result<Self> {
-        if self.kind != VNodeType::Data {
+        if !matches!(self.kind, VNodeType::Data | VNodeType::RelayMessage) {
             return Err(Error::VNodeNotAppendable);
         }
         if self.kind != other.kind {
@@ -215,6 +555,12 @@ impl VirtualNode {
             did: self.did,
             data,
             kind: self.kind,
+            subscribers: self.merge_subscribers(&other),
+            version: self.version,
+            signer: other.signer,
+            owner: self.owner,
+            writers: self.writers.clone(),
+            expires_at_ms: self.merge_expiry(&other),
         })
     }
 
@@ -254,6 +600,12 @@ impl VirtualNode {
             did: self.did,
             data,
             kind: self.kind,
+            subscribers: self.merge_subscribers(&other),
+            version: self.version,
+            signer: other.signer,
+            owner: self.owner,
+            writers: self.writers.clone(),
+            expires_at_ms: self.merge_expiry(&other),
         })
     }
 
@@ -265,14 +617,405 @@ impl VirtualNode {
         }
 
         let mut subring: Subring = self.clone().try_into()?;
-        subring.finger.join(did);
+        subring.join(did);
+        subring.try_into()
+    }
+
+    /// This method is used to leave a subring.
+    /// The handler of [VNodeOperation::LeaveSubring].
+    pub fn leave_subring(&self, did: Did) -> Result<Self> {
+        if self.kind != VNodeType::Subring {
+            return Err(Error::VNodeNotJoinable);
+        }
+
+        let mut subring: Subring = self.clone().try_into()?;
+        subring.leave(did);
         subring.try_into()
     }
+
+    /// This method is used to subscribe new data of a Data type VirtualNode.
+    /// The handler of [VNodeOperation::Subscribe].
+    pub fn subscribe(&self, did: Did) -> Result<Self> {
+        if self.kind != VNodeType::Data {
+            return Err(Error::VNodeNotSubscribable);
+        }
+        let mut vnode = self.clone();
+        if !vnode.subscribers.contains(&did) {
+            vnode.subscribers.push(did);
+        }
+        Ok(vnode)
+    }
+
+    /// This method is used to unsubscribe a Data type VirtualNode.
+    /// The handler of [VNodeOperation::Unsubscribe].
+    pub fn unsubscribe(&self, did: Did) -> Result<Self> {
+        if self.kind != VNodeType::Data {
+            return Err(Error::VNodeNotSubscribable);
+        }
+        let mut vnode = self.clone();
+        vnode.subscribers.retain(|s| s != &did);
+        Ok(vnode)
+    }
+
+    /// Returns `true` if current VirtualNode should replace `other` among replicas.
+    /// The higher version wins. Replicas diverged at the same version are ordered by their
+    /// encoded bytes, so that all nodes pick the same one.
+    pub fn is_newer_than(&self, other: &Self) -> bool {
+        match self.version.cmp(&other.version) {
+            std::cmp::Ordering::Equal => {
+                bincode::serialize(self).ok() > bincode::serialize(other).ok()
+            }
+            ord => ord.is_gt(),
+        }
+    }
+
+    /// This method is used to replace a stale replica with a newer one.
+    /// The handler of [VNodeOperation::Repair].
+    pub fn repair(&self, other: Self) -> Result<Self> {
+        if self.kind != other.kind {
+            return Err(Error::VNodeKindNotEqual);
+        }
+        if self.did != other.did {
+            return Err(Error::VNodeDidNotEqual);
+        }
+        if other.is_newer_than(self) {
+            Ok(other)
+        } else {
+            Ok(self.clone())
+        }
+    }
+
+    /// Check if `signer`, the verified signer of operation message, is allowed to apply the
+    /// operation to current VirtualNode.
+    ///
+    /// * The signer carried by data operations should be the `signer` itself.
+    /// * [VNodeOperation::Overwrite] and [VNodeOperation::CompareAndSwap] of owned VirtualNode
+    /// can only be applied by owner.
+    /// * [VNodeOperation::Extend] and [VNodeOperation::Touch] of owned VirtualNode can only be
+    /// applied by owner or writers.
+    /// * Subscribing, unsubscribing, joining and leaving subring can only be applied by the did
+    /// itself.
+    /// * [VNodeOperation::Repair] of owned VirtualNode can only be applied by owner, or by writers
+    /// keeping the access control. Only the owner can repair an unowned VirtualNode into an owned
+    /// one. The signer claimed by data is not trusted.
+    pub fn authorize(&self, op: &VNodeOperation, signer: Did) -> Result<()> {
+        let allowed = match op {
+            VNodeOperation::Overwrite(vnode) | VNodeOperation::CompareAndSwap(_, vnode) => {
+                vnode.signer.map_or(true, |s| s == signer)
+                    && self.owner.map_or(true, |owner| owner == signer)
+            }
+            VNodeOperation::Extend(vnode) | VNodeOperation::Touch(vnode) => {
+                vnode.signer.map_or(true, |s| s == signer) && self.is_writer(signer)
+            }
+            VNodeOperation::JoinSubring(_, did)
+            | VNodeOperation::LeaveSubring(_, did)
+            | VNodeOperation::Subscribe(_, did)
+            | VNodeOperation::Unsubscribe(_, did) => *did == signer,
+            VNodeOperation::Repair(vnode) => match self.owner {
+                None => vnode.owner.map_or(true, |owner| owner == signer),
+                Some(owner) => {
+                    owner == signer
+                        || (self.writers.contains(&signer)
+                            && vnode.owner == self.owner
+                            && vnode.writers == self.writers)
+                }
+            },
+        };
+        if !allowed {
+            return Err(Error::VNodePermissionDenied(signer));
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the did is allowed to extend or touch current VirtualNode.
+    pub fn is_writer(&self, did: Did) -> bool {
+        match self.owner {
+            None => true,
+            Some(owner) => owner == did || self.writers.contains(&did),
+        }
+    }
+
+    /// The expiry after extending or touching data of other.
+    /// Only the owner can change the expiry of an owned VirtualNode, while others can only
+    /// postpone the expiry of an unowned one. So the data of others is never expired earlier.
+    fn merge_expiry(&self, other: &Self) -> Option<u64> {
+        if self.owner.is_some() {
+            if other.signer == self.owner {
+                return other.expires_at_ms.or(self.expires_at_ms);
+            }
+            return self.expires_at_ms;
+        }
+        match (self.expires_at_ms, other.expires_at_ms) {
+            (Some(this), Some(other)) => Some(this.max(other)),
+            (this, _) => this,
+        }
+    }
+
+    /// Union subscribers of self and other, keeping the order of self.
+    fn merge_subscribers(&self, other: &Self) -> Vec<Did> {
+        let mut subscribers = self.subscribers.clone();
+        for did in other.subscribers.iter() {
+            if !subscribers.contains(did) {
+                subscribers.push(*did);
+            }
+        }
+        subscribers
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::message::FoundVNode;
+
+    /// [VirtualNode] known by legacy nodes.
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct LegacyVNode {
+        did: Did,
+        data: Vec<Encoded>,
+        kind: VNodeType,
+    }
+
+    /// [FoundVNode] known by legacy nodes.
+    #[derive(Debug, Serialize, Deserialize)]
+    struct LegacyFoundVNode {
+        data: Vec<LegacyVNode>,
+    }
+
+    #[test]
+    fn test_vnode_legacy_layout() {
+        let vnode: VirtualNode = "test0".to_string().try_into().unwrap();
+        let legacy = LegacyVNode {
+            did: vnode.did,
+            data: vnode.data.clone(),
+            kind: vnode.kind,
+        };
+
+        // Stored or sent by legacy nodes.
+        let encoded = bincode::serialize(&legacy).unwrap();
+        assert_eq!(
+            bincode::deserialize::<VirtualNode>(&encoded).unwrap(),
+            vnode
+        );
+
+        // Received by legacy nodes.
+        let vnode = VirtualNode {
+            version: 3,
+            owner: Some(Did::from(1u32)),
+            ..vnode
+        };
+        let encoded = bincode::serialize(&vnode).unwrap();
+        assert_eq!(
+            bincode::deserialize::<LegacyVNode>(&encoded).unwrap(),
+            legacy
+        );
+        assert_eq!(
+            bincode::deserialize::<VirtualNode>(&encoded).unwrap(),
+            vnode
+        );
+
+        // A list of vnodes in message.
+        let other = VirtualNode {
+            version: 5,
+            ..VirtualNode::try_from("test1".to_string()).unwrap()
+        };
+        let msg = FoundVNode {
+            data: vec![vnode.clone(), other.clone()],
+        };
+        let encoded = bincode::serialize(&msg).unwrap();
+        let legacy_msg: LegacyFoundVNode = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(legacy_msg.data[0], legacy);
+        assert_eq!(legacy_msg.data[1].did, other.did);
+        let decoded: FoundVNode = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded.data, msg.data);
+
+        let encoded = bincode::serialize(&legacy_msg).unwrap();
+        let decoded: FoundVNode = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded.data.len(), 2);
+        assert_eq!(decoded.data[1].did, other.did);
+        assert_eq!(decoded.data[1].version, 0);
+
+        // Human readable formats keep the fields flattened.
+        let json = serde_json::to_value(&vnode).unwrap();
+        assert_eq!(json["version"], 3);
+        assert_eq!(serde_json::from_value::<VirtualNode>(json).unwrap(), vnode);
+        let json = serde_json::to_value(&legacy).unwrap();
+        assert_eq!(
+            serde_json::from_value::<VirtualNode>(json).unwrap().version,
+            0
+        );
+    }
+
+    #[test]
+    fn test_vnode_authorize() {
+        let owner = Did::from(1u32);
+        let writer = Did::from(2u32);
+        let stranger = Did::from(3u32);
+
+        let vnode: VirtualNode = "test0".to_string().try_into().unwrap();
+        let owned = VirtualNode {
+            owner: Some(owner),
+            writers: vec![writer],
+            ..vnode.clone()
+        };
+        let this = VNodeOperation::Overwrite(owned.clone())
+            .gen_default_vnode()
+            .unwrap();
+        assert_eq!(this.owner, Some(owner));
+
+        // Nobody can create a vnode owned by others.
+        let op = VNodeOperation::Overwrite(owned.clone());
+        assert!(this.authorize(&op, owner).is_ok());
+        assert!(this.authorize(&op, writer).is_err());
+        let this = this.operate(op).unwrap();
+
+        // The access control is kept when overwriting without owner.
+        let op = VNodeOperation::Overwrite(vnode.clone());
+        assert!(this.authorize(&op, owner).is_ok());
+        assert!(this.authorize(&op, writer).is_err());
+        assert!(this.authorize(&op, stranger).is_err());
+        assert_eq!(this.operate(op).unwrap().writers, vec![writer]);
+
+        // Writers can only append.
+        let op = VNodeOperation::Extend(vnode.clone());
+        assert!(this.authorize(&op, owner).is_ok());
+        assert!(this.authorize(&op, writer).is_ok());
+        assert!(this.authorize(&op, stranger).is_err());
+
+        // The signer carried by operation should be the signer of message.
+        let op = VNodeOperation::Extend(VirtualNode {
+            signer: Some(writer),
+            ..vnode.clone()
+        });
+        assert!(this.authorize(&op, writer).is_ok());
+        assert!(this.authorize(&op, stranger).is_err());
+
+        // Only the did itself can subscribe.
+        let op = VNodeOperation::Subscribe("test0".to_string(), stranger);
+        assert!(this.authorize(&op, stranger).is_ok());
+        assert!(this.authorize(&op, writer).is_err());
+
+        // Repair is authorized by the signer of message, not the signer claimed by data.
+        let forged = VirtualNode {
+            signer: Some(owner),
+            version: 10,
+            ..this.clone()
+        };
+        let op = VNodeOperation::Repair(forged);
+        assert!(this.authorize(&op, stranger).is_err());
+        assert!(this.authorize(&op, writer).is_ok());
+        assert!(this.authorize(&op, owner).is_ok());
+
+        // Writers can't change the access control by repair.
+        let op = VNodeOperation::Repair(VirtualNode {
+            writers: vec![writer, stranger],
+            version: 10,
+            ..this.clone()
+        });
+        assert!(this.authorize(&op, writer).is_err());
+        assert!(this.authorize(&op, owner).is_ok());
+    }
+
+    #[test]
+    fn test_vnode_authorize_repair_missing() {
+        let owner = Did::from(1u32);
+        let stranger = Did::from(3u32);
+
+        let vnode: VirtualNode = "test0".to_string().try_into().unwrap();
+        let forged = VirtualNode {
+            signer: Some(owner),
+            owner: Some(owner),
+            version: 10,
+            ..vnode.clone()
+        };
+
+        // Repair can't create a vnode owned by others.
+        let op = VNodeOperation::Repair(forged);
+        let this = op.clone().gen_default_vnode().unwrap();
+        assert_eq!(this.owner, None);
+        assert!(this.data.is_empty());
+        assert!(this.authorize(&op, stranger).is_err());
+        assert!(this.authorize(&op, owner).is_ok());
+
+        // Nor claim an unowned one.
+        let this = VNodeOperation::Overwrite(vnode.clone())
+            .gen_default_vnode()
+            .unwrap()
+            .operate(VNodeOperation::Overwrite(vnode.clone()))
+            .unwrap();
+        assert!(this.authorize(&op, stranger).is_err());
+
+        // An unowned vnode can be repaired by anyone, like overwriting.
+        let op = VNodeOperation::Repair(VirtualNode {
+            version: 10,
+            ..vnode
+        });
+        assert!(this.authorize(&op, stranger).is_ok());
+    }
+
+    #[test]
+    fn test_vnode_compare_and_swap() {
+        let vnode: VirtualNode = "test0".to_string().try_into().unwrap();
+        let this = VNodeOperation::Overwrite(vnode.clone())
+            .gen_default_vnode()
+            .unwrap();
+        assert_eq!(this.version, 0);
+
+        let this = this
+            .operate(VNodeOperation::CompareAndSwap(0, vnode.clone()))
+            .unwrap();
+        assert_eq!(this.version, 1);
+        assert_eq!(this.data, vnode.data);
+
+        let other: VirtualNode = ("test0".to_string(), "test1".to_string())
+            .try_into()
+            .unwrap();
+        assert!(matches!(
+            this.operate(VNodeOperation::CompareAndSwap(0, other.clone())),
+            Err(Error::VNodeVersionConflict(0, 1))
+        ));
+
+        let this = this
+            .operate(VNodeOperation::CompareAndSwap(1, other.clone()))
+            .unwrap();
+        assert_eq!(this.version, 2);
+        assert_eq!(this.data, other.data);
+    }
+
+    #[test]
+    fn test_vnode_extend_expiry() {
+        let owner = Did::from(1u32);
+        let writer = Did::from(2u32);
+        let with_expiry = |signer: Did, expires_at_ms: Option<u64>| VirtualNode {
+            signer: Some(signer),
+            expires_at_ms,
+            ..VirtualNode::try_from(("test0".to_string(), "test1".to_string())).unwrap()
+        };
+
+        // A publish can't expire an unowned topic earlier, but can postpone it.
+        let this = with_expiry(writer, Some(100));
+        let shorter = this.extend(with_expiry(writer, Some(10))).unwrap();
+        assert_eq!(shorter.expires_at_ms, Some(100));
+        let longer = this.extend(with_expiry(writer, Some(1000))).unwrap();
+        assert_eq!(longer.expires_at_ms, Some(1000));
+        let never = VirtualNode {
+            expires_at_ms: None,
+            ..this.clone()
+        };
+        let never = never.touch(with_expiry(writer, Some(10))).unwrap();
+        assert_eq!(never.expires_at_ms, None);
+
+        // Only the owner can change the expiry of an owned topic.
+        let owned = VirtualNode {
+            owner: Some(owner),
+            writers: vec![writer],
+            ..this
+        };
+        let extended = owned.extend(with_expiry(writer, Some(1000))).unwrap();
+        assert_eq!(extended.expires_at_ms, Some(100));
+        let extended = owned.extend(with_expiry(owner, Some(10))).unwrap();
+        assert_eq!(extended.expires_at_ms, Some(10));
+    }
 
     #[test]
     fn test_vnode_extend_over_max_len() {
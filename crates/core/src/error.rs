@@ -40,6 +40,18 @@ pub enum Error {
     #[error("The type of VirtualNode is not allowed to be joined as a subring")]
     VNodeNotJoinable,
 
+    #[error("The type of VirtualNode is not allowed to be subscribed")]
+    VNodeNotSubscribable,
+
+    #[error("Subring {0} is not synced, cannot route message within it")]
+    SubringNotSynced(String),
+
+    #[error("Version of VirtualNode conflicted, expected {0}, current {1}")]
+    VNodeVersionConflict(u64, u64),
+
+    #[error("Did {0} is not allowed to operate the VirtualNode")]
+    VNodePermissionDenied(crate::dht::Did),
+
     #[error("Encode a byte vector into a base58-check string, adds 4 bytes checksum")]
     Encode,
 
@@ -169,6 +181,21 @@ pub enum Error {
     #[error("Node behaviour bad")]
     NodeBehaviourBad(crate::dht::Did),
 
+    #[error("Connection limit {0} reached and no connection can be evicted")]
+    ConnectionLimitReached(usize),
+
+    #[error("Peer {0} is not admitted")]
+    PeerNotAdmitted(crate::dht::Did),
+
+    #[error("Peer {0} is not in the same network")]
+    NetworkMismatch(crate::dht::Did),
+
+    #[error("Peer {0} speaks incompatible protocol version {1}")]
+    IncompatibleProtocol(crate::dht::Did, u32),
+
+    #[error("Peer {0} does not support capability {1}")]
+    UnsupportedByPeer(crate::dht::Did, String),
+
     #[error("Cannot get transport from did: {0}")]
     SwarmMissTransport(crate::dht::Did),
 
@@ -326,6 +353,15 @@ pub enum Error {
     #[error("Failed to lock callback of swarm")]
     CallbackSyncLockError,
 
+    #[error("Callback of swarm failed: {0}")]
+    CallbackError(String),
+
+    #[error("Request {0} timeout, no reply received")]
+    RequestTimeout(String),
+
+    #[error("Lookup of {0} failed, no hop answered")]
+    LookupFailed(crate::dht::Did),
+
     #[error("Failed to build swarm: {0}")]
     SwarmBuildFailed(String),
 
@@ -360,6 +396,12 @@ pub enum Error {
     #[error("Session is expired")]
     SessionExpired,
 
+    #[error("Public key does not belong to the session")]
+    InvalidSessionPubkey,
+
+    #[error("Cannot find session public key of {0}")]
+    SessionPubkeyNotFound(crate::dht::Did),
+
     #[error("Transport error: {0}")]
     Transport(#[from] rings_transport::error::Error),
 
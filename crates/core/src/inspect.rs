@@ -1,4 +1,5 @@
 use rings_transport::core::transport::ConnectionInterface;
+use rings_transport::core::transport::ConnectionStats;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -6,6 +7,8 @@ use crate::dht::vnode::VirtualNode;
 use crate::dht::PeerRing;
 use crate::dht::SuccessorReader;
 use crate::dht::VNodeStorage;
+use crate::message::ProtocolInfo;
+use crate::swarm::ReplayStats;
 use crate::swarm::Swarm;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,12 +17,18 @@ pub struct SwarmInspect {
     pub dht: DHTInspect,
     pub persistence_storage: StorageInspect,
     pub cache_storage: StorageInspect,
+    #[serde(default)]
+    pub replays: ReplayStats,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionInspect {
     pub did: String,
     pub state: String,
+    #[serde(default)]
+    pub stats: ConnectionStats,
+    #[serde(default)]
+    pub protocol: Option<ProtocolInfo>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -42,13 +51,16 @@ impl SwarmInspect {
         let connections = {
             let connections = swarm.get_connections();
 
-            connections
-                .iter()
-                .map(|(did, c)| ConnectionInspect {
+            let mut inspects = vec![];
+            for (did, c) in connections.iter() {
+                inspects.push(ConnectionInspect {
                     did: did.to_string(),
                     state: format!("{:?}", c.ice_connection_state()),
+                    stats: c.get_stats().await,
+                    protocol: swarm.peer_protocol(*did),
                 })
-                .collect()
+            }
+            inspects
         };
         let persistence_storage = StorageInspect::inspect_kv_storage(&swarm.dht().storage).await;
         let cache_storage = StorageInspect::inspect_kv_storage(&swarm.dht().cache).await;
@@ -58,6 +70,7 @@ impl SwarmInspect {
             dht,
             persistence_storage,
             cache_storage,
+            replays: swarm.replay_stats(),
         }
     }
 }
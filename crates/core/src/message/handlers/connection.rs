@@ -3,9 +3,11 @@ use std::ops::Deref;
 use async_trait::async_trait;
 
 use super::dht;
+use crate::consts::LOOKUP_REPORT_SIZE;
 use crate::dht::types::CorrectChord;
 use crate::dht::Chord;
 use crate::dht::PeerRingAction;
+use crate::dht::SuccessorReader;
 use crate::dht::TopoInfo;
 use crate::error::Error;
 use crate::error::Result;
@@ -14,7 +16,10 @@ use crate::message::types::ConnectNodeSend;
 use crate::message::types::FindSuccessorReport;
 use crate::message::types::FindSuccessorSend;
 use crate::message::types::JoinDHT;
+use crate::message::types::LookupReport;
+use crate::message::types::LookupSend;
 use crate::message::types::Message;
+use crate::message::types::NotifyLeave;
 use crate::message::types::QueryForTopoInfoReport;
 use crate::message::types::QueryForTopoInfoSend;
 use crate::message::types::Then;
@@ -25,6 +30,7 @@ use crate::message::LeaveDHT;
 use crate::message::MessageHandler;
 use crate::message::MessageHandlerEvent;
 use crate::message::MessagePayload;
+use crate::message::MessageVerificationExt;
 
 /// QueryForTopoInfoSend is direct message
 #[cfg_attr(feature = "wasm", async_trait(?Send))]
@@ -83,6 +89,34 @@ impl HandleMsg<LeaveDHT> for MessageHandler {
     }
 }
 
+/// Drop the leaving node from DHT, and take over its neighbours to keep the ring connected.
+#[cfg_attr(feature = "wasm", async_trait(?Send))]
+#[cfg_attr(not(feature = "wasm"), async_trait)]
+impl HandleMsg<NotifyLeave> for MessageHandler {
+    async fn handle(
+        &self,
+        ctx: &MessagePayload,
+        msg: &NotifyLeave,
+    ) -> Result<Vec<MessageHandlerEvent>> {
+        if self.dht.did != ctx.relay.destination {
+            return Ok(vec![MessageHandlerEvent::ForwardPayload(ctx.clone(), None)]);
+        }
+        // The leaving node is the signer, a node cannot announce the leave of others.
+        let leaving = ctx.transaction.signer();
+        // Remove it from DHT at once, so the virtual nodes it hands over are stored here instead
+        // of being sent back. The connection is closed by the leaving node after handover, and
+        // won't be reconnected since it's no longer a neighbour.
+        self.dht.depart(leaving)?;
+        let mut events = vec![];
+        for did in msg.successors.iter().chain(msg.predecessor.iter()) {
+            if *did != self.dht.did && *did != leaving {
+                events.push(MessageHandlerEvent::Connect(*did));
+            }
+        }
+        Ok(events)
+    }
+}
+
 #[cfg_attr(feature = "wasm", async_trait(?Send))]
 #[cfg_attr(not(feature = "wasm"), async_trait)]
 impl HandleMsg<JoinDHT> for MessageHandler {
@@ -146,7 +180,11 @@ impl HandleMsg<FindSuccessorSend> for MessageHandler {
         ctx: &MessagePayload,
         msg: &FindSuccessorSend,
     ) -> Result<Vec<MessageHandlerEvent>> {
-        match self.dht.find_successor(msg.did)? {
+        let act = match &ctx.subring {
+            Some(subring) => self.dht.subring_relay_successor(subring, msg.did)?,
+            None => self.dht.find_successor(msg.did)?,
+        };
+        match act {
             PeerRingAction::Some(did) => {
                 if !msg.strict || self.dht.did == msg.did {
                     match &msg.then {
@@ -200,6 +238,57 @@ impl HandleMsg<FindSuccessorReport> for MessageHandler {
     }
 }
 
+/// Answer by local view without forwarding, the origin of lookup decides the next hop.
+#[cfg_attr(feature = "wasm", async_trait(?Send))]
+#[cfg_attr(not(feature = "wasm"), async_trait)]
+impl HandleMsg<LookupSend> for MessageHandler {
+    async fn handle(
+        &self,
+        ctx: &MessagePayload,
+        msg: &LookupSend,
+    ) -> Result<Vec<MessageHandlerEvent>> {
+        if self.dht.did != ctx.relay.destination {
+            return Ok(vec![MessageHandlerEvent::ForwardPayload(ctx.clone(), None)]);
+        }
+
+        let report = match self.dht.find_successor(msg.did)? {
+            PeerRingAction::Some(did) => LookupReport {
+                did: msg.did,
+                successor: Some(did),
+                closer: self.dht.successors().list()?,
+            },
+            PeerRingAction::RemoteAction(..) => LookupReport {
+                did: msg.did,
+                successor: None,
+                closer: self
+                    .dht
+                    .closest_preceding_nodes(msg.did, LOOKUP_REPORT_SIZE)?,
+            },
+            act => return Err(Error::PeerRingUnexpectedAction(act)),
+        };
+        Ok(vec![MessageHandlerEvent::SendReportMessage(
+            ctx.clone(),
+            Message::LookupReport(report),
+        )])
+    }
+}
+
+#[cfg_attr(feature = "wasm", async_trait(?Send))]
+#[cfg_attr(not(feature = "wasm"), async_trait)]
+impl HandleMsg<LookupReport> for MessageHandler {
+    async fn handle(
+        &self,
+        ctx: &MessagePayload,
+        _: &LookupReport,
+    ) -> Result<Vec<MessageHandlerEvent>> {
+        if self.dht.did != ctx.relay.destination {
+            Ok(vec![MessageHandlerEvent::ForwardPayload(ctx.clone(), None)])
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+
 #[cfg(not(feature = "wasm"))]
 #[cfg(test)]
 pub mod tests {
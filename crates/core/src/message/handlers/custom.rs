@@ -2,6 +2,7 @@ use async_trait::async_trait;
 
 use crate::error::Result;
 use crate::message::types::CustomMessage;
+use crate::message::types::EncryptedMessage;
 use crate::message::HandleMsg;
 use crate::message::MessageHandler;
 use crate::message::MessageHandlerEvent;
@@ -22,3 +23,19 @@ impl HandleMsg<CustomMessage> for MessageHandler {
         }
     }
 }
+
+#[cfg_attr(feature = "wasm", async_trait(?Send))]
+#[cfg_attr(not(feature = "wasm"), async_trait)]
+impl HandleMsg<EncryptedMessage> for MessageHandler {
+    async fn handle(
+        &self,
+        ctx: &MessagePayload,
+        _: &EncryptedMessage,
+    ) -> Result<Vec<MessageHandlerEvent>> {
+        if self.dht.did != ctx.relay.destination {
+            Ok(vec![MessageHandlerEvent::ForwardPayload(ctx.clone(), None)])
+        } else {
+            Ok(vec![])
+        }
+    }
+}
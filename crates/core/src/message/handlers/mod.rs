@@ -34,6 +34,8 @@ pub mod stabilization;
 pub mod storage;
 /// Operator and Handler for Subring
 pub mod subring;
+/// Handler for tracing route
+pub mod trace;
 
 type NextHop = Did;
 
@@ -74,8 +76,13 @@ pub enum MessageHandlerEvent {
     /// Instructs the swarm to send a message to a peer via the dht network with a specific next hop.
     ResetDestination(MessagePayload, Did),
 
-    /// Instructs the swarm to store vnode.
-    StorageStore(VirtualNode),
+    /// Instructs the swarm to store vnode at the replica position, on behalf of the signer.
+    StorageStore(Did, VirtualNode, Did),
+    /// Instructs the swarm to sync vnodes to the successor, paired with their replica positions.
+    SyncReplicas(Did, Vec<(Did, VirtualNode)>),
+    /// Instructs the swarm to deliver the messages kept in mailbox to callback, then acknowledge
+    /// the node keeping it.
+    ReplayMailbox(VirtualNode, Did),
     /// Notify a node
     Notify(Did),
 }
@@ -132,10 +139,22 @@ impl MessageHandler {
             Message::FoundVNode(ref msg) => self.handle(payload, msg).await,
             Message::SyncVNodeWithSuccessor(ref msg) => self.handle(payload, msg).await,
             Message::OperateVNode(ref msg) => self.handle(payload, msg).await,
+            Message::OperateReplica(ref msg) => self.handle(payload, msg).await,
+            Message::VNodeConflict(ref msg) => self.handle(payload, msg).await,
+            Message::TopicMessage(ref msg) => self.handle(payload, msg).await,
+            Message::SyncReplicaWithSuccessor(ref msg) => self.handle(payload, msg).await,
             Message::CustomMessage(ref msg) => self.handle(payload, msg).await,
+            Message::EncryptedMessage(ref msg) => self.handle(payload, msg).await,
             Message::QueryForTopoInfoSend(ref msg) => self.handle(payload, msg).await,
             Message::QueryForTopoInfoReport(ref msg) => self.handle(payload, msg).await,
             Message::Chunk(_) => Ok(vec![]),
+            Message::NotifyLeave(ref msg) => self.handle(payload, msg).await,
+            Message::SyncVNodeWithSuccessorReport(ref msg) => self.handle(payload, msg).await,
+            Message::TraceRouteSend(ref msg) => self.handle(payload, msg).await,
+            Message::TraceRouteReport(ref msg) => self.handle(payload, msg).await,
+            Message::LookupSend(ref msg) => self.handle(payload, msg).await,
+            Message::LookupReport(ref msg) => self.handle(payload, msg).await,
+            Message::MailboxAck(ref msg) => self.handle(payload, msg).await,
         }?;
 
         tracing::debug!("FINISH HANDLE MESSAGE {}", &payload.transaction.tx_id);
@@ -147,14 +166,20 @@ impl MessageHandler {
 #[cfg(test)]
 pub mod tests {
     use futures::lock::Mutex;
+    use rings_transport::core::transport::ConnectionInterface;
+    use rings_transport::core::transport::WebrtcConnectionState;
     use tokio::time::sleep;
     use tokio::time::Duration;
 
     use super::*;
     use crate::dht::Did;
+    use crate::ecc::tests::gen_ordered_keys;
     use crate::ecc::SecretKey;
+    use crate::error::Error;
+    use crate::message::handlers::connection::tests::test_only_two_nodes_establish_connection;
     use crate::message::MessageVerificationExt;
     use crate::message::PayloadSender;
+    use crate::message::SubringInterface;
     use crate::swarm::callback::SwarmCallback;
     use crate::swarm::Swarm;
     use crate::tests::default::prepare_node;
@@ -270,6 +295,277 @@ pub mod tests {
         Ok(())
     }
 
+    struct ReplyCallback {
+        swarm: Arc<Swarm>,
+    }
+
+    #[async_trait]
+    impl SwarmCallback for ReplyCallback {
+        async fn on_inbound(
+            &self,
+            payload: &MessagePayload,
+        ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+            let msg: Message = payload.transaction.data().map_err(Box::new)?;
+            let Message::CustomMessage(msg) = msg else {
+                return Ok(());
+            };
+            let mut reply = b"reply: ".to_vec();
+            reply.extend(msg.0);
+            self.swarm.reply(payload, &reply).await.map_err(Box::new)?;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_reply() -> Result<()> {
+        let node1 = prepare_node(SecretKey::random()).await;
+        let node2 = prepare_node(SecretKey::random()).await;
+
+        node2
+            .set_callback(Arc::new(ReplyCallback {
+                swarm: node2.clone(),
+            }))
+            .unwrap();
+
+        manually_establish_connection(&node1, &node2).await;
+
+        let node11 = node1.clone();
+        let node22 = node2.clone();
+        tokio::spawn(async move { node11.listen().await });
+        tokio::spawn(async move { node22.listen().await });
+
+        println!("waiting for data channel ready");
+        sleep(Duration::from_secs(5)).await;
+
+        let reply = node1
+            .request(node2.did(), b"ping", Duration::from_secs(5))
+            .await?;
+        assert_eq!(reply, b"reply: ping".to_vec());
+
+        // node1 never replies, so the request should be timeout.
+        let reply = node2
+            .request(node1.did(), b"ping", Duration::from_secs(1))
+            .await;
+        assert!(matches!(reply, Err(crate::error::Error::RequestTimeout(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_message() -> Result<()> {
+        let node1 = prepare_node(SecretKey::random()).await;
+        let node2 = prepare_node(SecretKey::random()).await;
+
+        let cb2 = Arc::new(SwarmCallbackInstance {
+            handler_messages: Mutex::new(vec![]),
+        });
+        node2.set_callback(cb2.clone()).unwrap();
+
+        manually_establish_connection(&node1, &node2).await;
+
+        let node11 = node1.clone();
+        let node22 = node2.clone();
+        tokio::spawn(async move { node11.listen().await });
+        tokio::spawn(async move { node22.listen().await });
+
+        println!("waiting for session pubkey published");
+        sleep(Duration::from_secs(5)).await;
+
+        let pubkey = node1.lookup_session_pubkey(node2.did()).await?;
+        assert_eq!(pubkey, node2.session_sk().session_pubkey());
+
+        node1
+            .send_encrypted_message(b"Hello encrypted", node2.did())
+            .await?;
+        sleep(Duration::from_secs(3)).await;
+
+        assert_eq!(cb2.handler_messages.lock().await.as_slice(), &[(
+            node1.did(),
+            b"Hello encrypted".to_vec()
+        )]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_connection_stats() -> Result<()> {
+        let node1 = prepare_node(SecretKey::random()).await;
+        let node2 = prepare_node(SecretKey::random()).await;
+
+        manually_establish_connection(&node1, &node2).await;
+
+        let node11 = node1.clone();
+        let node22 = node2.clone();
+        tokio::spawn(async move { node11.listen().await });
+        tokio::spawn(async move { node22.listen().await });
+        sleep(Duration::from_secs(3)).await;
+
+        let stats1 = node1.get_connection(node2.did()).unwrap().get_stats().await;
+        let stats2 = node2.get_connection(node1.did()).unwrap().get_stats().await;
+
+        node1
+            .send_message(Message::custom(b"Hello stats")?, node2.did())
+            .await?;
+        sleep(Duration::from_secs(1)).await;
+
+        let new_stats1 = node1.get_connection(node2.did()).unwrap().get_stats().await;
+        let new_stats2 = node2.get_connection(node1.did()).unwrap().get_stats().await;
+        assert!(new_stats1.messages_sent > stats1.messages_sent);
+        assert!(new_stats2.messages_received > stats2.messages_received);
+        assert!(new_stats1.bytes_sent > stats1.bytes_sent);
+        assert!(new_stats2.bytes_received > stats2.bytes_received);
+        assert!(new_stats1
+            .state_durations
+            .iter()
+            .any(|(state, _)| *state == WebrtcConnectionState::Connected));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subring_membership() -> Result<()> {
+        let keys = gen_ordered_keys(2);
+        let node1 = prepare_node(keys[0]).await;
+        let node2 = prepare_node(keys[1]).await;
+
+        let cb2 = Arc::new(SwarmCallbackInstance {
+            handler_messages: Mutex::new(vec![]),
+        });
+        node2.set_callback(cb2.clone()).unwrap();
+
+        test_only_two_nodes_establish_connection(&node1, &node2).await?;
+
+        let node11 = node1.clone();
+        let node22 = node2.clone();
+        tokio::spawn(async move { node11.listen().await });
+        tokio::spawn(async move { node22.listen().await });
+
+        let name = "subring";
+        <Swarm as SubringInterface<1>>::subring_join(&node1, name).await?;
+        sleep(Duration::from_secs(1)).await;
+        <Swarm as SubringInterface<1>>::subring_join(&node2, name).await?;
+        sleep(Duration::from_secs(1)).await;
+
+        let members = <Swarm as SubringInterface<1>>::subring_members(&node1, name).await?;
+        assert_eq!(members, vec![node1.did(), node2.did()]);
+
+        let sent =
+            <Swarm as SubringInterface<1>>::subring_broadcast(&node1, name, b"hello").await?;
+        assert_eq!(sent, vec![node2.did()]);
+        sleep(Duration::from_secs(1)).await;
+        assert_eq!(cb2.handler_messages.lock().await.as_slice(), &[(
+            node1.did(),
+            b"hello".to_vec()
+        )]);
+
+        <Swarm as SubringInterface<1>>::subring_leave(&node2, name).await?;
+        sleep(Duration::from_secs(1)).await;
+
+        let members = <Swarm as SubringInterface<1>>::subring_members(&node1, name).await?;
+        assert_eq!(members, vec![node1.did()]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subring_routing() -> Result<()> {
+        let keys = gen_ordered_keys(2);
+        let node1 = prepare_node(keys[0]).await;
+        let node2 = prepare_node(keys[1]).await;
+
+        let cb2 = Arc::new(SwarmCallbackInstance {
+            handler_messages: Mutex::new(vec![]),
+        });
+        node2.set_callback(cb2.clone()).unwrap();
+
+        test_only_two_nodes_establish_connection(&node1, &node2).await?;
+
+        let node11 = node1.clone();
+        let node22 = node2.clone();
+        tokio::spawn(async move { node11.listen().await });
+        tokio::spawn(async move { node22.listen().await });
+
+        let name = "subring";
+        assert!(matches!(
+            <Swarm as SubringInterface<1>>::subring_send_message(&node1, name, b"hello", node2.did()).await,
+            Err(Error::SubringNotSynced(n)) if n == name
+        ));
+
+        <Swarm as SubringInterface<1>>::subring_join(&node1, name).await?;
+        sleep(Duration::from_secs(1)).await;
+        <Swarm as SubringInterface<1>>::subring_join(&node2, name).await?;
+        sleep(Duration::from_secs(1)).await;
+
+        <Swarm as SubringInterface<1>>::subring_sync(&node1, name).await?;
+        let view = node1.dht().subrings.get(name).unwrap().clone();
+        assert_eq!(view.successor(), Some(node2.did()));
+
+        <Swarm as SubringInterface<1>>::subring_send_message(&node1, name, b"hello", node2.did())
+            .await?;
+        sleep(Duration::from_secs(1)).await;
+        assert_eq!(cb2.handler_messages.lock().await.as_slice(), &[(
+            node1.did(),
+            b"hello".to_vec()
+        )]);
+
+        <Swarm as SubringInterface<1>>::subring_leave(&node1, name).await?;
+        assert!(node1.dht().subrings.get(name).is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_offline_mailbox() -> Result<()> {
+        let keys = gen_ordered_keys(3);
+        let (key1, key2, key3) = (keys[0], keys[1], keys[2]);
+        let node1 = prepare_node(key1).await;
+        let node2 = prepare_node(key2).await;
+        let node3 = prepare_node(key3).await;
+
+        let cb3 = Arc::new(SwarmCallbackInstance {
+            handler_messages: Mutex::new(vec![]),
+        });
+        node3.set_callback(cb3.clone()).unwrap();
+
+        test_only_two_nodes_establish_connection(&node1, &node2).await?;
+
+        // node3 is offline, and it should be the successor of node2.
+        let payload = MessagePayload::new_send(
+            Message::custom(b"hello")?,
+            node1.session_sk(),
+            node2.did(),
+            node3.did(),
+        )?;
+        assert!(!node1.is_undeliverable(&payload));
+        assert!(node2.is_undeliverable(&payload));
+
+        node2.store_in_mailbox(&payload).await?;
+        let mailbox = node2
+            .dht()
+            .mailbox(node3.did())
+            .await?
+            .expect("mailbox should be stored on node2");
+        assert_eq!(mailbox.did, VirtualNode::mailbox_did(node3.did()));
+        assert_eq!(mailbox.data.len(), 1);
+
+        // The mailbox is kept until the delivered version is acknowledged.
+        let dht2 = node2.dht();
+        assert!(!dht2.clear_mailbox(node3.did(), mailbox.version + 1).await?);
+        assert!(dht2.mailbox(node3.did()).await?.is_some());
+        assert!(dht2.clear_mailbox(node3.did(), mailbox.version).await?);
+        assert!(dht2.mailbox(node3.did()).await?.is_none());
+
+        // Replaying twice delivers the message only once.
+        node3.replay_mailbox(mailbox.clone()).await?;
+        node3.replay_mailbox(mailbox).await?;
+        assert_eq!(cb3.handler_messages.lock().await.as_slice(), &[(
+            node1.did(),
+            b"hello".to_vec()
+        )]);
+
+        Ok(())
+    }
+
     pub async fn assert_no_more_msg(node1: &Swarm, node2: &Swarm, node3: &Swarm) {
         tokio::select! {
             _ = node1.listen_once() => unreachable!("node1 should not receive any message"),
@@ -8,7 +8,6 @@ use crate::error::Result;
 use crate::message::types::Message;
 use crate::message::types::NotifyPredecessorReport;
 use crate::message::types::NotifyPredecessorSend;
-use crate::message::types::SyncVNodeWithSuccessor;
 use crate::message::HandleMsg;
 use crate::message::MessageHandler;
 use crate::message::MessageHandlerEvent;
@@ -53,10 +52,7 @@ impl HandleMsg<NotifyPredecessorReport> for MessageHandler {
             PeerRingRemoteAction::SyncVNodeWithSuccessor(data),
         )) = self.dht.sync_vnode_with_successor(msg.did).await
         {
-            events.push(MessageHandlerEvent::SendMessage(
-                Message::SyncVNodeWithSuccessor(SyncVNodeWithSuccessor { data }),
-                next,
-            ))
+            events.push(MessageHandlerEvent::SyncReplicas(next, data))
         }
 
         Ok(events)
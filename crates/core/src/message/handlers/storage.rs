@@ -1,7 +1,11 @@
 #![warn(missing_docs)]
+use std::time::Duration;
+
 use async_recursion::async_recursion;
 use async_trait::async_trait;
 
+use crate::consts::VNODE_FETCH_INTERVAL_MS;
+use crate::consts::VNODE_FETCH_TIMEOUT_MS;
 use crate::dht::vnode::VirtualNode;
 use crate::dht::ChordStorage;
 use crate::dht::ChordStorageCache;
@@ -13,17 +17,29 @@ use crate::error::Error;
 use crate::error::Result;
 use crate::handle_multi_actions;
 use crate::message::types::FoundVNode;
+use crate::message::types::MailboxAck;
 use crate::message::types::Message;
+use crate::message::types::OperateReplica;
 use crate::message::types::SearchVNode;
+use crate::message::types::SyncReplicaWithSuccessor;
 use crate::message::types::SyncVNodeWithSuccessor;
+use crate::message::types::SyncVNodeWithSuccessorReport;
+use crate::message::types::TopicMessage;
+use crate::message::types::VNodeConflict;
 use crate::message::Encoded;
 use crate::message::HandleMsg;
 use crate::message::MessageHandler;
 use crate::message::MessageHandlerEvent;
 use crate::message::MessagePayload;
+use crate::message::MessageVerificationExt;
 use crate::message::PayloadSender;
+use crate::message::CAPABILITY_REPLICA;
 use crate::prelude::vnode::VNodeOperation;
+use crate::prelude::vnode::VNodeType;
+use crate::swarm::callback::SwarmEvent;
+use crate::swarm::LookupMode;
 use crate::swarm::Swarm;
+use crate::utils::sleep;
 
 /// ChordStorageInterface should imply necessary method for DHT storage
 #[cfg_attr(feature = "wasm", async_trait(?Send))]
@@ -33,10 +49,18 @@ pub trait ChordStorageInterface<const REDUNDANT: u16> {
     async fn storage_fetch(&self, vid: Did) -> Result<()>;
     /// store virtual node on DHT
     async fn storage_store(&self, vnode: VirtualNode) -> Result<()>;
+    /// store virtual node on DHT if the version of stored one is `expected`
+    async fn storage_compare_and_swap(&self, vnode: VirtualNode, expected: u64) -> Result<()>;
     /// append data to Data type virtual node
     async fn storage_append_data(&self, topic: &str, data: Encoded) -> Result<()>;
+    /// append data of virtual node to Data type virtual node, with its expiry and access control
+    async fn storage_extend(&self, vnode: VirtualNode) -> Result<()>;
     /// append data to Data type virtual node uniquely
     async fn storage_touch_data(&self, topic: &str, data: Encoded) -> Result<()>;
+    /// subscribe new data of Data type virtual node
+    async fn storage_subscribe(&self, topic: &str) -> Result<()>;
+    /// unsubscribe Data type virtual node
+    async fn storage_unsubscribe(&self, topic: &str) -> Result<()>;
 }
 
 /// ChordStorageInterfaceCacheChecker defines the interface for checking the local cache of the DHT.
@@ -49,30 +73,55 @@ pub trait ChordStorageInterfaceCacheChecker {
     async fn storage_check_cache(&self, vid: Did) -> Option<VirtualNode>;
 }
 
-/// Handle the storage fetch action of the peer ring.
+/// Handle the storage fetch action of the peer ring, for the replica at `vid`.
+/// The remote replicas are found by lookup of `mode`.
 #[cfg_attr(feature = "wasm", async_recursion(?Send))]
 #[cfg_attr(not(feature = "wasm"), async_recursion)]
-async fn handle_storage_fetch_act(swarm: &Swarm, act: PeerRingAction) -> Result<()> {
+async fn handle_storage_fetch_act(
+    swarm: &Swarm,
+    vid: Did,
+    act: PeerRingAction,
+    mode: LookupMode,
+) -> Result<()> {
     match act {
         PeerRingAction::None => (),
         PeerRingAction::SomeVNode(v) => {
-            swarm.dht.local_cache_put(v).await?;
+            let found = swarm.dht.replica_reads.record(vid, v.clone(), swarm.did());
+            for stale in found {
+                swarm
+                    .storage_store_replica(stale.vid, stale.latest, stale.reporter, mode)
+                    .await?;
+            }
+            swarm.dht.local_cache_put(v.clone()).await?;
+            swarm
+                .emit_event(SwarmEvent::StorageHit { data: vec![v] })
+                .await;
         }
         PeerRingAction::RemoteAction(next, dht_act) => {
             if let PeerRingRemoteAction::FindVNode(vid) = dht_act {
+                let (target, next_hop) = swarm.storage_route(vid, next, mode).await?;
                 tracing::debug!(
                     "storage_fetch send_message: SearchVNode({:?}) to {:?}",
                     vid,
-                    next
+                    target
                 );
+                // Track the search before sending, so that the reply can't be missed.
+                let payload = MessagePayload::new_send(
+                    Message::SearchVNode(SearchVNode { vid }),
+                    swarm.session_sk(),
+                    next_hop,
+                    target,
+                )?;
                 swarm
-                    .send_message(Message::SearchVNode(SearchVNode { vid }), next)
-                    .await?;
+                    .dht
+                    .replica_reads
+                    .track_search(payload.transaction.tx_id, vid);
+                swarm.send_payload(payload).await?;
             }
         }
         PeerRingAction::MultiActions(acts) => {
             for act in acts {
-                handle_storage_fetch_act(swarm, act).await?;
+                handle_storage_fetch_act(swarm, vid, act, mode).await?;
             }
         }
         act => return Err(Error::PeerRingUnexpectedAction(act)),
@@ -81,19 +130,46 @@ async fn handle_storage_fetch_act(swarm: &Swarm, act: PeerRingAction) -> Result<
 }
 
 /// Handle the storage store operations of the peer ring.
+/// The remote vnodes and replicas are found by lookup of `mode`.
 #[cfg_attr(feature = "wasm", async_recursion(?Send))]
 #[cfg_attr(not(feature = "wasm"), async_recursion)]
-pub(super) async fn handle_storage_store_act(swarm: &Swarm, act: PeerRingAction) -> Result<()> {
+pub(super) async fn handle_storage_store_act(
+    swarm: &Swarm,
+    act: PeerRingAction,
+    mode: LookupMode,
+) -> Result<()> {
     match act {
         PeerRingAction::None => (),
-        PeerRingAction::RemoteAction(target, PeerRingRemoteAction::FindVNodeForOperate(op)) => {
+        PeerRingAction::RemoteAction(next, PeerRingRemoteAction::FindVNodeForOperate(op)) => {
+            let (target, next_hop) = swarm.storage_route(op.did()?, next, mode).await?;
+            swarm
+                .send_message_by_hop(Message::OperateVNode(op), target, next_hop)
+                .await?;
+        }
+        PeerRingAction::RemoteAction(
+            next,
+            PeerRingRemoteAction::FindReplicaForOperate(vid, op),
+        ) => {
+            let (target, next_hop) = swarm.storage_route(vid, next, mode).await?;
             swarm
-                .send_message(Message::OperateVNode(op), target)
+                .send_message_by_hop(
+                    Message::OperateReplica(OperateReplica { vid, op }),
+                    target,
+                    next_hop,
+                )
                 .await?;
         }
+        PeerRingAction::RemoteAction(target, PeerRingRemoteAction::PushToSubscriber(vid, data)) => {
+            let msg = Message::TopicMessage(TopicMessage { vid, data });
+            if target == swarm.did() {
+                swarm.deliver_to_self(msg).await?;
+            } else {
+                swarm.send_message(msg, target).await?;
+            }
+        }
         PeerRingAction::MultiActions(acts) => {
             for act in acts {
-                handle_storage_store_act(swarm, act).await?;
+                handle_storage_store_act(swarm, act, mode).await?;
             }
         }
         act => return Err(Error::PeerRingUnexpectedAction(act)),
@@ -138,6 +214,15 @@ pub(super) async fn handle_storage_operate_act(
 ) -> Result<Vec<MessageHandlerEvent>> {
     match act {
         PeerRingAction::None => Ok(vec![]),
+        PeerRingAction::RemoteAction(target, PeerRingRemoteAction::PushToSubscriber(vid, data)) => {
+            Ok(vec![MessageHandlerEvent::SendMessage(
+                Message::TopicMessage(TopicMessage {
+                    vid: *vid,
+                    data: data.clone(),
+                }),
+                *target,
+            )])
+        }
         PeerRingAction::RemoteAction(next, _) => Ok(vec![MessageHandlerEvent::ResetDestination(
             ctx.clone(),
             *next,
@@ -153,6 +238,98 @@ pub(super) async fn handle_storage_operate_act(
     }
 }
 
+impl Swarm {
+    /// Mark current node as the writer of vnode.
+    pub(crate) fn sign_vnode(&self, vnode: VirtualNode) -> VirtualNode {
+        VirtualNode {
+            signer: Some(self.did()),
+            ..vnode
+        }
+    }
+
+    /// Search the `REDUNDANT` replicas of `vid`, the remote ones are found by lookup of `mode`.
+    /// The [ChordStorageInterface] methods use the mode of [Swarm::lookup_policy], while the
+    /// ones caused by handling a message use [LookupMode::Recursive].
+    pub(crate) async fn storage_search<const REDUNDANT: u16>(
+        &self,
+        vid: Did,
+        mode: LookupMode,
+    ) -> Result<()> {
+        self.dht.replica_reads.start(vid);
+        for replica in vid.rotate_affine(REDUNDANT) {
+            // If peer found that data is on it's localstore, copy it to the cache
+            let act = <PeerRing as ChordStorage<_, 1>>::vnode_lookup(&self.dht, replica).await?;
+            handle_storage_fetch_act(self, replica, act, mode).await?;
+        }
+        Ok(())
+    }
+
+    /// Apply op to the `REDUNDANT` replicas of its vnode, the remote ones are found by lookup
+    /// of `mode`, see [Swarm::storage_search].
+    pub(crate) async fn storage_operate<const REDUNDANT: u16>(
+        &self,
+        op: VNodeOperation,
+        mode: LookupMode,
+    ) -> Result<()> {
+        let act = <PeerRing as ChordStorage<_, REDUNDANT>>::vnode_operate(&self.dht, op).await?;
+        handle_storage_store_act(self, act, mode).await
+    }
+
+    /// Store vnode at the replica position `vid`, the replica is kept if it's not older.
+    /// A local replica is repaired on behalf of `signer`, the verified signer of the message
+    /// carrying vnode, while a remote one is repaired by current node.
+    pub(crate) async fn storage_store_replica(
+        &self,
+        vid: Did,
+        vnode: VirtualNode,
+        signer: Did,
+        mode: LookupMode,
+    ) -> Result<()> {
+        let op = VNodeOperation::Repair(vnode);
+        let act = self.dht.vnode_operate_replica(vid, op, signer).await?;
+        handle_storage_store_act(self, act, mode).await
+    }
+
+    /// Sync vnodes to successor `did`, paired with the replica positions they are stored at.
+    /// A successor not supporting replicas gets them by [SyncVNodeWithSuccessor], where each
+    /// vnode is stored at its primary position.
+    pub(crate) async fn sync_replicas(
+        &self,
+        did: Did,
+        data: Vec<(Did, VirtualNode)>,
+    ) -> Result<()> {
+        let msg = if self.peer_supports(did, CAPABILITY_REPLICA) {
+            Message::SyncReplicaWithSuccessor(SyncReplicaWithSuccessor { data })
+        } else {
+            Message::SyncVNodeWithSuccessor(SyncVNodeWithSuccessor {
+                data: data.into_iter().map(|(_, vnode)| vnode).collect(),
+            })
+        };
+        self.send_message(msg, did).await?;
+        Ok(())
+    }
+
+    /// Fetch virtual node from DHT and wait until it's put into local cache.
+    /// The stale cache of `vid` is dropped before fetching.
+    /// Returns `None` if it's not fetched within [VNODE_FETCH_TIMEOUT_MS].
+    pub(crate) async fn storage_fetch_and_wait<const REDUNDANT: u16>(
+        &self,
+        vid: Did,
+    ) -> Result<Option<VirtualNode>> {
+        self.dht.cache.remove(&vid.to_string()).await?;
+        <Self as ChordStorageInterface<REDUNDANT>>::storage_fetch(self, vid).await?;
+
+        let interval = Duration::from_millis(VNODE_FETCH_INTERVAL_MS);
+        for _ in 0..VNODE_FETCH_TIMEOUT_MS / VNODE_FETCH_INTERVAL_MS {
+            if let Some(vnode) = self.storage_check_cache(vid).await {
+                return Ok(Some(vnode));
+            }
+            sleep(interval).await;
+        }
+        Ok(self.storage_check_cache(vid).await)
+    }
+}
+
 #[cfg_attr(feature = "wasm", async_trait(?Send))]
 #[cfg_attr(not(feature = "wasm"), async_trait)]
 impl ChordStorageInterfaceCacheChecker for Swarm {
@@ -166,36 +343,92 @@ impl ChordStorageInterfaceCacheChecker for Swarm {
 #[cfg_attr(not(feature = "wasm"), async_trait)]
 impl<const REDUNDANT: u16> ChordStorageInterface<REDUNDANT> for Swarm {
     /// Fetch virtual node, if exist in localstoreage, copy it to the cache,
-    /// else Query Remote Node
+    /// else Query Remote Node.
+    /// Each of the `REDUNDANT` replicas is searched, and the stale ones are repaired with the
+    /// latest one, see [ReplicaReads](crate::dht::ReplicaReads).
     async fn storage_fetch(&self, vid: Did) -> Result<()> {
-        // If peer found that data is on it's localstore, copy it to the cache
-        let act = <PeerRing as ChordStorage<_, REDUNDANT>>::vnode_lookup(&self.dht, vid).await?;
-        handle_storage_fetch_act(self, act).await?;
-        Ok(())
+        self.storage_search::<REDUNDANT>(vid, self.lookup_policy().mode)
+            .await
     }
 
     /// Store VirtualNode, `TryInto<VirtualNode>` is implemented for alot of types
     async fn storage_store(&self, vnode: VirtualNode) -> Result<()> {
+        let vnode = self.sign_vnode(vnode);
         let op = VNodeOperation::Overwrite(vnode);
-        let act = <PeerRing as ChordStorage<_, REDUNDANT>>::vnode_operate(&self.dht, op).await?;
-        handle_storage_store_act(self, act).await?;
+        self.storage_operate::<REDUNDANT>(op, self.lookup_policy().mode)
+            .await
+    }
+
+    /// Store VirtualNode if the version of stored one is `expected`.
+    /// It's decided by the primary replica, then the other replicas are repaired with the
+    /// result, see [ReplicaReads](crate::dht::ReplicaReads).
+    /// A conflict on local replica is returned as error, while a conflict on remote replica
+    /// is reported by [VNodeConflict] message.
+    async fn storage_compare_and_swap(&self, vnode: VirtualNode, expected: u64) -> Result<()> {
+        let mode = self.lookup_policy().mode;
+        let vnode = self.sign_vnode(vnode);
+        let vid = vnode.did;
+        let replicas: Vec<Did> = vid.rotate_affine(REDUNDANT).into_iter().skip(1).collect();
+        let op = VNodeOperation::CompareAndSwap(expected, vnode.clone());
+        match <PeerRing as ChordStorage<_, REDUNDANT>>::vnode_operate(&self.dht, op).await? {
+            PeerRingAction::RemoteAction(next, PeerRingRemoteAction::FindVNodeForOperate(op)) => {
+                let (target, next_hop) = self.storage_route(vid, next, mode).await?;
+                // Track the swap before sending, so that the report can't be missed.
+                let payload = MessagePayload::new_send(
+                    Message::OperateVNode(op),
+                    self.session_sk(),
+                    next_hop,
+                    target,
+                )?;
+                self.dht
+                    .replica_reads
+                    .track_swap(payload.transaction.tx_id, vnode, replicas);
+                self.send_payload(payload).await?;
+            }
+            act => {
+                handle_storage_store_act(self, act, mode).await?;
+                // The primary replica is local, and accepted the swap.
+                if let Some(swapped) = self.dht.storage.get(&vid.to_string()).await? {
+                    for replica in replicas {
+                        self.storage_store_replica(replica, swapped.clone(), self.did(), mode)
+                            .await?;
+                    }
+                }
+            }
+        }
         Ok(())
     }
 
     async fn storage_append_data(&self, topic: &str, data: Encoded) -> Result<()> {
         let vnode: VirtualNode = (topic.to_string(), data).try_into()?;
+        <Self as ChordStorageInterface<REDUNDANT>>::storage_extend(self, vnode).await
+    }
+
+    async fn storage_extend(&self, vnode: VirtualNode) -> Result<()> {
+        let vnode = self.sign_vnode(vnode);
         let op = VNodeOperation::Extend(vnode);
-        let act = <PeerRing as ChordStorage<_, REDUNDANT>>::vnode_operate(&self.dht, op).await?;
-        handle_storage_store_act(self, act).await?;
-        Ok(())
+        self.storage_operate::<REDUNDANT>(op, self.lookup_policy().mode)
+            .await
     }
 
     async fn storage_touch_data(&self, topic: &str, data: Encoded) -> Result<()> {
         let vnode: VirtualNode = (topic.to_string(), data).try_into()?;
+        let vnode = self.sign_vnode(vnode);
         let op = VNodeOperation::Touch(vnode);
-        let act = <PeerRing as ChordStorage<_, REDUNDANT>>::vnode_operate(&self.dht, op).await?;
-        handle_storage_store_act(self, act).await?;
-        Ok(())
+        self.storage_operate::<REDUNDANT>(op, self.lookup_policy().mode)
+            .await
+    }
+
+    async fn storage_subscribe(&self, topic: &str) -> Result<()> {
+        let op = VNodeOperation::Subscribe(topic.to_string(), self.did());
+        self.storage_operate::<REDUNDANT>(op, self.lookup_policy().mode)
+            .await
+    }
+
+    async fn storage_unsubscribe(&self, topic: &str) -> Result<()> {
+        let op = VNodeOperation::Unsubscribe(topic.to_string(), self.did());
+        self.storage_operate::<REDUNDANT>(op, self.lookup_policy().mode)
+            .await
     }
 }
 
@@ -210,6 +443,7 @@ impl HandleMsg<SearchVNode> for MessageHandler {
         msg: &SearchVNode,
     ) -> Result<Vec<MessageHandlerEvent>> {
         // For relay message, set redundant to 1
+        // The mailbox fetched by its owner is kept until it's acknowledged by MailboxAck.
         match <PeerRing as ChordStorage<_, 1>>::vnode_lookup(&self.dht, msg.vid).await {
             Ok(action) => handle_storage_search_act(ctx, action).await,
             Err(e) => Err(e),
@@ -228,10 +462,39 @@ impl HandleMsg<FoundVNode> for MessageHandler {
         if self.dht.did != ctx.relay.destination {
             return Ok(vec![MessageHandlerEvent::ForwardPayload(ctx.clone(), None)]);
         }
+        let tx_id = &ctx.transaction.tx_id;
+        let signer = ctx.transaction.signer();
+        // The replica position searched, if the search is sent by `storage_fetch`.
+        let replica = self.dht.replica_reads.take_search(tx_id);
+        let mut events = vec![];
         for data in msg.data.iter().cloned() {
+            if self.is_own_mailbox(&data) {
+                events.push(MessageHandlerEvent::ReplayMailbox(data, signer));
+                continue;
+            }
+            // Read repair: repair the replicas found older than the latest one reported.
+            if let Some(replica) = replica {
+                let found = self.dht.replica_reads.record(replica, data.clone(), signer);
+                for stale in found {
+                    events.push(MessageHandlerEvent::StorageStore(
+                        stale.vid,
+                        stale.latest,
+                        stale.reporter,
+                    ));
+                }
+            }
+            // The result of a swap written by current node and accepted by the primary replica,
+            // repair the other ones.
+            for vid in self.dht.replica_reads.take_swap(tx_id, &data) {
+                events.push(MessageHandlerEvent::StorageStore(
+                    vid,
+                    data.clone(),
+                    self.dht.did,
+                ));
+            }
             self.dht.local_cache_put(data).await?;
         }
-        Ok(vec![])
+        Ok(events)
     }
 }
 
@@ -243,10 +506,116 @@ impl HandleMsg<VNodeOperation> for MessageHandler {
         ctx: &MessagePayload,
         msg: &VNodeOperation,
     ) -> Result<Vec<MessageHandlerEvent>> {
-        // For relay message, set redundant to 1
-        let action =
-            <PeerRing as ChordStorage<_, 1>>::vnode_operate(&self.dht, msg.clone()).await?;
-        handle_storage_operate_act(ctx, &action).await
+        // For relay message, operate the primary replica only
+        let vid = msg.did()?;
+        match self
+            .dht
+            .vnode_operate_replica(vid, msg.clone(), ctx.transaction.signer())
+            .await
+        {
+            // The primary replica accepted a swap, report the result to the writer,
+            // who repairs the other replicas with it.
+            Ok(PeerRingAction::None) if matches!(msg, VNodeOperation::CompareAndSwap(..)) => {
+                let data = self.dht.storage.get(&vid.to_string()).await?;
+                Ok(vec![MessageHandlerEvent::SendReportMessage(
+                    ctx.clone(),
+                    Message::FoundVNode(FoundVNode {
+                        data: data.into_iter().collect(),
+                    }),
+                )])
+            }
+            Ok(action) => handle_storage_operate_act(ctx, &action).await,
+            Err(Error::VNodeVersionConflict(expected, _)) => {
+                self.report_conflict(ctx, vid, vid, expected).await
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", async_trait(?Send))]
+#[cfg_attr(not(feature = "wasm"), async_trait)]
+impl HandleMsg<OperateReplica> for MessageHandler {
+    async fn handle(
+        &self,
+        ctx: &MessagePayload,
+        msg: &OperateReplica,
+    ) -> Result<Vec<MessageHandlerEvent>> {
+        match self
+            .dht
+            .vnode_operate_replica(msg.vid, msg.op.clone(), ctx.transaction.signer())
+            .await
+        {
+            Ok(action) => handle_storage_operate_act(ctx, &action).await,
+            Err(Error::VNodeVersionConflict(expected, _)) => {
+                self.report_conflict(ctx, msg.vid, msg.op.did()?, expected)
+                    .await
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl MessageHandler {
+    /// Returns `true` if vnode keeps the messages sent to current node while it's offline.
+    fn is_own_mailbox(&self, vnode: &VirtualNode) -> bool {
+        vnode.kind == VNodeType::RelayMessage && vnode.did == VirtualNode::mailbox_did(self.dht.did)
+    }
+
+    /// Report the current vnode of replica `vid` to the sender of a rejected
+    /// [VNodeOperation::CompareAndSwap].
+    async fn report_conflict(
+        &self,
+        ctx: &MessagePayload,
+        vid: Did,
+        did: Did,
+        expected: u64,
+    ) -> Result<Vec<MessageHandlerEvent>> {
+        let current = self.dht.storage.get(&vid.to_string()).await?;
+        Ok(vec![MessageHandlerEvent::SendReportMessage(
+            ctx.clone(),
+            Message::VNodeConflict(VNodeConflict {
+                did,
+                expected,
+                current,
+            }),
+        )])
+    }
+}
+
+#[cfg_attr(feature = "wasm", async_trait(?Send))]
+#[cfg_attr(not(feature = "wasm"), async_trait)]
+impl HandleMsg<VNodeConflict> for MessageHandler {
+    /// Cache the current vnode, so that the origin can retry with the latest version.
+    async fn handle(
+        &self,
+        ctx: &MessagePayload,
+        msg: &VNodeConflict,
+    ) -> Result<Vec<MessageHandlerEvent>> {
+        if self.dht.did != ctx.relay.destination {
+            return Ok(vec![MessageHandlerEvent::ForwardPayload(ctx.clone(), None)]);
+        }
+        if let Some(current) = msg.current.clone() {
+            self.dht.local_cache_put(current).await?;
+        }
+        Ok(vec![])
+    }
+}
+
+#[cfg_attr(feature = "wasm", async_trait(?Send))]
+#[cfg_attr(not(feature = "wasm"), async_trait)]
+impl HandleMsg<TopicMessage> for MessageHandler {
+    /// Forward to subscriber, the data will be delivered to [crate::swarm::callback::SwarmCallback::on_inbound]
+    /// when reached.
+    async fn handle(
+        &self,
+        ctx: &MessagePayload,
+        _msg: &TopicMessage,
+    ) -> Result<Vec<MessageHandlerEvent>> {
+        if self.dht.did != ctx.relay.destination {
+            return Ok(vec![MessageHandlerEvent::ForwardPayload(ctx.clone(), None)]);
+        }
+        Ok(vec![])
     }
 }
 
@@ -256,27 +625,100 @@ impl HandleMsg<SyncVNodeWithSuccessor> for MessageHandler {
     // received remote sync vnode request
     async fn handle(
         &self,
-        _ctx: &MessagePayload,
+        ctx: &MessagePayload,
         msg: &SyncVNodeWithSuccessor,
     ) -> Result<Vec<MessageHandlerEvent>> {
+        let signer = ctx.transaction.signer();
         let mut events = vec![];
         for data in msg.data.iter().cloned() {
-            // only simply store here
-            // For relay message, set redundant to 1
-            events.push(MessageHandlerEvent::StorageStore(data));
+            if self.is_own_mailbox(&data) {
+                events.push(MessageHandlerEvent::ReplayMailbox(data, signer));
+                continue;
+            }
+            // only simply store here, at the primary position
+            events.push(MessageHandlerEvent::StorageStore(data.did, data, signer));
+        }
+        Ok(events)
+    }
+}
+
+#[cfg_attr(feature = "wasm", async_trait(?Send))]
+#[cfg_attr(not(feature = "wasm"), async_trait)]
+impl HandleMsg<SyncReplicaWithSuccessor> for MessageHandler {
+    // received remote sync replica request
+    async fn handle(
+        &self,
+        ctx: &MessagePayload,
+        msg: &SyncReplicaWithSuccessor,
+    ) -> Result<Vec<MessageHandlerEvent>> {
+        let signer = ctx.transaction.signer();
+        let mut events = vec![];
+        for (vid, data) in msg.data.iter().cloned() {
+            if self.is_own_mailbox(&data) {
+                events.push(MessageHandlerEvent::ReplayMailbox(data, signer));
+                continue;
+            }
+            // only simply store here, keep the replica position
+            events.push(MessageHandlerEvent::StorageStore(vid, data, signer));
         }
+        // Events are executed in order and stop at the first error, so the report is only sent
+        // after all virtual nodes are stored. A leaving node waits for it before dropping them.
+        events.push(MessageHandlerEvent::SendReportMessage(
+            ctx.clone(),
+            Message::SyncVNodeWithSuccessorReport(SyncVNodeWithSuccessorReport {
+                count: msg.data.len() as u64,
+            }),
+        ));
         Ok(events)
     }
 }
 
+#[cfg_attr(feature = "wasm", async_trait(?Send))]
+#[cfg_attr(not(feature = "wasm"), async_trait)]
+impl HandleMsg<SyncVNodeWithSuccessorReport> for MessageHandler {
+    // received the report of synced vnodes, which is resolved by pending requests of swarm
+    async fn handle(
+        &self,
+        ctx: &MessagePayload,
+        _msg: &SyncVNodeWithSuccessorReport,
+    ) -> Result<Vec<MessageHandlerEvent>> {
+        if self.dht.did != ctx.relay.destination {
+            return Ok(vec![MessageHandlerEvent::ForwardPayload(ctx.clone(), None)]);
+        }
+        Ok(vec![])
+    }
+}
+
+#[cfg_attr(feature = "wasm", async_trait(?Send))]
+#[cfg_attr(not(feature = "wasm"), async_trait)]
+impl HandleMsg<MailboxAck> for MessageHandler {
+    // received the acknowledgement of delivered mailbox from its owner
+    async fn handle(
+        &self,
+        ctx: &MessagePayload,
+        msg: &MailboxAck,
+    ) -> Result<Vec<MessageHandlerEvent>> {
+        if self.dht.did != ctx.relay.destination {
+            return Ok(vec![MessageHandlerEvent::ForwardPayload(ctx.clone(), None)]);
+        }
+        self.dht
+            .clear_mailbox(ctx.transaction.signer(), msg.version)
+            .await?;
+        Ok(vec![])
+    }
+}
+
 #[cfg(not(feature = "wasm"))]
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
+
+    use tokio::time::sleep;
+
     use super::*;
     use crate::ecc::tests::gen_ordered_keys;
     use crate::message::handlers::connection::tests::test_only_two_nodes_establish_connection;
     use crate::message::Encoder;
-    use crate::prelude::vnode::VNodeType;
     use crate::tests::default::prepare_node;
 
     #[tokio::test]
@@ -344,7 +786,13 @@ mod test {
             Some(VirtualNode {
                 did: vid,
                 data: vec![data.encode()?],
-                kind: VNodeType::Data
+                kind: VNodeType::Data,
+                subscribers: vec![],
+                version: 1,
+                signer: Some(node1.did()),
+                owner: None,
+                writers: vec![],
+                expires_at_ms: None,
             })
         );
 
@@ -389,7 +837,7 @@ mod test {
         let ev = node2.listen_once().await.unwrap().0;
         assert!(matches!(
             ev.transaction.data()?,
-            Message::OperateVNode(VNodeOperation::Extend(VirtualNode { did, data, kind: VNodeType::Data }))
+            Message::OperateVNode(VNodeOperation::Extend(VirtualNode { did, data, kind: VNodeType::Data, .. }))
                 if did == vid && data == vec!["111".to_string().encode()?]
         ));
         <Swarm as ChordStorageInterface<1>>::storage_append_data(
@@ -402,7 +850,7 @@ mod test {
         let ev = node2.listen_once().await.unwrap().0;
         assert!(matches!(
             ev.transaction.data()?,
-            Message::OperateVNode(VNodeOperation::Extend(VirtualNode { did, data, kind: VNodeType::Data }))
+            Message::OperateVNode(VNodeOperation::Extend(VirtualNode { did, data, kind: VNodeType::Data, .. }))
                 if did == vid && data == vec!["222".to_string().encode()?]
         ));
         assert!(node1.storage_check_cache(vid).await.is_none());
@@ -435,7 +883,13 @@ mod test {
             Some(VirtualNode {
                 did: vid,
                 data: vec!["111".to_string().encode()?, "222".to_string().encode()?],
-                kind: VNodeType::Data
+                kind: VNodeType::Data,
+                subscribers: vec![],
+                version: 2,
+                signer: Some(node1.did()),
+                owner: None,
+                writers: vec![],
+                expires_at_ms: None,
             })
         );
 
@@ -451,7 +905,7 @@ mod test {
         let ev = node2.listen_once().await.unwrap().0;
         assert!(matches!(
             ev.transaction.data()?,
-            Message::OperateVNode(VNodeOperation::Extend(VirtualNode { did, data, kind: VNodeType::Data }))
+            Message::OperateVNode(VNodeOperation::Extend(VirtualNode { did, data, kind: VNodeType::Data, .. }))
                 if did == vid && data == vec!["333".to_string().encode()?]
         ));
 
@@ -484,10 +938,262 @@ mod test {
                     "222".to_string().encode()?,
                     "333".to_string().encode()?
                 ],
-                kind: VNodeType::Data
+                kind: VNodeType::Data,
+                subscribers: vec![],
+                version: 3,
+                signer: Some(node1.did()),
+                owner: None,
+                writers: vec![],
+                expires_at_ms: None,
             })
         );
 
         Ok(())
     }
+
+    #[cfg(not(feature = "redundant"))]
+    #[tokio::test]
+    async fn test_subscribe_topic() -> Result<()> {
+        let keys = gen_ordered_keys(2);
+        let (key1, key2) = (keys[0], keys[1]);
+        let node1 = prepare_node(key1).await;
+        let node2 = prepare_node(key2).await;
+        test_only_two_nodes_establish_connection(&node1, &node2).await?;
+
+        let topic = "Across the Great Wall we can reach every corner in the world.".to_string();
+        let vid = VirtualNode::gen_did(&topic)?;
+
+        // Make sure the data is stored on node2.
+        let (node1, node2) = if vid.in_range(node2.did(), node2.did(), node1.did()) {
+            (node1, node2)
+        } else {
+            (node2, node1)
+        };
+
+        <Swarm as ChordStorageInterface<1>>::storage_subscribe(&node1, &topic).await?;
+        let ev = node2.listen_once().await.unwrap().0;
+        assert!(matches!(
+            ev.transaction.data()?,
+            Message::OperateVNode(VNodeOperation::Subscribe(t, did)) if t == topic && did == node1.did()
+        ));
+
+        <Swarm as ChordStorageInterface<1>>::storage_append_data(
+            &node1,
+            &topic,
+            "111".to_string().encode()?,
+        )
+        .await?;
+        let ev = node2.listen_once().await.unwrap().0;
+        assert!(matches!(
+            ev.transaction.data()?,
+            Message::OperateVNode(VNodeOperation::Extend(_))
+        ));
+
+        // node2 pushes new data to subscriber.
+        let ev = node1.listen_once().await.unwrap().0;
+        assert!(matches!(
+            ev.transaction.data()?,
+            Message::TopicMessage(x) if x.vid == vid && x.data == vec!["111".to_string().encode()?]
+        ));
+
+        <Swarm as ChordStorageInterface<1>>::storage_unsubscribe(&node1, &topic).await?;
+        let ev = node2.listen_once().await.unwrap().0;
+        assert!(matches!(
+            ev.transaction.data()?,
+            Message::OperateVNode(VNodeOperation::Unsubscribe(t, did)) if t == topic && did == node1.did()
+        ));
+        let vnode: VirtualNode = node2.dht().storage.get(&vid.to_string()).await?.unwrap();
+        assert!(vnode.subscribers.is_empty());
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "redundant"))]
+    #[tokio::test]
+    async fn test_compare_and_swap_conflict() -> Result<()> {
+        let keys = gen_ordered_keys(2);
+        let (key1, key2) = (keys[0], keys[1]);
+        let node1 = prepare_node(key1).await;
+        let node2 = prepare_node(key2).await;
+        test_only_two_nodes_establish_connection(&node1, &node2).await?;
+
+        let data = "Across the Great Wall we can reach every corner in the world.".to_string();
+        let vnode: VirtualNode = data.clone().try_into().unwrap();
+        let vid = vnode.did;
+
+        // Make sure the data is stored on node2.
+        let (node1, node2) = if vid.in_range(node2.did(), node2.did(), node1.did()) {
+            (node1, node2)
+        } else {
+            (node2, node1)
+        };
+
+        // Create the vnode by expecting version 0.
+        <Swarm as ChordStorageInterface<1>>::storage_compare_and_swap(&node1, vnode.clone(), 0)
+            .await?;
+        let ev = node2.listen_once().await.unwrap().0;
+        assert!(matches!(
+            ev.transaction.data()?,
+            Message::OperateVNode(VNodeOperation::CompareAndSwap(0, x)) if x.did == vid
+        ));
+        let stored: VirtualNode = node2.dht().storage.get(&vid.to_string()).await?.unwrap();
+        assert_eq!(stored.version, 1);
+        assert_eq!(stored.signer, Some(node1.did()));
+
+        // The primary replica reports the result to the writer.
+        let ev = node1.listen_once().await.unwrap().0;
+        assert!(matches!(
+            ev.transaction.data()?,
+            Message::FoundVNode(x) if x.data == vec![stored.clone()]
+        ));
+
+        // Expecting version 0 again is conflicted, node2 reports the current vnode.
+        let other: VirtualNode = (data.clone(), "other".to_string()).try_into()?;
+        <Swarm as ChordStorageInterface<1>>::storage_compare_and_swap(&node1, other.clone(), 0)
+            .await?;
+        node2.listen_once().await.unwrap();
+        let ev = node1.listen_once().await.unwrap().0;
+        assert!(matches!(
+            ev.transaction.data()?,
+            Message::VNodeConflict(x) if x.did == vid && x.expected == 0 && x.current == Some(stored.clone())
+        ));
+        assert_eq!(node1.storage_check_cache(vid).await, Some(stored));
+
+        // Expecting the current version succeeds.
+        <Swarm as ChordStorageInterface<1>>::storage_compare_and_swap(&node1, other, 1).await?;
+        node2.listen_once().await.unwrap();
+        let stored: VirtualNode = node2.dht().storage.get(&vid.to_string()).await?.unwrap();
+        assert_eq!(stored.version, 2);
+        assert_eq!(stored.data, vec!["other".to_string().encode()?]);
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "redundant"))]
+    #[tokio::test]
+    async fn test_owned_topic_writers() -> Result<()> {
+        let keys = gen_ordered_keys(2);
+        let (key1, key2) = (keys[0], keys[1]);
+        let node1 = prepare_node(key1).await;
+        let node2 = prepare_node(key2).await;
+        test_only_two_nodes_establish_connection(&node1, &node2).await?;
+
+        let topic = "Across the Great Wall we can reach every corner in the world.".to_string();
+        let vid = VirtualNode::gen_did(&topic)?;
+
+        // Make sure the data is stored on node2.
+        let (node1, node2) = if vid.in_range(node2.did(), node2.did(), node1.did()) {
+            (node1, node2)
+        } else {
+            (node2, node1)
+        };
+
+        // node1 creates a topic owned by itself.
+        let vnode: VirtualNode = (topic.clone(), "111".to_string()).try_into()?;
+        let owned = VirtualNode {
+            owner: Some(node1.did()),
+            ..vnode
+        };
+        <Swarm as ChordStorageInterface<1>>::storage_store(&node1, owned.clone()).await?;
+        node2.listen_once().await.unwrap();
+        let stored: VirtualNode = node2.dht().storage.get(&vid.to_string()).await?.unwrap();
+        assert_eq!(stored.owner, Some(node1.did()));
+
+        // node2 is not a writer of the topic.
+        let ret = <Swarm as ChordStorageInterface<1>>::storage_append_data(
+            &node2,
+            &topic,
+            "222".to_string().encode()?,
+        )
+        .await;
+        assert!(matches!(ret, Err(Error::VNodePermissionDenied(did)) if did == node2.did()));
+
+        // node1 adds node2 to writers, then node2 can append data.
+        let owned = VirtualNode {
+            writers: vec![node2.did()],
+            ..owned
+        };
+        <Swarm as ChordStorageInterface<1>>::storage_store(&node1, owned).await?;
+        node2.listen_once().await.unwrap();
+        <Swarm as ChordStorageInterface<1>>::storage_append_data(
+            &node2,
+            &topic,
+            "222".to_string().encode()?,
+        )
+        .await?;
+        let stored: VirtualNode = node2.dht().storage.get(&vid.to_string()).await?.unwrap();
+        assert_eq!(stored.data, vec![
+            "111".to_string().encode()?,
+            "222".to_string().encode()?
+        ]);
+        assert_eq!(stored.signer, Some(node2.did()));
+
+        // But node2 cannot overwrite it.
+        let vnode: VirtualNode = (topic.clone(), "333".to_string()).try_into()?;
+        let ret = <Swarm as ChordStorageInterface<1>>::storage_store(&node2, vnode).await;
+        assert!(matches!(ret, Err(Error::VNodePermissionDenied(_))));
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "redundant"))]
+    #[tokio::test]
+    async fn test_replicated_store_and_read_repair() -> Result<()> {
+        let keys = gen_ordered_keys(2);
+        let (key1, key2) = (keys[0], keys[1]);
+        let node1 = prepare_node(key1).await;
+        let node2 = prepare_node(key2).await;
+        test_only_two_nodes_establish_connection(&node1, &node2).await?;
+
+        let node11 = node1.clone();
+        let node22 = node2.clone();
+        tokio::spawn(async move { node11.listen().await });
+        tokio::spawn(async move { node22.listen().await });
+
+        let data = "Across the Great Wall we can reach every corner in the world.".to_string();
+        let vnode: VirtualNode = data.clone().try_into().unwrap();
+        let vid = vnode.did;
+        let replicas = vid.rotate_affine(2);
+
+        <Swarm as ChordStorageInterface<2>>::storage_store(&node1, vnode.clone()).await?;
+        sleep(Duration::from_secs(3)).await;
+
+        // Every replica position holds the vnode.
+        for rvid in replicas.iter() {
+            let v1 = node1.dht().storage.get(&rvid.to_string()).await?;
+            let v2 = node2.dht().storage.get(&rvid.to_string()).await?;
+            let replica = v1.or(v2).expect("replica should be stored");
+            assert_eq!(replica.did, vid);
+            assert_eq!(replica.data, vec![data.encode()?]);
+            assert_eq!(replica.version, 1);
+        }
+
+        // Make the second replica stale, and fetch from the node which is not holding it.
+        let stale_key = replicas[1].to_string();
+        let (holder, fetcher) = if node1.dht().storage.get(&stale_key).await?.is_some() {
+            (node1, node2)
+        } else {
+            (node2, node1)
+        };
+        let stale = VirtualNode {
+            data: vec!["stale".to_string().encode()?],
+            version: 0,
+            ..vnode.clone()
+        };
+        holder.dht().storage.put(&stale_key, &stale).await?;
+
+        // The stale replica is repaired whichever replica responds first.
+        <Swarm as ChordStorageInterface<2>>::storage_fetch(&fetcher, vid).await?;
+        sleep(Duration::from_secs(3)).await;
+
+        let cached = fetcher.storage_check_cache(vid).await.unwrap();
+        assert_eq!(cached.data, vec![data.encode()?]);
+        assert_eq!(cached.version, 1);
+
+        let repaired: VirtualNode = holder.dht().storage.get(&stale_key).await?.unwrap();
+        assert_eq!(repaired.data, vec![data.encode()?]);
+        assert_eq!(repaired.version, 1);
+
+        Ok(())
+    }
 }
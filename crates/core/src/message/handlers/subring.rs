@@ -1,11 +1,21 @@
 #![warn(missing_docs)]
+use std::collections::HashSet;
+
 use async_trait::async_trait;
+use uuid::Uuid;
 
-use super::storage::handle_storage_store_act;
-use crate::dht::ChordStorage;
-use crate::dht::PeerRing;
+use crate::dht::subring::Subring;
+use crate::dht::subring::SubringView;
+use crate::dht::vnode::VirtualNode;
+use crate::dht::Did;
 use crate::error::Result;
+use crate::message::types::FindSuccessorReportHandler;
+use crate::message::types::FindSuccessorSend;
+use crate::message::types::FindSuccessorThen;
+use crate::message::Message;
+use crate::message::PayloadSender;
 use crate::prelude::vnode::VNodeOperation;
+use crate::swarm::impls::JudgeConnection;
 use crate::swarm::Swarm;
 
 /// SubringInterface should imply necessary operator for DHT Subring
@@ -14,6 +24,18 @@ use crate::swarm::Swarm;
 pub trait SubringInterface<const REDUNDANT: u16> {
     /// join a subring
     async fn subring_join(&self, name: &str) -> Result<()>;
+    /// leave a subring
+    async fn subring_leave(&self, name: &str) -> Result<()>;
+    /// fetch members of a subring from DHT
+    async fn subring_members(&self, name: &str) -> Result<Vec<Did>>;
+    /// send a custom message to all members of a subring, returns the members it was sent to
+    async fn subring_broadcast(&self, name: &str, msg: &[u8]) -> Result<Vec<Did>>;
+    /// sync local view of a subring, which is required to route messages within it
+    async fn subring_sync(&self, name: &str) -> Result<()>;
+    /// find the successor of did among members of a subring, then connect to it
+    async fn subring_find_successor(&self, name: &str, did: Did) -> Result<()>;
+    /// send a custom message to destination, relayed only by members of a subring
+    async fn subring_send_message(&self, name: &str, msg: &[u8], destination: Did) -> Result<Uuid>;
 }
 
 #[cfg_attr(feature = "wasm", async_trait(?Send))]
@@ -23,8 +45,93 @@ impl<const REDUNDANT: u16> SubringInterface<REDUNDANT> for Swarm {
     /// send direct message with `JoinSubring` type, which will handled by `next` node.
     async fn subring_join(&self, name: &str) -> Result<()> {
         let op = VNodeOperation::JoinSubring(name.to_string(), self.dht.did);
-        let act = <PeerRing as ChordStorage<_, REDUNDANT>>::vnode_operate(&self.dht, op).await?;
-        handle_storage_store_act(self, act).await?;
+        self.storage_operate::<REDUNDANT>(op, self.lookup_policy().mode)
+            .await
+    }
+
+    /// remove did from current chord subring, and drop the local view of it.
+    /// send direct message with `LeaveSubring` type, which will handled by `next` node.
+    async fn subring_leave(&self, name: &str) -> Result<()> {
+        self.dht.subrings.remove(name);
+        let op = VNodeOperation::LeaveSubring(name.to_string(), self.dht.did);
+        self.storage_operate::<REDUNDANT>(op, self.lookup_policy().mode)
+            .await
+    }
+
+    /// fetch the subring virtual node and list its members.
+    /// returns empty list if the subring is not found.
+    async fn subring_members(&self, name: &str) -> Result<Vec<Did>> {
+        let vid = VirtualNode::gen_did(name)?;
+        let Some(vnode) = self.storage_fetch_and_wait::<REDUNDANT>(vid).await? else {
+            return Ok(vec![]);
+        };
+        let subring: Subring = vnode.try_into()?;
+        Ok(subring.members)
+    }
+
+    /// send custom message to members other than current node one by one.
+    /// failure of a member is logged and skipped.
+    async fn subring_broadcast(&self, name: &str, msg: &[u8]) -> Result<Vec<Did>> {
+        let members = <Self as SubringInterface<REDUNDANT>>::subring_members(self, name).await?;
+        let mut sent = vec![];
+        for did in members.into_iter().filter(|did| *did != self.did()) {
+            match self.send_message(Message::custom(msg)?, did).await {
+                Ok(_) => sent.push(did),
+                Err(e) => {
+                    tracing::warn!("Failed to send message to subring member {}: {:?}", did, e)
+                }
+            }
+        }
+        Ok(sent)
+    }
+
+    /// fetch members of subring and rebuild the local view of it if current node is a member,
+    /// then connect to the members in finger table of the view.
+    /// the local view is dropped if current node is not a member anymore.
+    async fn subring_sync(&self, name: &str) -> Result<()> {
+        let members = <Self as SubringInterface<REDUNDANT>>::subring_members(self, name).await?;
+        if !members.contains(&self.did()) {
+            self.dht.subrings.remove(name);
+            return Ok(());
+        }
+
+        let mut subring = Subring::new(name, self.did())?;
+        subring.members = members;
+        let view = SubringView::new(&subring, self.did());
+        let fingers = view
+            .finger
+            .list()
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<HashSet<_>>();
+        self.dht.subrings.insert(name.to_string(), view);
+
+        for did in fingers {
+            if self.get_and_check_connection(did).await.is_none() {
+                if let Err(e) = self.connect(did).await {
+                    tracing::warn!("Failed to connect subring member {}: {:?}", did, e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// send `FindSuccessorSend` routed within subring, the successor will be connected
+    /// when it's reported.
+    async fn subring_find_successor(&self, name: &str, did: Did) -> Result<()> {
+        let msg = Message::FindSuccessorSend(FindSuccessorSend {
+            did,
+            strict: false,
+            then: FindSuccessorThen::Report(FindSuccessorReportHandler::Connect),
+        });
+        self.send_message_within_subring(msg, did, name).await?;
         Ok(())
     }
+
+    /// send custom message routed within subring.
+    async fn subring_send_message(&self, name: &str, msg: &[u8], destination: Did) -> Result<Uuid> {
+        self.send_message_within_subring(Message::custom(msg)?, destination, name)
+            .await
+    }
 }
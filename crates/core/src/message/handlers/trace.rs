@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+
+use crate::error::Result;
+use crate::message::types::Message;
+use crate::message::types::TraceRouteReport;
+use crate::message::types::TraceRouteSend;
+use crate::message::HandleMsg;
+use crate::message::MessageHandler;
+use crate::message::MessageHandlerEvent;
+use crate::message::MessagePayload;
+use crate::utils::get_epoch_ms;
+
+/// Report to the sender before forwarding, so the route is traced until where it breaks.
+#[cfg_attr(feature = "wasm", async_trait(?Send))]
+#[cfg_attr(not(feature = "wasm"), async_trait)]
+impl HandleMsg<TraceRouteSend> for MessageHandler {
+    async fn handle(
+        &self,
+        ctx: &MessagePayload,
+        _: &TraceRouteSend,
+    ) -> Result<Vec<MessageHandlerEvent>> {
+        let reached = self.dht.did == ctx.relay.destination;
+        let report = MessageHandlerEvent::SendReportMessage(
+            ctx.clone(),
+            Message::TraceRouteReport(TraceRouteReport {
+                path: ctx.relay.path.clone(),
+                received_at_ms: get_epoch_ms(),
+                reached,
+            }),
+        );
+        if reached {
+            Ok(vec![report])
+        } else {
+            Ok(vec![
+                report,
+                MessageHandlerEvent::ForwardPayload(ctx.clone(), None),
+            ])
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", async_trait(?Send))]
+#[cfg_attr(not(feature = "wasm"), async_trait)]
+impl HandleMsg<TraceRouteReport> for MessageHandler {
+    async fn handle(
+        &self,
+        ctx: &MessagePayload,
+        _: &TraceRouteReport,
+    ) -> Result<Vec<MessageHandlerEvent>> {
+        if self.dht.did != ctx.relay.destination {
+            Ok(vec![MessageHandlerEvent::ForwardPayload(ctx.clone(), None)])
+        } else {
+            Ok(vec![])
+        }
+    }
+}
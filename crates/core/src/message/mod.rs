@@ -25,6 +25,23 @@ pub use handlers::MessageHandler;
 pub use handlers::MessageHandlerEvent;
 
 mod protocols;
+pub(crate) use protocols::deserialize_appended;
+pub use protocols::HandshakeInfo;
 pub use protocols::MessageRelay;
 pub use protocols::MessageVerification;
 pub use protocols::MessageVerificationExt;
+pub use protocols::ProtocolInfo;
+pub use protocols::CAPABILITIES;
+pub use protocols::CAPABILITY_CAS;
+pub use protocols::CAPABILITY_ENCRYPTED;
+pub use protocols::CAPABILITY_LEAVE;
+pub use protocols::CAPABILITY_LOOKUP;
+pub use protocols::CAPABILITY_MAILBOX;
+pub use protocols::CAPABILITY_REPLICA;
+pub use protocols::CAPABILITY_SUBRING;
+pub use protocols::CAPABILITY_SUBRING_ROUTE;
+pub use protocols::CAPABILITY_TOPIC;
+pub use protocols::CAPABILITY_TRACE;
+pub use protocols::LEGACY_PROTOCOL_VERSION;
+pub use protocols::MIN_PROTOCOL_VERSION;
+pub use protocols::PROTOCOL_VERSION;
@@ -16,9 +16,12 @@ use serde::Serialize;
 use super::encoder::Decoder;
 use super::encoder::Encoded;
 use super::encoder::Encoder;
+use super::protocols::deserialize_appended;
 use super::protocols::MessageRelay;
 use super::protocols::MessageVerification;
 use super::protocols::MessageVerificationExt;
+use super::types::EncryptedMessage;
+use super::types::Message;
 use crate::dht::Chord;
 use crate::dht::Did;
 use crate::dht::PeerRing;
@@ -106,6 +109,12 @@ pub struct MessagePayload {
     /// which is used to prove that payload was created by that node.
     #[derivative(Debug = "ignore")]
     pub verification: MessageVerification,
+    /// The name of subring that the message is routed within.
+    /// If it's None, the message is routed on the whole network.
+    /// It's appended to the payload and ignored by legacy nodes, so the payload should not be sent
+    /// to a peer lacking [CAPABILITY_SUBRING_ROUTE](crate::message::CAPABILITY_SUBRING_ROUTE).
+    #[serde(default, deserialize_with = "deserialize_appended")]
+    pub subring: Option<String>,
 }
 
 impl Transaction {
@@ -156,9 +165,16 @@ impl MessagePayload {
             transaction,
             relay,
             verification,
+            subring: None,
         })
     }
 
+    /// Route the message within the subring named `subring`.
+    pub fn within_subring(mut self, subring: &str) -> Self {
+        self.subring = Some(subring.to_string());
+        self
+    }
+
     /// Helps to create sending message from data.
     pub fn new_send<T>(
         data: T,
@@ -179,6 +195,19 @@ impl MessagePayload {
         Self::new(transaction, session_sk, relay)
     }
 
+    /// Decrypt the [EncryptedMessage] in transaction into
+    /// [CustomMessage](super::types::CustomMessage) by session_sk, other messages are returned as is.
+    /// The data of decrypted payload is changed, so it cannot pass the verification anymore.
+    pub fn decrypt(&self, session_sk: &SessionSk) -> Result<Self> {
+        let Ok(Message::EncryptedMessage(EncryptedMessage(data))) = self.transaction.data() else {
+            return Ok(self.clone());
+        };
+        let msg = Message::custom(&session_sk.decrypt(&data)?)?;
+        let mut payload = self.clone();
+        payload.transaction.data = bincode::serialize(&msg).map_err(Error::BincodeSerialize)?;
+        Ok(payload)
+    }
+
     /// Deserializes a `MessagePayload` instance from the given binary data.
     pub fn from_bincode(data: &[u8]) -> Result<Self> {
         bincode::deserialize(data).map_err(Error::BincodeDeserialize)
@@ -263,6 +292,21 @@ pub trait PayloadSender {
         }
     }
 
+    /// Infer the next hop for a message routed within subring by calling
+    /// `dht.subring_relay_successor()`. Only members of the subring will be chosen, unless
+    /// current node hasn't synced the subring.
+    fn infer_subring_next_hop(&self, subring: &str, destination: Did) -> Result<Did> {
+        if self.is_connected(destination) {
+            return Ok(destination);
+        }
+
+        match self.dht().subring_relay_successor(subring, destination)? {
+            PeerRingAction::Some(did) => Ok(did),
+            PeerRingAction::RemoteAction(did, _) => Ok(did),
+            _ => Err(Error::NoNextHop),
+        }
+    }
+
     /// Alias for `do_send_payload` that sets the next hop to `payload.relay.next_hop`.
     async fn send_payload(&self, payload: MessagePayload) -> Result<()> {
         self.do_send_payload(payload.relay.next_hop, payload).await
@@ -290,6 +334,29 @@ pub trait PayloadSender {
         let next_hop = self.infer_next_hop(None, destination)?;
         self.send_message_by_hop(msg, destination, next_hop).await
     }
+    /// Send a message to a specified destination within subring.
+    /// The message is relayed only by members of the subring.
+    /// Returns [Error::SubringNotSynced] if current node hasn't synced the subring.
+    async fn send_message_within_subring<T>(
+        &self,
+        msg: T,
+        destination: Did,
+        subring: &str,
+    ) -> Result<uuid::Uuid>
+    where
+        T: Serialize + Send,
+    {
+        if !self.dht().subrings.contains_key(subring) {
+            return Err(Error::SubringNotSynced(subring.to_string()));
+        }
+        let next_hop = self.infer_subring_next_hop(subring, destination)?;
+        let payload = MessagePayload::new_send(msg, self.session_sk(), next_hop, destination)?
+            .within_subring(subring);
+        let tx_id = payload.transaction.tx_id;
+        self.send_payload(payload).await?;
+        Ok(tx_id)
+    }
+
     /// Send a direct message to a specified destination.
     async fn send_direct_message<T>(&self, msg: T, destination: Did) -> Result<uuid::Uuid>
     where T: Serialize + Send {
@@ -298,6 +365,7 @@ pub trait PayloadSender {
     }
 
     /// Send a report message to a specified destination.
+    /// The report is routed within the same subring as the message.
     async fn send_report_message<T>(&self, payload: &MessagePayload, msg: T) -> Result<()>
     where T: Serialize + Send {
         let relay = payload.relay.report(self.dht().did)?;
@@ -309,20 +377,29 @@ pub trait PayloadSender {
             self.session_sk(),
         )?;
 
-        let pl = MessagePayload::new(transaction, self.session_sk(), relay)?;
+        let mut pl = MessagePayload::new(transaction, self.session_sk(), relay)?;
+        pl.subring = payload.subring.clone();
         self.send_payload(pl).await
     }
 
     /// Forward a payload message by relay.
     /// It just create a new payload, cloned data, resigned with session and send
     async fn forward_by_relay(&self, payload: &MessagePayload, relay: MessageRelay) -> Result<()> {
-        let new_pl = MessagePayload::new(payload.transaction.clone(), self.session_sk(), relay)?;
+        let mut new_pl =
+            MessagePayload::new(payload.transaction.clone(), self.session_sk(), relay)?;
+        new_pl.subring = payload.subring.clone();
         self.send_payload(new_pl).await
     }
 
     /// Forward a payload message, with the next hop inferred by the DHT.
+    /// If the payload is routed within subring, the next hop is inferred by the subring.
     async fn forward_payload(&self, payload: &MessagePayload, next_hop: Option<Did>) -> Result<()> {
-        let next_hop = self.infer_next_hop(next_hop, payload.relay.destination)?;
+        let next_hop = match (next_hop, &payload.subring) {
+            (None, Some(subring)) => {
+                self.infer_subring_next_hop(subring, payload.relay.destination)?
+            }
+            _ => self.infer_next_hop(next_hop, payload.relay.destination)?,
+        };
         let relay = payload.relay.forward(self.dht().did, next_hop)?;
         self.forward_by_relay(payload, relay).await
     }
@@ -394,6 +471,20 @@ pub mod test {
         assert_eq!(payload, payload2);
     }
 
+    #[test]
+    fn test_message_payload_within_subring() {
+        let next_hop = SecretKey::random().address().into();
+
+        let payload = new_test_payload(next_hop);
+        let payload2 = MessagePayload::from_bincode(&payload.to_bincode().unwrap()).unwrap();
+        assert_eq!(payload2.subring, None);
+
+        let payload = payload.within_subring("subring");
+        let payload2 = MessagePayload::from_bincode(&payload.to_bincode().unwrap()).unwrap();
+        assert_eq!(payload2.subring.as_deref(), Some("subring"));
+        assert_eq!(payload.relay, payload2.relay);
+    }
+
     #[test]
     fn test_message_payload_encode_len() {
         let next_hop = SecretKey::random().address().into();
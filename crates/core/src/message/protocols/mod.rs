@@ -1,6 +1,24 @@
 mod relay;
 mod verify;
+mod version;
 
 pub use self::relay::MessageRelay;
 pub use self::verify::MessageVerification;
 pub use self::verify::MessageVerificationExt;
+pub(crate) use self::version::deserialize_appended;
+pub use self::version::HandshakeInfo;
+pub use self::version::ProtocolInfo;
+pub use self::version::CAPABILITIES;
+pub use self::version::CAPABILITY_CAS;
+pub use self::version::CAPABILITY_ENCRYPTED;
+pub use self::version::CAPABILITY_LEAVE;
+pub use self::version::CAPABILITY_LOOKUP;
+pub use self::version::CAPABILITY_MAILBOX;
+pub use self::version::CAPABILITY_REPLICA;
+pub use self::version::CAPABILITY_SUBRING;
+pub use self::version::CAPABILITY_SUBRING_ROUTE;
+pub use self::version::CAPABILITY_TOPIC;
+pub use self::version::CAPABILITY_TRACE;
+pub use self::version::LEGACY_PROTOCOL_VERSION;
+pub use self::version::MIN_PROTOCOL_VERSION;
+pub use self::version::PROTOCOL_VERSION;
@@ -0,0 +1,341 @@
+#![warn(missing_docs)]
+
+//! Version and capabilities of wire protocol, exchanged in connection handshake.
+//!
+//! [Message] is encoded by bincode, which identifies variants by index and fields by order.
+//! So reordering, inserting or changing existing variants or fields breaks older nodes, and
+//! requires a new [PROTOCOL_VERSION]. A new variant must be appended after all existing ones,
+//! and requires a new capability: the message is not sent to a peer that doesn't declare the
+//! capability, instead of failing on decoding there.
+//!
+//! A new field can only be appended to the end of encoded data, where legacy nodes ignore it as
+//! trailing bytes, and it's decoded by [deserialize_appended] as missing if sent by them, like
+//! [MessagePayload::subring](crate::message::MessagePayload::subring) and the fields of
+//! [VirtualNode](crate::dht::vnode::VirtualNode) added after version 1.
+//!
+//! The protocol is declared by [HandshakeInfo], which is appended to the handshake messages
+//! in the same way. A peer that doesn't declare its protocol talks [ProtocolInfo::legacy].
+
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+
+use crate::dht::vnode::VNodeOperation;
+use crate::message::Message;
+
+/// Current version of wire protocol.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// The oldest version of wire protocol current node can talk with.
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Version of wire protocol talked by legacy nodes, which don't declare their protocol.
+pub const LEGACY_PROTOCOL_VERSION: u32 = 1;
+
+/// Capability of replicating virtual nodes, see [Message::OperateReplica] and
+/// [Message::SyncReplicaWithSuccessor].
+pub const CAPABILITY_REPLICA: &str = "replica";
+/// Capability of compare-and-swap operations of virtual nodes, see [Message::VNodeConflict].
+pub const CAPABILITY_CAS: &str = "cas";
+/// Capability of pushing topic messages to subscribers, see [Message::TopicMessage].
+pub const CAPABILITY_TOPIC: &str = "topic";
+/// Capability of end-to-end encrypted messages, see [Message::EncryptedMessage].
+pub const CAPABILITY_ENCRYPTED: &str = "encrypted";
+
+/// Capability of leaving the ring gracefully, see [Message::NotifyLeave] and
+/// [Message::SyncVNodeWithSuccessorReport].
+pub const CAPABILITY_LEAVE: &str = "leave";
+/// Capability of tracing the route to a node, see [Message::TraceRouteSend].
+pub const CAPABILITY_TRACE: &str = "trace";
+/// Capability of answering iterative lookup, see [Message::LookupSend].
+pub const CAPABILITY_LOOKUP: &str = "lookup";
+/// Capability of clearing mailbox once its messages were delivered, see [Message::MailboxAck].
+pub const CAPABILITY_MAILBOX: &str = "mailbox";
+/// Capability of leaving subring, see [VNodeOperation::LeaveSubring].
+pub const CAPABILITY_SUBRING: &str = "subring";
+/// Capability of routing messages within subring, see
+/// [MessagePayload::subring](crate::message::MessagePayload::subring).
+pub const CAPABILITY_SUBRING_ROUTE: &str = "subring_route";
+
+/// Capabilities supported by current node.
+pub const CAPABILITIES: &[&str] = &[
+    CAPABILITY_REPLICA,
+    CAPABILITY_CAS,
+    CAPABILITY_TOPIC,
+    CAPABILITY_ENCRYPTED,
+    CAPABILITY_LEAVE,
+    CAPABILITY_TRACE,
+    CAPABILITY_LOOKUP,
+    CAPABILITY_SUBRING_ROUTE,
+    CAPABILITY_MAILBOX,
+    CAPABILITY_SUBRING,
+];
+
+/// Protocol declared by a node in handshake.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolInfo {
+    /// Version of wire protocol.
+    pub version: u32,
+    /// The oldest version the node can talk with.
+    pub min_version: u32,
+    /// Capabilities supported by the node. Unknown ones are ignored.
+    pub capabilities: Vec<String>,
+}
+
+impl Default for ProtocolInfo {
+    fn default() -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            min_version: MIN_PROTOCOL_VERSION,
+            capabilities: CAPABILITIES.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+}
+
+/// Handshake info appended to [ConnectNodeSend](crate::message::ConnectNodeSend) and
+/// [ConnectNodeReport](crate::message::ConnectNodeReport), missing if sent by a legacy node.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HandshakeInfo {
+    /// Proof of knowing the key of private network, see
+    /// [AdmissionPolicy](crate::swarm::AdmissionPolicy).
+    pub network_proof: Option<Vec<u8>>,
+    /// Version and capabilities of wire protocol.
+    pub protocol: ProtocolInfo,
+}
+
+/// Deserialize a field appended to the end of a message, which is `None` if the message is sent
+/// by a legacy node. A field failing to decode is treated as missing, so it must be the last
+/// field of the message.
+pub(crate) fn deserialize_appended<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer).ok().flatten())
+}
+
+impl ProtocolInfo {
+    /// Protocol of a peer that doesn't declare one, which supports no capabilities.
+    pub fn legacy() -> Self {
+        Self {
+            version: LEGACY_PROTOCOL_VERSION,
+            min_version: LEGACY_PROTOCOL_VERSION,
+            capabilities: vec![],
+        }
+    }
+
+    /// Returns `true` if current node can talk with the node declaring this protocol.
+    pub fn is_compatible(&self) -> bool {
+        self.version >= MIN_PROTOCOL_VERSION && self.min_version <= PROTOCOL_VERSION
+    }
+
+    /// The version used to talk with the node declaring this protocol.
+    pub fn negotiated_version(&self) -> u32 {
+        self.version.min(PROTOCOL_VERSION)
+    }
+
+    /// Returns `true` if the node declaring this protocol supports capability.
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+}
+
+impl Message {
+    /// The capability a peer should support to decode this message, if any.
+    pub fn required_capability(&self) -> Option<&'static str> {
+        match self {
+            Message::OperateReplica(_)
+            | Message::SyncReplicaWithSuccessor(_)
+            | Message::OperateVNode(VNodeOperation::Repair(_)) => Some(CAPABILITY_REPLICA),
+            Message::VNodeConflict(_)
+            | Message::OperateVNode(VNodeOperation::CompareAndSwap(..)) => Some(CAPABILITY_CAS),
+            Message::TopicMessage(_)
+            | Message::OperateVNode(VNodeOperation::Subscribe(..))
+            | Message::OperateVNode(VNodeOperation::Unsubscribe(..)) => Some(CAPABILITY_TOPIC),
+            Message::EncryptedMessage(_) => Some(CAPABILITY_ENCRYPTED),
+            Message::NotifyLeave(_) | Message::SyncVNodeWithSuccessorReport(_) => {
+                Some(CAPABILITY_LEAVE)
+            }
+            Message::TraceRouteSend(_) | Message::TraceRouteReport(_) => Some(CAPABILITY_TRACE),
+            Message::LookupSend(_) | Message::LookupReport(_) => Some(CAPABILITY_LOOKUP),
+            Message::MailboxAck(_) => Some(CAPABILITY_MAILBOX),
+            Message::OperateVNode(VNodeOperation::LeaveSubring(..)) => Some(CAPABILITY_SUBRING),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dht::vnode::VirtualNode;
+    use crate::dht::Did;
+    use crate::ecc::SecretKey;
+    use crate::message::ConnectNodeSend;
+    use crate::message::MessagePayload;
+    use crate::message::MessageRelay;
+    use crate::message::MessageVerification;
+    use crate::message::MessageVerificationExt;
+    use crate::message::Transaction;
+    use crate::session::SessionSk;
+
+    /// [ConnectNodeSend] known by legacy nodes.
+    #[derive(Debug, Serialize, Deserialize)]
+    struct LegacyConnectNodeSend {
+        sdp: String,
+    }
+
+    /// [MessageRelay] known by legacy nodes.
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct LegacyMessageRelay {
+        path: Vec<Did>,
+        next_hop: Did,
+        destination: Did,
+    }
+
+    /// [MessagePayload] known by legacy nodes.
+    #[derive(Debug, Serialize, Deserialize)]
+    struct LegacyMessagePayload {
+        transaction: Transaction,
+        relay: LegacyMessageRelay,
+        verification: MessageVerification,
+    }
+
+    impl From<MessagePayload> for LegacyMessagePayload {
+        fn from(payload: MessagePayload) -> Self {
+            Self {
+                transaction: payload.transaction,
+                relay: LegacyMessageRelay {
+                    path: payload.relay.path,
+                    next_hop: payload.relay.next_hop,
+                    destination: payload.relay.destination,
+                },
+                verification: payload.verification,
+            }
+        }
+    }
+
+    #[test]
+    fn test_protocol_compatible() {
+        let current = ProtocolInfo::default();
+        assert!(current.is_compatible());
+        assert_eq!(current.negotiated_version(), PROTOCOL_VERSION);
+
+        let newer = ProtocolInfo {
+            version: PROTOCOL_VERSION + 1,
+            ..Default::default()
+        };
+        assert!(newer.is_compatible());
+        assert_eq!(newer.negotiated_version(), PROTOCOL_VERSION);
+
+        let too_new = ProtocolInfo {
+            version: PROTOCOL_VERSION + 2,
+            min_version: PROTOCOL_VERSION + 1,
+            ..Default::default()
+        };
+        assert!(!too_new.is_compatible());
+
+        let too_old = ProtocolInfo {
+            version: MIN_PROTOCOL_VERSION - 1,
+            min_version: 0,
+            capabilities: vec![],
+        };
+        assert!(!too_old.is_compatible());
+    }
+
+    #[test]
+    fn test_protocol_capabilities() {
+        let legacy = ProtocolInfo {
+            capabilities: vec![CAPABILITY_TOPIC.to_string(), "unknown".to_string()],
+            ..Default::default()
+        };
+        assert!(legacy.supports(CAPABILITY_TOPIC));
+        assert!(!legacy.supports(CAPABILITY_ENCRYPTED));
+        assert!(ProtocolInfo::default().supports(CAPABILITY_ENCRYPTED));
+
+        let legacy = ProtocolInfo::legacy();
+        assert!(legacy.is_compatible());
+        assert!(CAPABILITIES.iter().all(|c| !legacy.supports(c)));
+    }
+
+    #[test]
+    fn test_operate_vnode_required_capability() {
+        let did: Did = SecretKey::random().address().into();
+        let subscribe = Message::OperateVNode(VNodeOperation::Subscribe("topic".into(), did));
+        let unsubscribe = Message::OperateVNode(VNodeOperation::Unsubscribe("topic".into(), did));
+        assert_eq!(subscribe.required_capability(), Some(CAPABILITY_TOPIC));
+        assert_eq!(unsubscribe.required_capability(), Some(CAPABILITY_TOPIC));
+
+        let vnode = VirtualNode::try_from("data".to_string()).unwrap();
+        let repair = Message::OperateVNode(VNodeOperation::Repair(vnode));
+        assert_eq!(repair.required_capability(), Some(CAPABILITY_REPLICA));
+
+        let join = Message::OperateVNode(VNodeOperation::JoinSubring("subring".into(), did));
+        assert_eq!(join.required_capability(), None);
+        let leave = Message::OperateVNode(VNodeOperation::LeaveSubring("subring".into(), did));
+        assert_eq!(leave.required_capability(), Some(CAPABILITY_SUBRING));
+    }
+
+    #[test]
+    fn test_handshake_info_skipped_by_legacy_nodes() {
+        let msg = ConnectNodeSend {
+            sdp: "sdp".to_string(),
+            handshake: Some(HandshakeInfo {
+                network_proof: Some(vec![1, 2, 3]),
+                protocol: ProtocolInfo::default(),
+            }),
+        };
+        let encoded = bincode::serialize(&msg).unwrap();
+        let legacy: LegacyConnectNodeSend = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(legacy.sdp, "sdp");
+
+        let encoded = bincode::serialize(&legacy).unwrap();
+        let msg: ConnectNodeSend = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(msg.sdp, "sdp");
+        assert!(msg.handshake.is_none());
+    }
+
+    #[test]
+    fn test_message_payload_with_legacy_nodes() {
+        let session_sk = SessionSk::new_with_seckey(&SecretKey::random()).unwrap();
+        let next_hop: Did = SecretKey::random().address().into();
+        let msg = Message::ConnectNodeSend(ConnectNodeSend {
+            sdp: "sdp".to_string(),
+            handshake: Some(HandshakeInfo {
+                network_proof: Some(vec![1, 2, 3]),
+                protocol: ProtocolInfo::default(),
+            }),
+        });
+        let payload = MessagePayload::new_send(msg, &session_sk, next_hop, next_hop)
+            .unwrap()
+            .within_subring("subring");
+
+        // Payload sent to legacy nodes.
+        let encoded = payload.to_bincode().unwrap();
+        let legacy: LegacyMessagePayload = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(
+            legacy.relay,
+            LegacyMessagePayload::from(payload.clone()).relay
+        );
+        let (variant, msg): (u32, LegacyConnectNodeSend) =
+            bincode::deserialize(&legacy.transaction.data).unwrap();
+        assert_eq!(msg.sdp, "sdp");
+
+        // Payload sent by legacy nodes.
+        let transaction =
+            Transaction::new(next_hop, uuid::Uuid::new_v4(), (variant, msg), &session_sk).unwrap();
+        let relay = MessageRelay::new(vec![session_sk.account_did()], next_hop, next_hop);
+        let legacy: LegacyMessagePayload = MessagePayload::new(transaction, &session_sk, relay)
+            .unwrap()
+            .into();
+        let encoded = bincode::serialize(&legacy).unwrap();
+        let payload = MessagePayload::from_bincode(&encoded).unwrap();
+        assert!(payload.verify());
+        assert!(payload.subring.is_none());
+        let Message::ConnectNodeSend(msg) = payload.transaction.data().unwrap() else {
+            panic!("unexpected message");
+        };
+        assert_eq!(msg.sdp, "sdp");
+        assert!(msg.handshake.is_none());
+    }
+}
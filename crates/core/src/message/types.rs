@@ -7,11 +7,16 @@ use serde::Deserialize;
 use serde::Serialize;
 
 use crate::chunk::Chunk;
+use crate::dht::vnode::vnode_list;
 use crate::dht::vnode::VNodeOperation;
 use crate::dht::vnode::VirtualNode;
 use crate::dht::Did;
 use crate::dht::TopoInfo;
 use crate::error::Result;
+use crate::message::deserialize_appended;
+use crate::message::Encoded;
+use crate::message::HandshakeInfo;
+use crate::session::SessionPubkey;
 
 /// The `Then` trait is used to associate a type with a "then" scenario.
 pub trait Then {
@@ -24,6 +29,9 @@ pub trait Then {
 pub struct ConnectNodeSend {
     /// sdp offer of webrtc
     pub sdp: String,
+    /// network proof and protocol of sender, `None` if sent by a legacy node
+    #[serde(default, deserialize_with = "deserialize_appended")]
+    pub handshake: Option<HandshakeInfo>,
 }
 
 /// MessageType report to origin with own transport_uuid and handshake_info.
@@ -31,6 +39,9 @@ pub struct ConnectNodeSend {
 pub struct ConnectNodeReport {
     /// sdp answer of webrtc
     pub sdp: String,
+    /// network proof and protocol of sender, `None` if sent by a legacy node
+    #[serde(default, deserialize_with = "deserialize_appended")]
+    pub handshake: Option<HandshakeInfo>,
 }
 
 /// MessageType use to find successor in a chord ring.
@@ -156,20 +167,126 @@ pub struct SearchVNode {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct FoundVNode {
     /// Response of [SearchVNode], containing response data
+    #[serde(with = "vnode_list")]
     pub data: Vec<VirtualNode>,
 }
 
+/// MessageType use to operate a replica of virtual node.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OperateReplica {
+    /// The virtual id of replica, which is one of the affine positions of target virtual node.
+    pub vid: Did,
+    /// The operation applied to the replica.
+    pub op: VNodeOperation,
+}
+
+/// MessageType report to origin that a [VNodeOperation::CompareAndSwap] was rejected.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct VNodeConflict {
+    /// The did of target virtual node.
+    pub did: Did,
+    /// The version expected by the operation.
+    pub expected: u64,
+    /// The current virtual node stored on the replica, if existed.
+    pub current: Option<VirtualNode>,
+}
+
 /// MessageType after `FindSuccessorSend` and syncing data.
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SyncVNodeWithSuccessor {
     /// Data of virtual nodes for syncing.
+    #[serde(with = "vnode_list")]
     pub data: Vec<VirtualNode>,
 }
 
+/// MessageType after `FindSuccessorSend` and syncing replicas of virtual nodes.
+/// Unlike [SyncVNodeWithSuccessor], the receiver reports by [SyncVNodeWithSuccessorReport].
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SyncReplicaWithSuccessor {
+    /// Data of virtual nodes for syncing, paired with the replica positions they are stored at.
+    pub data: Vec<(Did, VirtualNode)>,
+}
+
+/// MessageType report to the sender of [SyncReplicaWithSuccessor] after the virtual nodes are stored.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SyncVNodeWithSuccessorReport {
+    /// Number of virtual nodes stored.
+    pub count: u64,
+}
+
+/// MessageType use to notify neighbours that the sender is leaving the ring.
+/// The sender is identified by the signer of transaction.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NotifyLeave {
+    /// The successors of leaving node, which the predecessor should take over.
+    pub successors: Vec<Did>,
+    /// The predecessor of leaving node, which the successors should take over.
+    pub predecessor: Option<Did>,
+}
+
+/// MessageType use to probe the route to destination. Every node handling it reports to the
+/// sender by [TraceRouteReport], then forwards it by the normal routing.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TraceRouteSend {}
+
+/// MessageType report to the sender of [TraceRouteSend] by a node on the route.
+/// The reporter is identified by the signer of transaction.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TraceRouteReport {
+    /// The nodes the probe passed before reaching reporter, starting from the sender.
+    pub path: Vec<Did>,
+    /// The time reporter received the probe, in unix timestamp milliseconds of its clock.
+    pub received_at_ms: u128,
+    /// Whether reporter is the destination of probe.
+    pub reached: bool,
+}
+
+/// MessageType use to ask a node for the successor of did by its local view, without forwarding.
+/// It's sent by the origin of an iterative lookup to each hop it queries.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LookupSend {
+    /// did of target
+    pub did: Did,
+}
+
+/// MessageType report to the sender of [LookupSend].
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LookupReport {
+    /// did of target
+    pub did: Did,
+    /// The successor of target, if reporter knows it.
+    pub successor: Option<Did>,
+    /// Nodes known by reporter preceding target, closest to target first.
+    /// If successor is found, they are the successors of reporter to fall back on.
+    pub closer: Vec<Did>,
+}
+
+/// MessageType use to acknowledge the node keeping the mailbox of sender that the messages in
+/// it were delivered, so that it can be cleared.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MailboxAck {
+    /// The version of mailbox delivered. The mailbox is kept if it's extended since then.
+    pub version: u64,
+}
+
+/// MessageType use to push new data of a subscribed topic to subscriber.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TopicMessage {
+    /// The virtual id of topic.
+    pub vid: Did,
+    /// New data extended or touched to the topic.
+    pub data: Vec<Encoded>,
+}
+
 /// MessageType use to customize message, will be handle by `custom_message` method.
 #[derive(Deserialize, Serialize, Clone)]
 pub struct CustomMessage(pub Vec<u8>);
 
+/// MessageType of custom message encrypted to the session of destination.
+/// It will be decrypted into [CustomMessage] before delivering to callback of destination.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct EncryptedMessage(pub Vec<u8>);
+
 /// MessageType enum Report contain FindSuccessorSend.
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[non_exhaustive]
@@ -228,6 +345,30 @@ pub enum Message {
     QueryForTopoInfoReport(QueryForTopoInfoReport),
     /// A chunk that can be deserialized to a payload.
     Chunk(Chunk),
+    /// Remote message of pushing new data of a topic to subscriber.
+    TopicMessage(TopicMessage),
+    /// Remote message of operations of a replica of virtual node.
+    OperateReplica(OperateReplica),
+    /// Remote message for syncing replicas of virtual nodes.
+    SyncReplicaWithSuccessor(SyncReplicaWithSuccessor),
+    /// Response when a compare-and-swap operation of virtual node was rejected.
+    VNodeConflict(VNodeConflict),
+    /// End-to-end encrypted custom messages
+    EncryptedMessage(EncryptedMessage),
+    /// Remote message of notifying neighbours that the sender is leaving the ring.
+    NotifyLeave(NotifyLeave),
+    /// Response of SyncReplicaWithSuccessor
+    SyncVNodeWithSuccessorReport(SyncVNodeWithSuccessorReport),
+    /// Remote message of probing the route to destination.
+    TraceRouteSend(TraceRouteSend),
+    /// Response of TraceRouteSend, sent by every node on the route.
+    TraceRouteReport(TraceRouteReport),
+    /// Remote message of querying a hop of iterative lookup.
+    LookupSend(LookupSend),
+    /// Response of LookupSend.
+    LookupReport(LookupReport),
+    /// Remote message of acknowledging that the messages in mailbox were delivered.
+    MailboxAck(MailboxAck),
 }
 
 impl std::fmt::Display for Message {
@@ -241,6 +382,13 @@ impl Message {
     pub fn custom(msg: &[u8]) -> Result<Message> {
         Ok(Message::CustomMessage(CustomMessage(msg.to_vec())))
     }
+
+    /// Encrypt a data of message to the session of destination, then wrap it into EncryptedMessage.
+    pub fn encrypted(msg: &[u8], pubkey: &SessionPubkey) -> Result<Message> {
+        Ok(Message::EncryptedMessage(EncryptedMessage(
+            pubkey.encrypt(msg)?,
+        )))
+    }
 }
 
 impl std::fmt::Debug for CustomMessage {
@@ -250,3 +398,11 @@ impl std::fmt::Debug for CustomMessage {
             .finish()
     }
 }
+
+impl std::fmt::Debug for EncryptedMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptedMessage")
+            .field("size", &self.0.len())
+            .finish()
+    }
+}
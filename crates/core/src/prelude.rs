@@ -5,6 +5,7 @@ pub use base58;
 pub use dashmap;
 pub use futures;
 pub use libsecp256k1;
+pub use rings_transport;
 pub use url;
 pub use uuid;
 
@@ -149,6 +149,19 @@ pub struct Session {
     sig: Vec<u8>,
 }
 
+/// SessionPubkey is the public key of a [Session], which can be published to let others
+/// encrypt messages to the account of session.
+///
+/// To check the public key is really held by the session, use `verify()` method.
+/// Messages encrypted by `encrypt(msg)` can be decrypted by `decrypt(msg)` of [SessionSk].
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct SessionPubkey {
+    /// Session that the public key belongs to.
+    pub session: Session,
+    /// The public key of session.
+    pub pubkey: PublicKey,
+}
+
 /// We will support as many protocols/algorithms as possible.
 /// Currently, it comprises Secp256k1, EIP191, BIP137, and Ed25519.
 /// We welcome any issues and PRs for additional implementations.
@@ -333,6 +346,27 @@ impl Session {
     }
 }
 
+impl SessionPubkey {
+    /// Verify that the session is valid and the public key belongs to it.
+    pub fn verify(&self) -> Result<()> {
+        self.session.verify_self()?;
+        if Did::from(self.pubkey.address()) != self.session.session_id {
+            return Err(Error::InvalidSessionPubkey);
+        }
+        Ok(())
+    }
+
+    /// Encrypt message to the session.
+    pub fn encrypt(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        ecies::encrypt(&self.pubkey.0, msg).map_err(Error::MessageEncryptionFailed)
+    }
+
+    /// Get account did of session.
+    pub fn account_did(&self) -> Did {
+        self.session.account_did()
+    }
+}
+
 impl SessionSk {
     /// Generate Session with private key. Only use it for unittest.
     /// To protect your private key, please use [SessionSkBuilder] to generate session.
@@ -365,6 +399,19 @@ impl SessionSk {
         self.session.account_did()
     }
 
+    /// Get the public key of session, for others to encrypt messages to this session.
+    pub fn session_pubkey(&self) -> SessionPubkey {
+        SessionPubkey {
+            session: self.session(),
+            pubkey: self.sk.pubkey(),
+        }
+    }
+
+    /// Decrypt message encrypted by [SessionPubkey].
+    pub fn decrypt(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        ecies::decrypt(&self.sk.ser(), msg).map_err(Error::MessageDecryptionFailed)
+    }
+
     /// Dump session_sk to string, allowing user to save it in a config file.
     /// It can be restored using `SessionSk::from_str`.
     pub fn dump(&self) -> Result<String> {
@@ -394,6 +441,27 @@ mod test {
         assert_eq!(key.pubkey(), pubkey);
     }
 
+    #[test]
+    pub fn test_session_pubkey_encrypt_decrypt() {
+        let key = SecretKey::random();
+        let sm = SessionSk::new_with_seckey(&key).unwrap();
+        let pubkey = sm.session_pubkey();
+        assert!(pubkey.verify().is_ok());
+        assert_eq!(pubkey.account_did(), Did::from(key.address()));
+
+        let encrypted = pubkey.encrypt(b"hello").unwrap();
+        assert_ne!(encrypted, b"hello".to_vec());
+        assert_eq!(sm.decrypt(&encrypted).unwrap(), b"hello".to_vec());
+
+        let other = SessionSk::new_with_seckey(&SecretKey::random()).unwrap();
+        assert!(other.decrypt(&encrypted).is_err());
+        let forged = SessionPubkey {
+            pubkey: other.session_pubkey().pubkey,
+            ..pubkey
+        };
+        assert!(forged.verify().is_err());
+    }
+
     #[test]
     pub fn test_dump_restore() {
         let key = SecretKey::random();
@@ -15,6 +15,7 @@ use crate::storage::KvStorageInterface;
 #[allow(dead_code)]
 pub struct SledStorage {
     db: sled::Db,
+    tree: sled::Tree,
     cap: u32,
     path: String,
 }
@@ -32,11 +33,22 @@ impl SledStorage {
             .open()
             .map_err(Error::SledError)?;
         Ok(Self {
+            tree: (*db).clone(),
             db,
             cap,
             path: path.as_ref().to_string_lossy().to_string(),
         })
     }
+
+    /// Open a named tree of the same db, which is a keyspace separated from the default one.
+    pub fn open_tree(&self, name: &str) -> Result<Self> {
+        Ok(Self {
+            db: self.db.clone(),
+            tree: self.db.open_tree(name).map_err(Error::SledError)?,
+            cap: self.cap,
+            path: self.path.clone(),
+        })
+    }
 }
 
 #[async_trait]
@@ -44,7 +56,7 @@ impl<V> KvStorageInterface<V> for SledStorage
 where V: Serialize + DeserializeOwned + Sync
 {
     async fn get(&self, key: &str) -> Result<Option<V>> {
-        let v = self.db.get(key).map_err(Error::SledError)?;
+        let v = self.tree.get(key).map_err(Error::SledError)?;
         if let Some(v) = v {
             let v = v.as_ref();
             return bincode::deserialize(v)
@@ -57,12 +69,12 @@ where V: Serialize + DeserializeOwned + Sync
     async fn put(&self, key: &str, value: &V) -> Result<()> {
         let data = bincode::serialize(&value).map_err(Error::BincodeSerialize)?;
         tracing::debug!("Try inserting key: {:?}", key);
-        self.db.insert(key, data).map_err(Error::SledError)?;
+        self.tree.insert(key, data).map_err(Error::SledError)?;
         Ok(())
     }
 
     async fn get_all(&self) -> Result<Vec<(String, V)>> {
-        let iter = self.db.iter();
+        let iter = self.tree.iter();
         Ok(iter
             .flatten()
             .flat_map(|(k, v)| {
@@ -75,19 +87,19 @@ where V: Serialize + DeserializeOwned + Sync
     }
 
     async fn remove(&self, key: &str) -> Result<()> {
-        self.db
+        self.tree
             .remove(key.to_string().as_bytes())
             .map_err(Error::SledError)?;
         Ok(())
     }
 
     async fn clear(&self) -> Result<()> {
-        self.db.clear().map_err(Error::SledError)?;
+        self.tree.clear().map_err(Error::SledError)?;
         Ok(())
     }
 
     async fn count(&self) -> Result<u32> {
-        Ok(self.db.len() as u32)
+        Ok(self.tree.len() as u32)
     }
 }
 
@@ -183,4 +195,25 @@ mod test {
 
         drop(storage)
     }
+
+    #[tokio::test]
+    async fn test_kv_storage_open_tree() {
+        let storage = SledStorage::new_with_cap_and_path(4096, "tmp/test_db_tree")
+            .await
+            .unwrap();
+        let tree = storage.open_tree("tree").unwrap();
+        let data: u64 = 101;
+        tree.put("key", &data).await.unwrap();
+
+        let got: Option<u64> = storage.get("key").await.unwrap();
+        assert!(got.is_none());
+        let got: Option<u64> = tree.get("key").await.unwrap();
+        assert_eq!(got, Some(data));
+
+        <SledStorage as KvStorageInterface<u64>>::clear::<'_, '_>(&tree)
+            .await
+            .unwrap();
+        drop(tree);
+        drop(storage)
+    }
 }
@@ -0,0 +1,140 @@
+//! Admission of peers.
+//!
+//! [AdmissionPolicy] decides which peers can connect to swarm. It's checked in
+//! [Judegement::should_connect](super::impls::Judegement::should_connect) before connecting,
+//! and in handshake before answering an offer or accepting an answer.
+//!
+//! In private network mode, nodes share a secret [AdmissionPolicy::network_key]. Both offer and
+//! answer carry a proof in [HandshakeInfo](crate::message::HandshakeInfo), which is the hash of
+//! the key, the did of sender and the sdp. So the key itself is never sent, and peers from other
+//! rings, as well as legacy nodes not sending the proof, are rejected in handshake.
+use std::collections::HashSet;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::dht::Did;
+use crate::ecc::keccak256;
+use crate::error::Error;
+use crate::error::Result;
+use crate::swarm::Swarm;
+
+/// Policy of admitting peers, everyone is admitted by default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdmissionPolicy {
+    /// Only these peers are admitted if set.
+    pub allowlist: Option<HashSet<Did>>,
+    /// These peers are never admitted, even if they are in allowlist.
+    pub denylist: HashSet<Did>,
+    /// The pre-shared key of a private network. Peers without the same key are rejected.
+    pub network_key: Option<String>,
+}
+
+impl AdmissionPolicy {
+    /// Returns `true` if did passes allowlist and denylist.
+    pub fn is_admitted(&self, did: Did) -> bool {
+        if self.denylist.contains(&did) {
+            return false;
+        }
+        self.allowlist
+            .as_ref()
+            .map_or(true, |allowlist| allowlist.contains(&did))
+    }
+
+    /// Prove that sender of sdp knows the network key. Returns `None` if not in private network.
+    pub fn network_proof(&self, sender: Did, sdp: &str) -> Option<Vec<u8>> {
+        let key = self.network_key.as_ref()?;
+        let mut msg = vec![];
+        msg.extend_from_slice(key.as_bytes());
+        msg.extend_from_slice(sender.as_bytes());
+        msg.extend_from_slice(sdp.as_bytes());
+        Some(keccak256(&msg).to_vec())
+    }
+
+    /// Check the handshake message from peer, which carries sdp and network proof.
+    pub fn check_handshake(&self, peer: Did, sdp: &str, proof: Option<&[u8]>) -> Result<()> {
+        if !self.is_admitted(peer) {
+            return Err(Error::PeerNotAdmitted(peer));
+        }
+        if let Some(expected) = self.network_proof(peer, sdp) {
+            if proof != Some(expected.as_slice()) {
+                return Err(Error::NetworkMismatch(peer));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Swarm {
+    /// Returns `true` if did is admitted by the [AdmissionPolicy] of swarm.
+    pub fn is_admitted(&self, did: Did) -> bool {
+        self.admission.is_admitted(did)
+    }
+
+    /// Prove that sdp is sent by swarm in the same private network.
+    pub(crate) fn network_proof(&self, sdp: &str) -> Option<Vec<u8>> {
+        self.admission.network_proof(self.did(), sdp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecc::SecretKey;
+
+    #[test]
+    fn test_allowlist_and_denylist() {
+        let did1: Did = SecretKey::random().address().into();
+        let did2: Did = SecretKey::random().address().into();
+        let did3: Did = SecretKey::random().address().into();
+
+        let policy = AdmissionPolicy::default();
+        assert!(policy.is_admitted(did1));
+
+        let policy = AdmissionPolicy {
+            allowlist: Some([did1, did2].into()),
+            denylist: [did2].into(),
+            ..Default::default()
+        };
+        assert!(policy.is_admitted(did1));
+        assert!(!policy.is_admitted(did2));
+        assert!(!policy.is_admitted(did3));
+    }
+
+    #[test]
+    fn test_network_proof() {
+        let did: Did = SecretKey::random().address().into();
+        let staging = AdmissionPolicy {
+            network_key: Some("staging".to_string()),
+            ..Default::default()
+        };
+        let production = AdmissionPolicy {
+            network_key: Some("production".to_string()),
+            ..Default::default()
+        };
+
+        let proof = staging.network_proof(did, "sdp");
+        assert!(proof.is_some());
+        assert!(staging
+            .check_handshake(did, "sdp", proof.as_deref())
+            .is_ok());
+        assert!(matches!(
+            staging.check_handshake(did, "other sdp", proof.as_deref()),
+            Err(Error::NetworkMismatch(_))
+        ));
+        assert!(matches!(
+            production.check_handshake(did, "sdp", proof.as_deref()),
+            Err(Error::NetworkMismatch(_))
+        ));
+        assert!(matches!(
+            staging.check_handshake(did, "sdp", None),
+            Err(Error::NetworkMismatch(_))
+        ));
+
+        // Public network ignores the proof.
+        let public = AdmissionPolicy::default();
+        assert!(public.network_proof(did, "sdp").is_none());
+        assert!(public.check_handshake(did, "sdp", proof.as_deref()).is_ok());
+    }
+}
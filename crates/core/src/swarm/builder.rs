@@ -2,9 +2,14 @@
 //! This module provider [SwarmBuilder] and it's interface for
 //! [Swarm]
 
+#[cfg(all(feature = "websocket", not(feature = "dummy")))]
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::sync::RwLock;
 
+use dashmap::DashSet;
+
 use crate::channels::Channel;
 use crate::dht::PeerRing;
 use crate::dht::VNodeStorage;
@@ -12,8 +17,23 @@ use crate::message::MessageHandler;
 use crate::session::SessionSk;
 use crate::swarm::callback::SharedSwarmCallback;
 use crate::swarm::callback::SwarmCallback;
+use crate::swarm::AdmissionPolicy;
+use crate::swarm::ConnectionLimits;
+use crate::swarm::InboundLimiter;
+use crate::swarm::InboundLimits;
+use crate::swarm::KnownPeers;
+use crate::swarm::LastActive;
+use crate::swarm::LookupPolicy;
 use crate::swarm::MeasureImpl;
+use crate::swarm::PeerProtocols;
+use crate::swarm::PendingRequests;
+use crate::swarm::ReconnectPolicy;
+use crate::swarm::Reconnector;
+use crate::swarm::ReplayCache;
+use crate::swarm::ReplayedMessages;
+use crate::swarm::RoutingStorage;
 use crate::swarm::Swarm;
+use crate::swarm::DEFAULT_REPLAY_CACHE_CAPACITY;
 use crate::types::channel::Channel as ChannelTrait;
 use crate::types::Transport;
 
@@ -24,12 +44,21 @@ impl SwarmCallback for DefaultCallback {}
 pub struct SwarmBuilder {
     ice_servers: String,
     external_address: Option<String>,
+    #[cfg(all(feature = "websocket", not(feature = "dummy")))]
+    websocket_listener: Option<(SocketAddr, String)>,
     dht_succ_max: u8,
     dht_storage: VNodeStorage,
     session_sk: SessionSk,
     session_ttl: Option<usize>,
     measure: Option<MeasureImpl>,
     callback: Option<SharedSwarmCallback>,
+    reconnect_policy: ReconnectPolicy,
+    connection_limits: ConnectionLimits,
+    inbound_limits: InboundLimits,
+    replay_cache_capacity: usize,
+    admission: AdmissionPolicy,
+    routing_storage: Option<RoutingStorage>,
+    lookup_policy: LookupPolicy,
 }
 
 impl SwarmBuilder {
@@ -38,12 +67,21 @@ impl SwarmBuilder {
         SwarmBuilder {
             ice_servers: ice_servers.to_string(),
             external_address: None,
+            #[cfg(all(feature = "websocket", not(feature = "dummy")))]
+            websocket_listener: None,
             dht_succ_max: 3,
             dht_storage,
             session_sk,
             session_ttl: None,
             measure: None,
             callback: None,
+            reconnect_policy: ReconnectPolicy::default(),
+            connection_limits: ConnectionLimits::default(),
+            inbound_limits: InboundLimits::default(),
+            replay_cache_capacity: DEFAULT_REPLAY_CACHE_CAPACITY,
+            admission: AdmissionPolicy::default(),
+            routing_storage: None,
+            lookup_policy: LookupPolicy::default(),
         }
     }
 
@@ -60,6 +98,14 @@ impl SwarmBuilder {
         self
     }
 
+    /// Sets up the address to accept websocket connections, and the url remote peers use to
+    /// dial it. Without this, the swarm can only connect to peers that are listening.
+    #[cfg(all(feature = "websocket", not(feature = "dummy")))]
+    pub fn websocket_listener(mut self, addr: SocketAddr, public_url: String) -> Self {
+        self.websocket_listener = Some((addr, public_url));
+        self
+    }
+
     /// Setup timeout for session.
     pub fn session_ttl(mut self, ttl: usize) -> Self {
         self.session_ttl = Some(ttl);
@@ -78,6 +124,50 @@ impl SwarmBuilder {
         self
     }
 
+    /// Setup the policy of reconnecting dropped peers.
+    pub fn reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = policy;
+        self
+    }
+
+    /// Setup the limits of connections, which is unlimited by default.
+    pub fn connection_limits(mut self, limits: ConnectionLimits) -> Self {
+        self.connection_limits = limits;
+        self
+    }
+
+    /// Setup the rate limits and quotas of inbound traffic.
+    pub fn inbound_limits(mut self, limits: InboundLimits) -> Self {
+        self.inbound_limits = limits;
+        self
+    }
+
+    /// Setup the number of transactions remembered to drop replayed messages.
+    /// Set to `0` to disable replay protection.
+    pub fn replay_cache_capacity(mut self, capacity: usize) -> Self {
+        self.replay_cache_capacity = capacity;
+        self
+    }
+
+    /// Setup the policy of admitting peers, which admits everyone by default.
+    pub fn admission(mut self, policy: AdmissionPolicy) -> Self {
+        self.admission = policy;
+        self
+    }
+
+    /// Setup the storage to persist routing state, which is used to rejoin the ring after
+    /// restart. Routing state is not persisted by default.
+    pub fn routing_storage(mut self, storage: RoutingStorage) -> Self {
+        self.routing_storage = Some(storage);
+        self
+    }
+
+    /// Setup the policy of iterative lookup. Lookups are recursive by default.
+    pub fn lookup_policy(mut self, policy: LookupPolicy) -> Self {
+        self.lookup_policy = policy;
+        self
+    }
+
     /// Try build for `Swarm`.
     pub fn build(self) -> Swarm {
         let dht_did = self.session_sk.account_did();
@@ -91,7 +181,14 @@ impl SwarmBuilder {
         let message_handler = MessageHandler::new(dht.clone());
 
         let transport_event_channel = Channel::new();
+        #[cfg(not(all(feature = "websocket", not(feature = "dummy"))))]
         let transport = Box::new(Transport::new(&self.ice_servers, self.external_address));
+        #[cfg(all(feature = "websocket", not(feature = "dummy")))]
+        let transport = Box::new(Transport::new(
+            &self.ice_servers,
+            self.external_address,
+            self.websocket_listener,
+        ));
 
         let callback = RwLock::new(
             self.callback
@@ -106,6 +203,20 @@ impl SwarmBuilder {
             message_handler,
             transport,
             callback,
+            pending_requests: Arc::new(PendingRequests::default()),
+            replayed_messages: ReplayedMessages::default(),
+            session_pubkey_published: AtomicBool::new(false),
+            reconnector: Reconnector::new(self.reconnect_policy),
+            connection_limits: self.connection_limits,
+            last_active: LastActive::default(),
+            inbound_limiter: Arc::new(InboundLimiter::new(self.inbound_limits)),
+            replay_cache: Arc::new(ReplayCache::new(self.replay_cache_capacity)),
+            admission: self.admission,
+            peer_protocols: PeerProtocols::default(),
+            known_peers: KnownPeers::default(),
+            routing_storage: self.routing_storage,
+            lookup_policy: self.lookup_policy,
+            joined_peers: DashSet::new(),
         }
     }
 }
@@ -1,6 +1,7 @@
 use std::str::FromStr;
 use std::sync::Arc;
 
+use async_recursion::async_recursion;
 use async_trait::async_trait;
 use futures::lock::Mutex as FuturesMutex;
 use rings_transport::core::callback::TransportCallback;
@@ -10,10 +11,15 @@ use crate::channels::Channel;
 use crate::chunk::ChunkList;
 use crate::chunk::ChunkManager;
 use crate::consts::TRANSPORT_MTU;
+use crate::dht::vnode::VirtualNode;
 use crate::dht::Did;
 use crate::message::Message;
 use crate::message::MessagePayload;
 use crate::message::MessageVerificationExt;
+use crate::session::SessionSk;
+use crate::swarm::InboundLimiter;
+use crate::swarm::PendingRequests;
+use crate::swarm::ReplayCache;
 use crate::types::channel::Channel as ChannelTrait;
 use crate::types::channel::TransportEvent;
 
@@ -39,6 +45,52 @@ pub enum SwarmEvent {
         /// The final state of the connection.
         state: WebrtcConnectionState,
     },
+    /// Indicates that the successor list of local DHT has changed.
+    SuccessorsChange {
+        /// The latest successor list.
+        successors: Vec<Did>,
+    },
+    /// Indicates that the predecessor of local DHT has changed.
+    PredecessorChange {
+        /// The latest predecessor.
+        predecessor: Option<Did>,
+    },
+    /// Indicates that virtual nodes were fetched from DHT, either from local storage
+    /// or from a `FoundVNode` reported by remote peer.
+    StorageHit {
+        /// The virtual nodes that were found.
+        data: Vec<VirtualNode>,
+    },
+    /// Indicates that a compare-and-swap operation was rejected by a remote replica.
+    StorageConflict {
+        /// The did of target virtual node.
+        did: Did,
+        /// The version expected by the operation.
+        expected: u64,
+        /// The current virtual node stored on the replica, if existed.
+        current: Option<VirtualNode>,
+    },
+    /// Indicates that an attempt of reconnecting a dropped peer was made.
+    ReconnectAttempt {
+        /// The did of remote peer.
+        peer: Did,
+        /// The number of attempts made, starting from 1.
+        attempt: u32,
+        /// The error raised when starting the attempt, if any.
+        error: Option<String>,
+    },
+    /// Indicates that a connection was closed to make room for a new one.
+    ConnectionEvicted {
+        /// The did of remote peer.
+        peer: Did,
+    },
+    /// Indicates that reconnecting a dropped peer was given up.
+    ReconnectGaveUp {
+        /// The did of remote peer.
+        peer: Did,
+        /// The number of attempts made.
+        attempts: u32,
+    },
 }
 
 /// Any object that implements this trait can be used as a callback for the swarm.
@@ -65,42 +117,124 @@ pub trait SwarmCallback {
 /// [InnerSwarmCallback] wraps [SharedSwarmCallback] with inner handling for a specific connection.
 pub struct InnerSwarmCallback {
     did: Did,
+    session_sk: SessionSk,
     transport_event_sender: TransportEventSender,
     callback: SharedSwarmCallback,
+    pending_requests: Arc<PendingRequests>,
+    inbound_limiter: Arc<InboundLimiter>,
+    replay_cache: Arc<ReplayCache>,
     chunk_list: Arc<FuturesMutex<ChunkList<TRANSPORT_MTU>>>,
 }
 
 impl InnerSwarmCallback {
-    /// Create a new [InnerSwarmCallback] with the provided did, transport_event_sender and callback.
+    /// Create a new [InnerSwarmCallback] with the provided session_sk, transport_event_sender,
+    /// callback, pending requests which are waiting for reply, limiter of inbound traffic and
+    /// cache of received transactions. The session_sk is used to decrypt end-to-end encrypted
+    /// messages.
     pub fn new(
-        did: Did,
+        session_sk: SessionSk,
         transport_event_sender: TransportEventSender,
         callback: SharedSwarmCallback,
+        pending_requests: Arc<PendingRequests>,
+        inbound_limiter: Arc<InboundLimiter>,
+        replay_cache: Arc<ReplayCache>,
     ) -> Self {
         Self {
-            did,
+            did: session_sk.account_did(),
+            session_sk,
             transport_event_sender,
             callback,
+            pending_requests,
+            inbound_limiter,
+            replay_cache,
             chunk_list: Default::default(),
         }
     }
 
+    /// Drop a message exceeding inbound limits. Ask swarm to close the connection if it
+    /// violates too many times.
+    async fn reject(&self, peer: Did, reason: &str) -> Result<(), CallbackError> {
+        tracing::warn!("Drop message from {}: {}", peer, reason);
+        if self.inbound_limiter.record_violation(peer) {
+            Channel::send(&self.transport_event_sender, TransportEvent::Abused(peer))
+                .await
+                .map_err(Box::new)?;
+        }
+        Err(format!("Drop message from {peer}: {reason}").into())
+    }
+
+    /// Verify and handle a complete message, which may be reassembled from chunks.
+    #[cfg_attr(feature = "wasm", async_recursion(?Send))]
+    #[cfg_attr(not(feature = "wasm"), async_recursion)]
+    async fn handle_message(&self, cid: &str, msg: &[u8]) -> Result<(), CallbackError> {
+        let payload = MessagePayload::from_bincode(msg)?;
+        if !(payload.verify() && payload.transaction.verify()) {
+            tracing::error!("Cannot verify msg or it's expired: {:?}", payload);
+            return Err("Cannot verify msg or it's expired".into());
+        }
+
+        if !self.replay_cache.record(&payload)? {
+            tracing::warn!("Drop replayed msg {}", payload.transaction.tx_id);
+            return Err("Replayed msg".into());
+        }
+
+        let peer = Did::from_str(cid).ok();
+        let signer = payload.transaction.signer();
+        if !self.inbound_limiter.check_sender(signer) {
+            return match peer {
+                Some(peer) if peer == signer => self.reject(peer, "sender rate limited").await,
+                _ => Err(format!("Drop message signed by {signer}: sender rate limited").into()),
+            };
+        }
+
+        self.callback.on_validate(&payload).await?;
+
+        Channel::send(
+            &self.transport_event_sender,
+            TransportEvent::DataChannelMessage(msg.into()),
+        )
+        .await
+        .map_err(Box::new)?;
+
+        self.handle_payload(cid, peer, &payload).await
+    }
+
     async fn handle_payload(
         &self,
         cid: &str,
+        peer: Option<Did>,
         payload: &MessagePayload,
     ) -> Result<(), CallbackError> {
         let message: Message = payload.transaction.data()?;
 
         if let Message::Chunk(msg) = message {
-            if let Some(data) = self.chunk_list.lock().await.handle(msg.clone()) {
-                return self.on_message(cid, &data).await;
+            let mut chunk_list = self.chunk_list.lock().await;
+            if let Some(peer) = peer {
+                let pending = chunk_list.data_len() + msg.data.len();
+                if !self.inbound_limiter.check_pending_chunks(peer, pending) {
+                    drop(chunk_list);
+                    return self.reject(peer, "too many pending chunks").await;
+                }
+            }
+
+            let data = chunk_list.handle(msg.clone());
+            if let Some(peer) = peer {
+                self.inbound_limiter
+                    .set_pending_chunks(peer, chunk_list.data_len());
+            }
+            drop(chunk_list);
+
+            if let Some(data) = data {
+                return self.handle_message(cid, &data).await;
             }
             return Ok(());
         };
 
         if payload.transaction.destination == self.did {
-            self.callback.on_inbound(payload).await?;
+            let payload = payload.decrypt(&self.session_sk)?;
+            if !self.pending_requests.resolve(&payload) {
+                self.callback.on_inbound(&payload).await?;
+            }
         }
 
         Ok(())
@@ -111,21 +245,12 @@ impl InnerSwarmCallback {
 #[cfg_attr(not(feature = "wasm"), async_trait)]
 impl TransportCallback for InnerSwarmCallback {
     async fn on_message(&self, cid: &str, msg: &[u8]) -> Result<(), CallbackError> {
-        let payload = MessagePayload::from_bincode(msg)?;
-        if !(payload.verify() && payload.transaction.verify()) {
-            tracing::error!("Cannot verify msg or it's expired: {:?}", payload);
-            return Err("Cannot verify msg or it's expired".into());
+        if let Ok(peer) = Did::from_str(cid) {
+            if !self.inbound_limiter.check_connection(peer, msg.len()) {
+                return self.reject(peer, "connection rate limited").await;
+            }
         }
-        self.callback.on_validate(&payload).await?;
-
-        Channel::send(
-            &self.transport_event_sender,
-            TransportEvent::DataChannelMessage(msg.into()),
-        )
-        .await
-        .map_err(Box::new)?;
-
-        self.handle_payload(cid, &payload).await
+        self.handle_message(cid, msg).await
     }
 
     async fn on_peer_connection_state_change(
@@ -0,0 +1,108 @@
+//! End-to-end encryption of custom messages.
+//!
+//! Each node publishes its [SessionPubkey] to DHT once it joined the network. The [SessionPubkey]
+//! is kept in a virtual node at [session_pubkey_vid] owned by the account, so that nobody else
+//! can overwrite it.
+//! The sender looks up [SessionPubkey] of destination, then encrypts the message into
+//! [EncryptedMessage](crate::message::EncryptedMessage), which is opaque to the relays on path.
+//! The destination decrypts it back into [CustomMessage](crate::message::CustomMessage) before
+//! delivering to [SwarmCallback::on_inbound](super::callback::SwarmCallback::on_inbound).
+use std::sync::atomic::Ordering;
+
+use uuid::Uuid;
+
+use crate::dht::vnode::VNodeOperation;
+use crate::dht::vnode::VNodeType;
+use crate::dht::vnode::VirtualNode;
+use crate::dht::Did;
+use crate::error::Error;
+use crate::error::Result;
+use crate::message::ChordStorageInterfaceCacheChecker;
+use crate::message::Decoder;
+use crate::message::Encoder;
+use crate::message::Message;
+use crate::message::PayloadSender;
+use crate::session::SessionPubkey;
+use crate::swarm::LookupMode;
+use crate::swarm::Swarm;
+
+/// The did of virtual node which keeps the [SessionPubkey] of `account`.
+pub fn session_pubkey_vid(account: Did) -> Result<Did> {
+    VirtualNode::gen_did(&format!("session_pubkey:{}", account))
+}
+
+impl Swarm {
+    /// Publish [SessionPubkey] of current node to DHT.
+    pub async fn publish_session_pubkey(&self) -> Result<()> {
+        self.publish_session_pubkey_by(self.lookup_policy.mode)
+            .await
+    }
+
+    /// Like [Swarm::publish_session_pubkey], but the vnode is found by lookup of `mode`.
+    async fn publish_session_pubkey_by(&self, mode: LookupMode) -> Result<()> {
+        let pubkey = bincode::serialize(&self.session_sk().session_pubkey())
+            .map_err(Error::BincodeSerialize)?;
+        let vnode = VirtualNode {
+            did: session_pubkey_vid(self.did())?,
+            data: vec![pubkey.encode()?],
+            kind: VNodeType::Data,
+            subscribers: vec![],
+            version: 0,
+            signer: None,
+            owner: Some(self.did()),
+            writers: vec![],
+            expires_at_ms: None,
+        };
+        let op = VNodeOperation::Overwrite(self.sign_vnode(vnode));
+        self.storage_operate::<1>(op, mode).await
+    }
+
+    /// Publish [SessionPubkey] of current node if it's not published yet.
+    /// It's called by handling [JoinDHT](crate::message::MessageHandlerEvent::JoinDHT), so the
+    /// vnode is found by recursive lookup.
+    pub(crate) async fn ensure_session_pubkey_published(&self) {
+        if self.session_pubkey_published.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        if let Err(e) = self.publish_session_pubkey_by(LookupMode::Recursive).await {
+            tracing::warn!("Failed to publish session pubkey: {:?}", e);
+            self.session_pubkey_published.store(false, Ordering::SeqCst);
+        }
+    }
+
+    /// Get the verified [SessionPubkey] of `did` from local cache.
+    pub async fn session_pubkey_cache(&self, did: Did) -> Option<SessionPubkey> {
+        if did == self.did() {
+            return Some(self.session_sk().session_pubkey());
+        }
+        let vnode = self
+            .storage_check_cache(session_pubkey_vid(did).ok()?)
+            .await?;
+        vnode.data.iter().rev().find_map(|e| {
+            let data = Vec::<u8>::from_encoded(e).ok()?;
+            let pubkey: SessionPubkey = bincode::deserialize(&data).ok()?;
+            (pubkey.account_did() == did && pubkey.verify().is_ok()).then_some(pubkey)
+        })
+    }
+
+    /// Look up [SessionPubkey] of `did`, fetch it from DHT if it's not cached.
+    /// Returns [Error::SessionPubkeyNotFound] if it's not fetched within timeout.
+    pub async fn lookup_session_pubkey(&self, did: Did) -> Result<SessionPubkey> {
+        if let Some(pubkey) = self.session_pubkey_cache(did).await {
+            return Ok(pubkey);
+        }
+        self.storage_fetch_and_wait::<1>(session_pubkey_vid(did)?)
+            .await?;
+        self.session_pubkey_cache(did)
+            .await
+            .ok_or(Error::SessionPubkeyNotFound(did))
+    }
+
+    /// Send a custom message encrypted to the session of destination.
+    /// Only the destination can decrypt it, the relays on path can only see the ciphertext.
+    pub async fn send_encrypted_message(&self, msg: &[u8], destination: Did) -> Result<Uuid> {
+        let pubkey = self.lookup_session_pubkey(destination).await?;
+        self.send_message(Message::encrypted(msg, &pubkey)?, destination)
+            .await
+    }
+}
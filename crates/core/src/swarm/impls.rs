@@ -2,6 +2,7 @@ use std::str::FromStr;
 
 use async_trait::async_trait;
 use rings_transport::core::transport::ConnectionInterface;
+use rings_transport::core::transport::ConnectionKind;
 
 use super::callback::InnerSwarmCallback;
 use crate::dht::Did;
@@ -15,6 +16,7 @@ use crate::message::MessagePayload;
 use crate::message::MessageVerificationExt;
 use crate::message::PayloadSender;
 use crate::swarm::callback::SharedSwarmCallback;
+use crate::swarm::callback::SwarmEvent;
 use crate::swarm::Swarm;
 use crate::types::channel::Channel;
 use crate::types::Connection;
@@ -140,7 +142,7 @@ impl Swarm {
         }
     }
 
-    fn callback(&self) -> Result<SharedSwarmCallback> {
+    pub(crate) fn callback(&self) -> Result<SharedSwarmCallback> {
         let inner = self
             .callback
             .read()
@@ -149,6 +151,32 @@ impl Swarm {
         Ok(inner.clone())
     }
 
+    /// Deliver a [SwarmEvent] to the callback of swarm.
+    /// Errors raised by callback will be logged and ignored.
+    pub(crate) async fn emit_event(&self, event: SwarmEvent) {
+        let callback = match self.callback() {
+            Ok(cb) => cb,
+            Err(e) => {
+                tracing::error!("Failed to get callback of swarm: {:?}", e);
+                return;
+            }
+        };
+        if let Err(e) = callback.on_event(&event).await {
+            tracing::error!("Callback failed on handling event {:?}: {:?}", event, e);
+        }
+    }
+
+    /// Deliver a message, whose destination is self, to
+    /// [SwarmCallback::on_inbound](super::callback::SwarmCallback::on_inbound) directly
+    /// without going through the network.
+    pub(crate) async fn deliver_to_self(&self, msg: Message) -> Result<()> {
+        let payload = MessagePayload::new_send(msg, self.session_sk(), self.did(), self.did())?;
+        self.callback()?
+            .on_inbound(&payload)
+            .await
+            .map_err(|e| Error::CallbackError(e.to_string()))
+    }
+
     /// Set callback for swarm.
     pub fn set_callback(&self, callback: SharedSwarmCallback) -> Result<()> {
         let mut inner = self
@@ -162,11 +190,18 @@ impl Swarm {
     }
 
     /// Create new connection that will be handled by swarm.
+    /// It's refused if [ConnectionLimits](super::ConnectionLimits) is reached and no connection
+    /// can be evicted. Other connections are evicted once it's established.
     pub async fn new_connection(&self, did: Did) -> Result<Connection> {
+        self.check_connection_slot(did).await?;
+
         let inner_callback = InnerSwarmCallback::new(
-            self.did(),
+            self.session_sk().clone(),
             self.transport_event_channel.sender(),
             self.callback()?,
+            self.pending_requests.clone(),
+            self.inbound_limiter.clone(),
+            self.replay_cache.clone(),
         );
 
         let cid = did.to_string();
@@ -198,6 +233,12 @@ impl Swarm {
         None
     }
 
+    /// Set the kind of connection used to connect did, it takes effect on next connecting.
+    /// It's ignored if the transport supports only webrtc.
+    pub fn set_connection_kind(&self, did: Did, kind: ConnectionKind) {
+        self.transport.set_connection_kind(&did.to_string(), kind)
+    }
+
     /// Get connection by did.
     pub fn get_connection(&self, did: Did) -> Option<Connection> {
         self.transport.connection(&did.to_string()).ok()
@@ -234,7 +275,10 @@ impl ConnectionHandshake for Swarm {
 
         let offer = conn.webrtc_create_offer().await.map_err(Error::Transport)?;
         let offer_str = serde_json::to_string(&offer).map_err(|_| Error::SerializeToString)?;
-        let offer_msg = ConnectNodeSend { sdp: offer_str };
+        let offer_msg = ConnectNodeSend {
+            handshake: Some(self.handshake_info(&offer_str)),
+            sdp: offer_str,
+        };
 
         Ok((conn, offer_msg))
     }
@@ -244,6 +288,7 @@ impl ConnectionHandshake for Swarm {
         peer: Did,
         offer_msg: &ConnectNodeSend,
     ) -> Result<(Connection, ConnectNodeReport)> {
+        self.check_handshake_info(peer, &offer_msg.sdp, offer_msg.handshake.as_ref())?;
         if self.get_and_check_connection(peer).await.is_some() {
             return Err(Error::AlreadyConnected);
         };
@@ -254,7 +299,10 @@ impl ConnectionHandshake for Swarm {
             .await
             .map_err(Error::Transport)?;
         let answer_str = serde_json::to_string(&answer).map_err(|_| Error::SerializeToString)?;
-        let answer_msg = ConnectNodeReport { sdp: answer_str };
+        let answer_msg = ConnectNodeReport {
+            handshake: Some(self.handshake_info(&answer_str)),
+            sdp: answer_str,
+        };
 
         Ok((conn, answer_msg))
     }
@@ -264,6 +312,14 @@ impl ConnectionHandshake for Swarm {
         peer: Did,
         answer_msg: &ConnectNodeReport,
     ) -> Result<Connection> {
+        let checked =
+            self.check_handshake_info(peer, &answer_msg.sdp, answer_msg.handshake.as_ref());
+        if let Err(e) = checked {
+            if self.get_connection(peer).is_some() {
+                self.disconnect(peer).await?;
+            }
+            return Err(e);
+        }
         let answer = serde_json::from_str(&answer_msg.sdp).map_err(Error::Deserialize)?;
 
         let conn = self.get_connection(peer).ok_or(Error::ConnectionNotFound)?;
@@ -356,6 +412,8 @@ impl ConnectionManager for Swarm {
 
     /// Connect a given Did. If the did is already connected, return directly,
     /// else try prepare offer and establish connection by dht.
+    /// With [LookupMode::Iterative](crate::swarm::LookupMode::Iterative), the offer is sent
+    /// via the node found keeping did by [Swarm::lookup].
     /// This function may returns a pending connection or connected connection.
     async fn connect(&self, did: Did) -> Result<Connection> {
         tracing::info!("Try connect Did {:?}", &did);
@@ -363,13 +421,17 @@ impl ConnectionManager for Swarm {
             return Ok(t);
         }
 
+        let next_hop = self.connect_hop(did, self.lookup_policy.mode).await?;
         let conn = self.new_connection(did).await?;
 
         let offer = conn.webrtc_create_offer().await.map_err(Error::Transport)?;
         let offer_str = serde_json::to_string(&offer).map_err(|_| Error::SerializeToString)?;
-        let offer_msg = ConnectNodeSend { sdp: offer_str };
+        let offer_msg = ConnectNodeSend {
+            handshake: Some(self.handshake_info(&offer_str)),
+            sdp: offer_str,
+        };
 
-        self.send_message(Message::ConnectNodeSend(offer_msg), did)
+        self.send_message_by_hop(Message::ConnectNodeSend(offer_msg), did, next_hop)
             .await?;
 
         Ok(conn)
@@ -387,7 +449,10 @@ impl ConnectionManager for Swarm {
 
         let offer = conn.webrtc_create_offer().await.map_err(Error::Transport)?;
         let offer_str = serde_json::to_string(&offer).map_err(|_| Error::SerializeToString)?;
-        let offer_msg = ConnectNodeSend { sdp: offer_str };
+        let offer_msg = ConnectNodeSend {
+            handshake: Some(self.handshake_info(&offer_str)),
+            sdp: offer_str,
+        };
 
         self.send_message_by_hop(Message::ConnectNodeSend(offer_msg), did, next_hop)
             .await?;
@@ -419,6 +484,6 @@ impl Judegement for Swarm {
 
     /// Asynchronously checks if a connection should be established with the provided DID.
     async fn should_connect(&self, did: Did) -> bool {
-        self.behaviour_good(did).await
+        self.is_admitted(did) && self.behaviour_good(did).await
     }
 }
@@ -0,0 +1,91 @@
+//! Graceful leave of the ring.
+//!
+//! Instead of just closing transports, a leaving node:
+//! 1. notifies its predecessor and successors by [Message::NotifyLeave], so they remove it from
+//!    DHT and connect to each other;
+//! 2. hands over its local virtual nodes to the first successor by
+//!    [Message::SyncReplicaWithSuccessor] in batches, waiting for
+//!    [Message::SyncVNodeWithSuccessorReport] of each batch before removing them locally;
+//! 3. closes all connections.
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::consts::VNODE_HANDOVER_BATCH_SIZE;
+use crate::dht::Did;
+use crate::error::Result;
+use crate::message::types::NotifyLeave;
+use crate::message::types::SyncReplicaWithSuccessor;
+use crate::message::Message;
+use crate::message::MessagePayload;
+use crate::message::PayloadSender;
+use crate::swarm::Swarm;
+
+impl Swarm {
+    /// Leave the ring gracefully, see [module docs](self). Each batch of handover should be
+    /// acknowledged within timeout. Connections are closed even if handover failed, and the
+    /// error of handover is returned then. Swarm should not be used after leaving.
+    pub async fn leave(&self, timeout: Duration) -> Result<usize> {
+        let (successors, predecessor) = self.dht_snapshot();
+        let successors: Vec<Did> = successors
+            .into_iter()
+            .filter(|did| *did != self.did())
+            .collect();
+        let predecessor = predecessor.filter(|did| *did != self.did());
+
+        let mut neighbours = successors.clone();
+        if let Some(did) = predecessor.filter(|did| !successors.contains(did)) {
+            neighbours.push(did);
+        }
+        for did in neighbours {
+            let msg = Message::NotifyLeave(NotifyLeave {
+                successors: successors.clone(),
+                predecessor,
+            });
+            if let Err(e) = self.send_direct_message(msg, did).await {
+                tracing::warn!("Failed to notify {} of leaving: {:?}", did, e);
+            }
+        }
+
+        let handover = match successors.first() {
+            Some(successor) => self.hand_over_storage(*successor, timeout).await,
+            None => Ok(0),
+        };
+        if let Err(e) = &handover {
+            tracing::error!("Failed to hand over storage: {:?}", e);
+        }
+
+        for did in self.get_connection_ids() {
+            if let Err(e) = self.disconnect(did).await {
+                tracing::warn!("Failed to disconnect {}: {:?}", did, e);
+            }
+        }
+        handover
+    }
+
+    /// Send local virtual nodes to successor, and remove the ones acknowledged.
+    /// Returns the number of virtual nodes handed over.
+    async fn hand_over_storage(&self, successor: Did, timeout: Duration) -> Result<usize> {
+        let items = self.dht.storage.get_all().await?;
+        let mut count = 0;
+        for batch in items.chunks(VNODE_HANDOVER_BATCH_SIZE) {
+            let data = batch
+                .iter()
+                .map(|(vid, vnode)| Ok((Did::from_str(vid)?, vnode.clone())))
+                .collect::<Result<Vec<_>>>()?;
+            let payload = MessagePayload::new_send(
+                Message::SyncReplicaWithSuccessor(SyncReplicaWithSuccessor { data }),
+                self.session_sk(),
+                successor,
+                successor,
+            )?;
+            self.send_and_wait(payload, timeout).await?;
+
+            for (vid, _) in batch {
+                self.dht.storage.remove(vid).await?;
+            }
+            count += batch.len();
+        }
+        tracing::info!("Handed over {} virtual nodes to {}", count, successor);
+        Ok(count)
+    }
+}
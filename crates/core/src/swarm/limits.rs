@@ -0,0 +1,183 @@
+//! Limits of connections.
+//!
+//! Every new connection, either offered or answered, takes a slot in the transport. When
+//! [ConnectionLimits::max_connections] is reached, the new one is rejected with
+//! [Error::ConnectionLimitReached] unless an existing connection can be evicted according to
+//! [EvictionPolicy]. The eviction happens only once the new connection is established, so a
+//! connection that never completes its handshake can't push out a working one.
+//!
+//! Successors, the predecessor, peers in finger table and pinned peers are critical to the
+//! DHT. They are never evicted, and only they can take the last
+//! [ConnectionLimits::reserved_slots] slots.
+use dashmap::DashMap;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::dht::Did;
+use crate::error::Error;
+use crate::error::Result;
+use crate::measure::MeasureCounter;
+use crate::swarm::callback::SwarmEvent;
+use crate::swarm::Swarm;
+use crate::utils::get_epoch_ms;
+
+/// How to choose the connection to evict when the limit is reached.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionPolicy {
+    /// Never evict, reject the new connection instead.
+    Reject,
+    /// Evict the connection that sent or received a message least recently.
+    #[default]
+    LeastRecentlyUsed,
+    /// Evict the connection with most failures counted by measure, then the least recently
+    /// used one. Same as [EvictionPolicy::LeastRecentlyUsed] if swarm has no measure.
+    WorstMeasure,
+}
+
+/// Limits of connections, unlimited by default.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionLimits {
+    /// The maximum number of connections, `None` means unlimited.
+    pub max_connections: Option<usize>,
+    /// Number of slots reserved for the peers critical to DHT.
+    pub reserved_slots: usize,
+    /// How to make room for a new connection when the limit is reached.
+    pub eviction: EvictionPolicy,
+}
+
+/// The last time each peer sent or received a message.
+#[derive(Default)]
+pub struct LastActive(DashMap<Did, u128>);
+
+impl LastActive {
+    fn touch(&self, did: Did) {
+        self.0.insert(did, get_epoch_ms());
+    }
+
+    fn get(&self, did: Did) -> u128 {
+        self.0.get(&did).map(|ts| *ts).unwrap_or_default()
+    }
+
+    fn remove(&self, did: Did) {
+        self.0.remove(&did);
+    }
+}
+
+impl Swarm {
+    /// Record that a message was sent to or received from did.
+    pub(crate) fn touch_connection(&self, did: Did) {
+        self.last_active.touch(did);
+    }
+
+    /// Forget the activity of did, when its connection is closed.
+    pub(crate) fn forget_connection(&self, did: Did) {
+        self.last_active.remove(did);
+    }
+
+    /// Returns `true` if did is a successor, the predecessor, in finger table or pinned.
+    pub fn is_critical_peer(&self, did: Did) -> bool {
+        let (successors, predecessor) = self.dht_snapshot();
+        if successors.contains(&did) || predecessor == Some(did) {
+            return true;
+        }
+        if self.pinned_peers().contains(&did) {
+            return true;
+        }
+        self.dht
+            .lock_finger()
+            .map(|finger| finger.list().contains(&Some(did)))
+            .unwrap_or(false)
+    }
+
+    /// Check that there is room for a new connection to did, or room can be made by evicting
+    /// other connections once it's established.
+    pub(crate) async fn check_connection_slot(&self, did: Did) -> Result<()> {
+        self.connections_to_evict(did).await.map(|_| ())
+    }
+
+    /// Make room for the established connection to did, evicting other connections if
+    /// necessary.
+    pub(crate) async fn ensure_connection_slot(&self, did: Did) -> Result<()> {
+        for victim in self.connections_to_evict(did).await? {
+            tracing::info!("evict connection {} for {}", victim, did);
+            self.disconnect(victim).await?;
+            self.last_active.remove(victim);
+            self.emit_event(SwarmEvent::ConnectionEvicted { peer: victim })
+                .await;
+        }
+        Ok(())
+    }
+
+    /// The connections to evict to make room for the connection to did.
+    async fn connections_to_evict(&self, did: Did) -> Result<Vec<Did>> {
+        let Some(max) = self.connection_limits.max_connections else {
+            return Ok(vec![]);
+        };
+
+        let others = self
+            .get_connection_ids()
+            .into_iter()
+            .filter(|peer| *peer != did)
+            .collect::<Vec<_>>();
+
+        let limit = if self.is_critical_peer(did) {
+            max
+        } else {
+            max.saturating_sub(self.connection_limits.reserved_slots)
+        };
+        if others.len() < limit {
+            return Ok(vec![]);
+        }
+
+        let needed = others.len() + 1 - limit;
+        let candidates = others
+            .into_iter()
+            .filter(|peer| !self.is_critical_peer(*peer))
+            .collect::<Vec<_>>();
+        let victims = self.eviction_victims(candidates).await;
+        if victims.len() < needed {
+            return Err(Error::ConnectionLimitReached(max));
+        }
+        Ok(victims.into_iter().take(needed).collect())
+    }
+
+    /// Sort candidates by eviction policy, the first one should be evicted first.
+    async fn eviction_victims(&self, mut candidates: Vec<Did>) -> Vec<Did> {
+        match self.connection_limits.eviction {
+            EvictionPolicy::Reject => vec![],
+            EvictionPolicy::LeastRecentlyUsed => {
+                candidates.sort_by_key(|did| self.last_active.get(*did));
+                candidates
+            }
+            EvictionPolicy::WorstMeasure => {
+                let mut scored = vec![];
+                for did in candidates {
+                    scored.push((self.failure_count(did).await, did));
+                }
+                scored.sort_by(|(a_failures, a), (b_failures, b)| {
+                    b_failures
+                        .cmp(a_failures)
+                        .then(self.last_active.get(*a).cmp(&self.last_active.get(*b)))
+                });
+                scored.into_iter().map(|(_, did)| did).collect()
+            }
+        }
+    }
+
+    /// Count the failures of did recorded by measure.
+    async fn failure_count(&self, did: Did) -> u64 {
+        let Some(measure) = &self.measure else {
+            return 0;
+        };
+        let mut count = 0;
+        for counter in [
+            MeasureCounter::FailedToSend,
+            MeasureCounter::FailedToReceive,
+            MeasureCounter::Disconnected,
+        ] {
+            count += measure.get_count(did, counter).await;
+        }
+        count
+    }
+}
@@ -0,0 +1,294 @@
+//! Iterative lookup of successor.
+//!
+//! By default, [FindSuccessorSend](crate::message::FindSuccessorSend) is forwarded hop by hop,
+//! so a single slow hop stalls the whole lookup. In an iterative lookup, the origin queries the
+//! hops itself by [LookupSend], with up to [LookupPolicy::alpha] queries in flight and a timeout
+//! for each. A hop reports the successor if it knows, otherwise the nodes it knows preceding the
+//! target, which are queried next. A hop that times out is skipped, and the lookup falls back on
+//! the other candidates, including the successor lists reported by previous hops.
+//!
+//! With [LookupMode::Iterative], fixing fingers, joining, and the storage operations and
+//! connecting called by users are routed by iterative lookup. The ones caused by handling a
+//! message are always routed by recursive lookup, so that the listen loop is not stalled by
+//! waiting for reports, and the lookup of joining is deferred to next round of
+//! [Stabilization](crate::dht::Stabilization), see [Swarm::lookup_joined_peers].
+use std::collections::HashSet;
+use std::time::Duration;
+
+use futures::future::join_all;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::consts::LOOKUP_REPORT_SIZE;
+use crate::dht::Chord;
+use crate::dht::Did;
+use crate::dht::PeerRingAction;
+use crate::dht::PeerRingRemoteAction;
+use crate::error::Error;
+use crate::error::Result;
+use crate::message::types::LookupReport;
+use crate::message::types::LookupSend;
+use crate::message::Message;
+use crate::message::MessageHandlerEvent;
+use crate::message::MessagePayload;
+use crate::message::PayloadSender;
+use crate::swarm::Swarm;
+
+/// How the successors of fingers, joined nodes, virtual nodes and connecting nodes are found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LookupMode {
+    /// Forward the lookup hop by hop.
+    #[default]
+    Recursive,
+    /// Query the hops from current node by [Swarm::lookup].
+    Iterative,
+}
+
+/// Policy of iterative lookup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LookupPolicy {
+    /// How the successors are found.
+    pub mode: LookupMode,
+    /// Number of hops queried in parallel.
+    pub alpha: usize,
+    /// Timeout of waiting each hop to report, in milliseconds.
+    pub timeout_ms: u64,
+    /// Give up a lookup after querying this many hops.
+    pub max_queries: usize,
+}
+
+impl Default for LookupPolicy {
+    fn default() -> Self {
+        Self {
+            mode: LookupMode::Recursive,
+            alpha: 3,
+            timeout_ms: 3000,
+            max_queries: 32,
+        }
+    }
+}
+
+/// A node to query, with the connected peer the query is sent via.
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    node: Did,
+    via: Did,
+}
+
+/// Result of an iterative lookup.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LookupRoute {
+    /// The successor of target.
+    pub successor: Did,
+    /// The node reported the successor, which keeps the virtual nodes of target.
+    /// It's current node if the successor is found by local view.
+    pub reporter: Did,
+    /// The connected peer the reporter is reached via.
+    pub via: Did,
+}
+
+impl Swarm {
+    /// Get the policy of iterative lookup.
+    pub fn lookup_policy(&self) -> &LookupPolicy {
+        &self.lookup_policy
+    }
+
+    /// Find the successor of did by querying hops from current node, regardless of
+    /// [LookupMode]. Returns [Error::LookupFailed] if no hop reports the successor before
+    /// running out of candidates or [LookupPolicy::max_queries].
+    pub async fn lookup(&self, did: Did) -> Result<Did> {
+        Ok(self.lookup_route(did).await?.successor)
+    }
+
+    /// Like [Swarm::lookup], but also returns the node reported the successor.
+    pub(crate) async fn lookup_route(&self, did: Did) -> Result<LookupRoute> {
+        let candidates = match self.dht.find_successor(did)? {
+            PeerRingAction::Some(successor) => {
+                return Ok(LookupRoute {
+                    successor,
+                    reporter: self.did(),
+                    via: self.did(),
+                })
+            }
+            PeerRingAction::RemoteAction(..) => self
+                .dht
+                .closest_preceding_nodes(did, LOOKUP_REPORT_SIZE)?
+                .into_iter()
+                .map(|node| Candidate { node, via: node })
+                .collect(),
+            act => return Err(Error::PeerRingUnexpectedAction(act)),
+        };
+        self.lookup_from(did, candidates).await
+    }
+
+    /// Find the successor of did by querying the candidates and the hops they report.
+    async fn lookup_from(&self, did: Did, mut candidates: Vec<Candidate>) -> Result<LookupRoute> {
+        let policy = &self.lookup_policy;
+        let mut queried = HashSet::from([self.did()]);
+
+        loop {
+            candidates.retain(|c| !queried.contains(&c.node));
+            candidates.sort_by_key(|c| did.bias(c.node).pos());
+            candidates.dedup_by_key(|c| c.node);
+
+            let limit = policy
+                .alpha
+                .max(1)
+                .min(policy.max_queries.saturating_sub(queried.len() - 1));
+            let batch: Vec<Candidate> = candidates.iter().take(limit).copied().collect();
+            if batch.is_empty() {
+                tracing::warn!(
+                    "Lookup of {} failed after {} queries",
+                    did,
+                    queried.len() - 1
+                );
+                return Err(Error::LookupFailed(did));
+            }
+            queried.extend(batch.iter().map(|c| c.node));
+
+            let reports = join_all(batch.iter().map(|c| self.query_lookup(*c, did))).await;
+
+            // Batch is sorted closest first, so the closest hop knowing the successor wins.
+            let mut found = None;
+            for (c, report) in batch.into_iter().zip(reports) {
+                let report = match report {
+                    Ok(report) => report,
+                    Err(e) => {
+                        tracing::debug!("Lookup of {} skipped {}: {:?}", did, c.node, e);
+                        continue;
+                    }
+                };
+                let hop = if self.get_connection(c.node).is_some() {
+                    c.node
+                } else {
+                    c.via
+                };
+                if let Some(successor) = report.successor {
+                    found.get_or_insert(LookupRoute {
+                        successor,
+                        reporter: c.node,
+                        via: hop,
+                    });
+                }
+                candidates.extend(report.closer.into_iter().map(|node| Candidate {
+                    node,
+                    via: if self.get_connection(node).is_some() {
+                        node
+                    } else {
+                        hop
+                    },
+                }));
+            }
+            if let Some(route) = found {
+                return Ok(route);
+            }
+        }
+    }
+
+    /// Resolve the node to send a storage message of `vid` to, with the next hop to it.
+    /// With [LookupMode::Iterative], the node keeping `vid` is looked up by current node, so the
+    /// message is sent to it directly. Otherwise the message is sent to `next`, which forwards
+    /// it hop by hop.
+    pub(crate) async fn storage_route(
+        &self,
+        vid: Did,
+        next: Did,
+        mode: LookupMode,
+    ) -> Result<(Did, Did)> {
+        if mode == LookupMode::Iterative {
+            let route = self.lookup_route(vid).await?;
+            if route.reporter != self.did() {
+                return Ok((route.reporter, route.via));
+            }
+        }
+        Ok((next, self.infer_next_hop(None, next)?))
+    }
+
+    /// Get the next hop to send the connection offer to did by. With [LookupMode::Iterative],
+    /// the offer is sent via the node reported did as a successor.
+    pub(crate) async fn connect_hop(&self, did: Did, mode: LookupMode) -> Result<Did> {
+        if mode == LookupMode::Iterative {
+            let route = self.lookup_route(did).await?;
+            if route.successor == did && route.reporter != self.did() {
+                return Ok(route.via);
+            }
+        }
+        self.infer_next_hop(None, did)
+    }
+
+    /// Get the event connecting the successor found by a lookup.
+    pub(crate) fn connect_route(&self, route: LookupRoute) -> MessageHandlerEvent {
+        if route.reporter == self.did() {
+            MessageHandlerEvent::Connect(route.successor)
+        } else {
+            MessageHandlerEvent::ConnectVia(route.successor, route.via)
+        }
+    }
+
+    /// With [LookupMode::Iterative], take the lookups of joining out of act, which ask the
+    /// joined nodes for the successor of current node. The joined nodes are kept for
+    /// [Swarm::lookup_joined_peers]. Returns the actions left.
+    pub(crate) fn defer_join_lookups(&self, act: PeerRingAction) -> PeerRingAction {
+        if self.lookup_policy.mode != LookupMode::Iterative {
+            return act;
+        }
+        match act {
+            PeerRingAction::RemoteAction(
+                next,
+                PeerRingRemoteAction::FindSuccessorForConnect(_),
+            ) => {
+                self.joined_peers.insert(next);
+                PeerRingAction::None
+            }
+            PeerRingAction::MultiActions(acts) => PeerRingAction::MultiActions(
+                acts.into_iter()
+                    .map(|act| self.defer_join_lookups(act))
+                    .collect(),
+            ),
+            act => act,
+        }
+    }
+
+    /// Look up the successor of current node, starting from each node joined since last call,
+    /// then connect it. It's called by [Stabilization](crate::dht::Stabilization) on each round.
+    pub async fn lookup_joined_peers(&self) {
+        let joined: Vec<Did> = self.joined_peers.iter().map(|did| *did).collect();
+        for did in joined {
+            self.joined_peers.remove(&did);
+            let candidates = vec![Candidate {
+                node: did,
+                via: did,
+            }];
+            let route = match self.lookup_from(self.did(), candidates).await {
+                Ok(route) => route,
+                Err(e) => {
+                    tracing::warn!("Failed to look up successor from joined {}: {:?}", did, e);
+                    continue;
+                }
+            };
+            let evs = vec![self.connect_route(route)];
+            if let Err(e) = self.handle_message_handler_events(&evs).await {
+                tracing::warn!("Failed to connect successor found by {}: {:?}", did, e);
+            }
+        }
+    }
+
+    /// Ask candidate for the successor of did by its local view.
+    async fn query_lookup(&self, candidate: Candidate, did: Did) -> Result<LookupReport> {
+        let payload = MessagePayload::new_send(
+            Message::LookupSend(LookupSend { did }),
+            self.session_sk(),
+            candidate.via,
+            candidate.node,
+        )?;
+        let timeout = Duration::from_millis(self.lookup_policy.timeout_ms);
+        match self.send_and_wait(payload, timeout).await? {
+            Message::LookupReport(report) if report.did == did => Ok(report),
+            _ => Err(Error::InvalidMessage(
+                "Reply of lookup is not a report of target".to_string(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Offline delivery of custom messages.
+//!
+//! A custom message is undeliverable if its destination should be the successor of current
+//! node but is not connected. Such message is stored into the mailbox of destination, which
+//! is a [VNodeType::RelayMessage](crate::dht::vnode::VNodeType::RelayMessage) virtual node at
+//! [VirtualNode::mailbox_did]. The mailbox is handed over to the destination when it comes
+//! online, either synced by stabilization or fetched by [Swarm::fetch_mailbox] when it joins its
+//! successor or rejoins the ring, then the messages are replayed through
+//! [SwarmCallback::on_inbound](super::callback::SwarmCallback::on_inbound). A fetched mailbox is
+//! kept until the destination acknowledges it by [MailboxAck], or it expires.
+use dashmap::DashMap;
+use uuid::Uuid;
+
+use crate::dht::vnode::VNodeOperation;
+use crate::dht::vnode::VirtualNode;
+use crate::dht::Chord;
+use crate::dht::Did;
+use crate::dht::PeerRingAction;
+use crate::error::Error;
+use crate::error::Result;
+use crate::message::types::MailboxAck;
+use crate::message::types::SyncVNodeWithSuccessor;
+use crate::message::Decoder;
+use crate::message::Message;
+use crate::message::MessagePayload;
+use crate::message::MessageVerificationExt;
+use crate::message::PayloadSender;
+use crate::swarm::LookupMode;
+use crate::swarm::Swarm;
+use crate::utils::get_epoch_ms;
+
+/// Tx ids of replayed messages, each one is kept until the message expired.
+#[derive(Default)]
+pub struct ReplayedMessages(DashMap<Uuid, u128>);
+
+impl ReplayedMessages {
+    /// Record the tx_id of payload. Returns false if it was already replayed.
+    fn record(&self, payload: &MessagePayload) -> bool {
+        let now = get_epoch_ms();
+        self.0.retain(|_, expires_at| *expires_at > now);
+
+        let verification = payload.transaction.verification();
+        let expires_at = verification.ts_ms + verification.ttl_ms as u128;
+        self.0
+            .insert(payload.transaction.tx_id, expires_at)
+            .is_none()
+    }
+}
+
+impl Swarm {
+    /// Returns `true` if payload is a custom or encrypted message whose destination should be the
+    /// successor of current node but is not connected.
+    pub(crate) fn is_undeliverable(&self, payload: &MessagePayload) -> bool {
+        let destination = payload.relay.destination;
+        if destination == self.did() || self.is_connected(destination) {
+            return false;
+        }
+        if !matches!(
+            payload.transaction.data::<Message>(),
+            Ok(Message::CustomMessage(_) | Message::EncryptedMessage(_))
+        ) {
+            return false;
+        }
+        matches!(
+            self.dht.find_successor(destination),
+            Ok(PeerRingAction::Some(succ)) if succ != destination
+        )
+    }
+
+    /// Store the payload into the mailbox of its destination. It's called by handling the
+    /// payload, so the mailbox is found by recursive lookup.
+    pub(crate) async fn store_in_mailbox(&self, payload: &MessagePayload) -> Result<()> {
+        tracing::debug!(
+            "store message {} in mailbox of {}",
+            payload.transaction.tx_id,
+            payload.relay.destination
+        );
+        let vnode: VirtualNode = payload.clone().try_into()?;
+        let op = VNodeOperation::Extend(self.sign_vnode(vnode));
+        self.storage_operate::<1>(op, LookupMode::Recursive).await
+    }
+
+    /// Hand over the mailbox of `did` kept by current node, if any.
+    pub(crate) async fn hand_over_mailbox(&self, did: Did) -> Result<()> {
+        let Some(vnode) = self.dht.mailbox(did).await? else {
+            return Ok(());
+        };
+        let msg = Message::SyncVNodeWithSuccessor(SyncVNodeWithSuccessor { data: vec![vnode] });
+        self.send_message(msg, did).await?;
+        Ok(())
+    }
+
+    /// Fetch the messages sent to current node while it's offline.
+    /// The messages found will be delivered to
+    /// [SwarmCallback::on_inbound](super::callback::SwarmCallback::on_inbound).
+    pub async fn fetch_mailbox(&self) -> Result<()> {
+        self.fetch_mailbox_by(self.lookup_policy.mode).await
+    }
+
+    /// Like [Swarm::fetch_mailbox], but the mailbox is found by lookup of `mode`.
+    pub(crate) async fn fetch_mailbox_by(&self, mode: LookupMode) -> Result<()> {
+        let vid = VirtualNode::mailbox_did(self.did());
+        self.storage_search::<1>(vid, mode).await
+    }
+
+    /// Acknowledge `keeper` that the mailbox of current node at `version` is delivered, so that
+    /// it can be cleared.
+    pub(crate) async fn ack_mailbox(&self, version: u64, keeper: Did) -> Result<()> {
+        if keeper == self.did() {
+            return Ok(());
+        }
+        let msg = Message::MailboxAck(MailboxAck { version });
+        self.send_message(msg, keeper).await?;
+        Ok(())
+    }
+
+    /// Deliver the messages in mailbox to callback. Invalid, expired and already
+    /// replayed messages are skipped.
+    pub(crate) async fn replay_mailbox(&self, vnode: VirtualNode) -> Result<()> {
+        for data in vnode.data.iter() {
+            let payload = match MessagePayload::from_encoded(data) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    tracing::warn!("Failed to decode message in mailbox: {:?}", e);
+                    continue;
+                }
+            };
+            if payload.transaction.destination != self.did()
+                || !(payload.verify() && payload.transaction.verify())
+            {
+                continue;
+            }
+            if !self.replayed_messages.record(&payload) {
+                continue;
+            }
+            let payload = payload.decrypt(self.session_sk())?;
+            self.callback()?
+                .on_inbound(&payload)
+                .await
+                .map_err(|e| Error::CallbackError(e.to_string()))?;
+        }
+        Ok(())
+    }
+}
@@ -1,25 +1,64 @@
 #![warn(missing_docs)]
 //! Tranposrt management
 
+mod admission;
 mod builder;
 /// Callback interface for swarm
 pub mod callback;
+/// End-to-end encryption of custom messages
+pub mod encryption;
 /// Implementations of connection management traits for swarm
 pub mod impls;
+mod leave;
+mod limits;
+mod lookup;
+mod mailbox;
+mod protocol;
+mod quota;
+mod reconnect;
+mod rejoin;
+mod replay;
+mod request;
+mod trace;
 mod types;
 
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::sync::RwLock;
 
+pub use admission::AdmissionPolicy;
 use async_recursion::async_recursion;
 use async_trait::async_trait;
 pub use builder::SwarmBuilder;
+use dashmap::DashSet;
+pub use limits::ConnectionLimits;
+pub use limits::EvictionPolicy;
+pub use limits::LastActive;
+pub use lookup::LookupMode;
+pub use lookup::LookupPolicy;
+pub use mailbox::ReplayedMessages;
+pub use protocol::PeerProtocols;
+pub use quota::InboundLimiter;
+pub use quota::InboundLimits;
+pub use reconnect::ReconnectPolicy;
+pub use reconnect::Reconnector;
+pub use rejoin::KnownPeer;
+pub use rejoin::KnownPeers;
+pub use rejoin::RoutingState;
+pub use rejoin::RoutingStorage;
+pub use rejoin::MAX_KNOWN_PEERS;
+pub use replay::ReplayCache;
+pub use replay::ReplayStats;
+pub use replay::DEFAULT_REPLAY_CACHE_CAPACITY;
+pub use request::PendingRequests;
 use rings_derive::JudgeConnection;
 use rings_transport::core::transport::BoxedTransport;
 use rings_transport::core::transport::ConnectionInterface;
 use rings_transport::core::transport::TransportMessage;
 use rings_transport::core::transport::WebrtcConnectionState;
 use rings_transport::error::Error as TransportError;
+pub use trace::TraceHop;
+pub use trace::TraceRoute;
 pub use types::MeasureImpl;
 pub use types::WrappedDid;
 
@@ -31,12 +70,13 @@ use crate::dht::types::Chord;
 use crate::dht::CorrectChord;
 use crate::dht::Did;
 use crate::dht::PeerRing;
+use crate::dht::SuccessorReader;
 use crate::error::Error;
 use crate::error::Result;
 use crate::inspect::SwarmInspect;
+use crate::measure::MeasureCounter;
 use crate::message;
 use crate::message::types::NotifyPredecessorSend;
-use crate::message::ChordStorageInterface;
 use crate::message::Message;
 use crate::message::MessageHandler;
 use crate::message::MessageHandlerEvent;
@@ -45,6 +85,7 @@ use crate::message::MessageVerificationExt;
 use crate::message::PayloadSender;
 use crate::session::SessionSk;
 use crate::swarm::callback::SharedSwarmCallback;
+use crate::swarm::callback::SwarmEvent;
 use crate::swarm::impls::ConnectionHandshake;
 use crate::types::channel::Channel as ChannelTrait;
 use crate::types::channel::TransportEvent;
@@ -64,6 +105,21 @@ pub struct Swarm {
     message_handler: MessageHandler,
     transport: BoxedTransport<ConnectionOwner, TransportError>,
     callback: RwLock<SharedSwarmCallback>,
+    pending_requests: Arc<PendingRequests>,
+    replayed_messages: ReplayedMessages,
+    session_pubkey_published: AtomicBool,
+    reconnector: Reconnector,
+    connection_limits: ConnectionLimits,
+    last_active: LastActive,
+    inbound_limiter: Arc<InboundLimiter>,
+    replay_cache: Arc<ReplayCache>,
+    admission: AdmissionPolicy,
+    peer_protocols: PeerProtocols,
+    known_peers: KnownPeers,
+    routing_storage: Option<RoutingStorage>,
+    lookup_policy: LookupPolicy,
+    /// Nodes joined, to be asked for the successor of current node by iterative lookup.
+    joined_peers: DashSet<Did>,
 }
 
 impl Swarm {
@@ -94,6 +150,12 @@ impl Swarm {
             }
             TransportEvent::Connected(did) => match self.get_connection(did) {
                 Some(_) => {
+                    if let Err(e) = self.ensure_connection_slot(did).await {
+                        tracing::warn!("Drop connection {}: {:?}", did, e);
+                        self.disconnect(did).await?;
+                        return Ok(None);
+                    }
+                    self.cancel_reconnect(did);
                     let payload = MessagePayload::new_send(
                         Message::JoinDHT(message::JoinDHT { did }),
                         &self.session_sk,
@@ -105,6 +167,10 @@ impl Swarm {
                 None => Err(Error::SwarmMissTransport(did)),
             },
             TransportEvent::Closed(did) => {
+                self.schedule_reconnect(did);
+                self.inbound_limiter.forget(did);
+                self.forget_peer_protocol(did);
+                self.forget_connection(did);
                 let payload = MessagePayload::new_send(
                     Message::LeaveDHT(message::LeaveDHT { did }),
                     &self.session_sk,
@@ -113,6 +179,14 @@ impl Swarm {
                 )?;
                 Ok(Some(payload))
             }
+            TransportEvent::Abused(did) => {
+                tracing::warn!("Disconnect {} for exceeding inbound limits", did);
+                if let Some(measure) = &self.measure {
+                    measure.incr(did, MeasureCounter::FailedToReceive).await;
+                }
+                self.disconnect(did).await?;
+                Ok(None)
+            }
         }
     }
 
@@ -139,11 +213,15 @@ impl Swarm {
     /// which means a listening loop cannot running concurrency.
     pub async fn listen_once(&self) -> Option<(MessagePayload, Vec<MessageHandlerEvent>)> {
         let payload = self.poll_message().await?;
+        if let Some(prev_hop) = payload.relay.path.last() {
+            self.touch_connection(*prev_hop);
+        }
 
         if !(payload.verify() && payload.transaction.verify()) {
             tracing::error!("Cannot verify msg or it's expired: {:?}", payload);
             return None;
         }
+        let (successors, predecessor) = self.dht_snapshot();
         let events = self.message_handler.handle_message(&payload).await;
 
         match events {
@@ -156,6 +234,8 @@ impl Swarm {
                             e
                         );
                     });
+                self.emit_dht_changes(successors, predecessor).await;
+                self.emit_storage_event(&payload).await;
                 Some((payload, evs))
             }
             Err(e) => {
@@ -165,6 +245,52 @@ impl Swarm {
         }
     }
 
+    /// Take a snapshot of successor list and predecessor of local DHT.
+    fn dht_snapshot(&self) -> (Vec<Did>, Option<Did>) {
+        let successors = self.dht.successors().list().unwrap_or_default();
+        let predecessor = self.dht.lock_predecessor().map(|x| *x).ok().flatten();
+        (successors, predecessor)
+    }
+
+    /// Compare local DHT with given snapshot, and notify callback if changed.
+    async fn emit_dht_changes(&self, successors: Vec<Did>, predecessor: Option<Did>) {
+        let (new_successors, new_predecessor) = self.dht_snapshot();
+        if new_successors != successors {
+            self.emit_event(SwarmEvent::SuccessorsChange {
+                successors: new_successors,
+            })
+            .await;
+        }
+        if new_predecessor != predecessor {
+            self.emit_event(SwarmEvent::PredecessorChange {
+                predecessor: new_predecessor,
+            })
+            .await;
+        }
+    }
+
+    /// Notify callback when a `FoundVNode` or `VNodeConflict` reaches its destination.
+    async fn emit_storage_event(&self, payload: &MessagePayload) {
+        if payload.transaction.destination != self.did() {
+            return;
+        }
+        match payload.transaction.data() {
+            Ok(Message::FoundVNode(msg)) => {
+                self.emit_event(SwarmEvent::StorageHit { data: msg.data })
+                    .await
+            }
+            Ok(Message::VNodeConflict(msg)) => {
+                self.emit_event(SwarmEvent::StorageConflict {
+                    did: msg.did,
+                    expected: msg.expected,
+                    current: msg.current,
+                })
+                .await
+            }
+            _ => {}
+        }
+    }
+
     /// Event handler of Swarm.
     pub async fn handle_message_handler_event(
         &self,
@@ -172,10 +298,12 @@ impl Swarm {
     ) -> Result<Vec<MessageHandlerEvent>> {
         tracing::debug!("Handle message handler event: {:?}", event);
         match event {
+            // Lookups caused by handling a message are recursive, see the [lookup] module.
             MessageHandlerEvent::Connect(did) => {
                 let did = *did;
                 if self.get_and_check_connection(did).await.is_none() && did != self.did() {
-                    self.connect(did).await?;
+                    let next_hop = self.connect_hop(did, LookupMode::Recursive).await?;
+                    self.connect_via(did, next_hop).await?;
                 }
                 Ok(vec![])
             }
@@ -225,6 +353,8 @@ impl Swarm {
                 {
                     self.forward_payload(payload, Some(payload.relay.destination))
                         .await?;
+                } else if next_hop.is_none() && self.is_undeliverable(payload) {
+                    self.store_in_mailbox(payload).await?;
                 } else {
                     self.forward_payload(payload, *next_hop).await?;
                 }
@@ -232,14 +362,26 @@ impl Swarm {
             }
 
             MessageHandlerEvent::JoinDHT(ctx, did) => {
-                if cfg!(feature = "experimental") {
+                let events = if cfg!(feature = "experimental") {
                     let wdid: WrappedDid = WrappedDid::new(self, *did);
                     let dht_ev = self.dht.join_then_sync(wdid).await?;
+                    let dht_ev = self.defer_join_lookups(dht_ev);
                     crate::message::handlers::dht::handle_dht_events(&dht_ev, ctx).await
                 } else {
-                    let dht_ev = self.dht.join(*did)?;
+                    let dht_ev = self.defer_join_lookups(self.dht.join(*did)?);
                     crate::message::handlers::dht::handle_dht_events(&dht_ev, ctx).await
+                }?;
+                if let Err(e) = self.hand_over_mailbox(*did).await {
+                    tracing::warn!("Failed to hand over mailbox to {}: {:?}", did, e);
                 }
+                // The mailbox of current node is kept by its successor.
+                if self.dht.successors().min()? == *did {
+                    if let Err(e) = self.fetch_mailbox_by(LookupMode::Recursive).await {
+                        tracing::warn!("Failed to fetch mailbox: {:?}", e);
+                    }
+                }
+                self.ensure_session_pubkey_published().await;
+                Ok(events)
             }
 
             MessageHandlerEvent::SendDirectMessage(msg, dest) => {
@@ -248,7 +390,11 @@ impl Swarm {
             }
 
             MessageHandlerEvent::SendMessage(msg, dest) => {
-                self.send_message(msg.clone(), *dest).await?;
+                if *dest == self.did() {
+                    self.deliver_to_self(msg.clone()).await?;
+                } else {
+                    self.send_message(msg.clone(), *dest).await?;
+                }
                 Ok(vec![])
             }
 
@@ -262,8 +408,23 @@ impl Swarm {
                 Ok(vec![])
             }
 
-            MessageHandlerEvent::StorageStore(vnode) => {
-                <Self as ChordStorageInterface<1>>::storage_store(self, vnode.clone()).await?;
+            MessageHandlerEvent::StorageStore(vid, vnode, signer) => {
+                self.storage_store_replica(*vid, vnode.clone(), *signer, LookupMode::Recursive)
+                    .await?;
+                Ok(vec![])
+            }
+
+            MessageHandlerEvent::SyncReplicas(did, data) => {
+                self.sync_replicas(*did, data.clone()).await?;
+                Ok(vec![])
+            }
+
+            MessageHandlerEvent::ReplayMailbox(vnode, keeper) => {
+                self.replay_mailbox(vnode.clone()).await?;
+                // The mailbox expires anyway if it's not acknowledged.
+                if let Err(e) = self.ack_mailbox(vnode.version, *keeper).await {
+                    tracing::warn!("Failed to acknowledge mailbox to {}: {:?}", keeper, e);
+                }
                 Ok(vec![])
             }
         }
@@ -303,9 +464,15 @@ impl Swarm {
 
     /// Similar to connect, but this function will try connect a Did by given hop.
     pub async fn connect_via(&self, did: Did, next_hop: Did) -> Result<Connection> {
+        self.record_peer_via(did, next_hop);
         JudgeConnection::connect_via(self, did, next_hop).await
     }
 
+    /// Get the statistics of replayed messages dropped by swarm.
+    pub fn replay_stats(&self) -> ReplayStats {
+        self.replay_cache.stats()
+    }
+
     /// Check the status of swarm
     pub async fn inspect(&self) -> SwarmInspect {
         SwarmInspect::inspect(self).await
@@ -344,6 +511,7 @@ impl PayloadSender for Swarm {
             .get_and_check_connection(did)
             .await
             .ok_or(Error::SwarmMissDidInTable(did))?;
+        self.check_capability(did, &payload)?;
 
         tracing::debug!(
             "Try send {:?}, to node {:?}",
@@ -379,6 +547,7 @@ impl PayloadSender for Swarm {
         );
 
         if result.is_ok() {
+            self.touch_connection(did);
             self.record_sent(payload.relay.next_hop).await
         } else {
             self.record_sent_failed(payload.relay.next_hop).await
@@ -0,0 +1,115 @@
+//! Protocols of connected peers.
+//!
+//! The [ProtocolInfo] of a peer is received in handshake by [HandshakeInfo] and kept until its
+//! connection is closed. Incompatible peers are refused in handshake with
+//! [Error::IncompatibleProtocol], and messages are not sent to a peer lacking the capability to
+//! decode them, see [Message::required_capability].
+//!
+//! Peers connected without declaring protocols, either legacy nodes or the ones connected
+//! manually by sdp, are assumed to talk [ProtocolInfo::legacy], which supports no capabilities.
+use dashmap::DashMap;
+
+use crate::dht::Did;
+use crate::error::Error;
+use crate::error::Result;
+use crate::message::HandshakeInfo;
+use crate::message::Message;
+use crate::message::MessagePayload;
+use crate::message::ProtocolInfo;
+use crate::message::CAPABILITIES;
+use crate::message::CAPABILITY_SUBRING_ROUTE;
+use crate::swarm::Swarm;
+
+/// Protocols declared by connected peers.
+#[derive(Default)]
+pub struct PeerProtocols(DashMap<Did, ProtocolInfo>);
+
+impl Swarm {
+    /// Get the protocol declared by did in handshake.
+    pub fn peer_protocol(&self, did: Did) -> Option<ProtocolInfo> {
+        self.peer_protocols.0.get(&did).map(|p| p.clone())
+    }
+
+    /// Returns `true` if did declared capability in handshake. A peer that didn't declare its
+    /// protocol is legacy, which supports no capabilities.
+    pub fn peer_supports(&self, did: Did, capability: &str) -> bool {
+        self.peer_protocols
+            .0
+            .get(&did)
+            .map_or(false, |p| p.supports(capability))
+    }
+
+    /// The handshake info sent with sdp.
+    pub(crate) fn handshake_info(&self, sdp: &str) -> HandshakeInfo {
+        HandshakeInfo {
+            network_proof: self.network_proof(sdp),
+            protocol: ProtocolInfo::default(),
+        }
+    }
+
+    /// Check the handshake info received with sdp from did, which is `None` if did is legacy.
+    /// The protocol of did is recorded if it's admitted and compatible.
+    pub(crate) fn check_handshake_info(
+        &self,
+        did: Did,
+        sdp: &str,
+        handshake: Option<&HandshakeInfo>,
+    ) -> Result<()> {
+        let proof = handshake.and_then(|h| h.network_proof.as_deref());
+        self.admission.check_handshake(did, sdp, proof)?;
+        match handshake {
+            Some(handshake) => self.check_peer_protocol(did, &handshake.protocol),
+            None => self.check_peer_protocol(did, &ProtocolInfo::legacy()),
+        }
+    }
+
+    /// Check the protocol declared by did in handshake, and record it if compatible.
+    pub(crate) fn check_peer_protocol(&self, did: Did, protocol: &ProtocolInfo) -> Result<()> {
+        if !protocol.is_compatible() {
+            tracing::warn!("Refuse {} with incompatible protocol {:?}", did, protocol);
+            return Err(Error::IncompatibleProtocol(did, protocol.version));
+        }
+        if protocol.negotiated_version() < protocol.version {
+            tracing::info!(
+                "Downgrade protocol of {} from {} to {}",
+                did,
+                protocol.version,
+                protocol.negotiated_version()
+            );
+        }
+        self.peer_protocols.0.insert(did, protocol.clone());
+        Ok(())
+    }
+
+    /// Forget the protocol of did, since its connection is closed.
+    pub(crate) fn forget_peer_protocol(&self, did: Did) {
+        self.peer_protocols.0.remove(&did);
+    }
+
+    /// Check if next hop can decode the message in payload.
+    pub(crate) fn check_capability(&self, next_hop: Did, payload: &MessagePayload) -> Result<()> {
+        let protocol = self
+            .peer_protocol(next_hop)
+            .unwrap_or_else(ProtocolInfo::legacy);
+        // Skip decoding the message if next hop supports everything.
+        if CAPABILITIES.iter().all(|c| protocol.supports(c)) {
+            return Ok(());
+        }
+        // Legacy nodes would drop the subring and route the message on the whole network.
+        if payload.subring.is_some() && !protocol.supports(CAPABILITY_SUBRING_ROUTE) {
+            return Err(Error::UnsupportedByPeer(
+                next_hop,
+                CAPABILITY_SUBRING_ROUTE.to_string(),
+            ));
+        }
+        let Ok(msg) = payload.transaction.data::<Message>() else {
+            return Ok(());
+        };
+        match msg.required_capability() {
+            Some(capability) if !protocol.supports(capability) => {
+                Err(Error::UnsupportedByPeer(next_hop, capability.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,329 @@
+//! Rate limits and quotas of inbound traffic.
+//!
+//! Each connection has token buckets of messages and bytes, checked before a message is
+//! verified. Each sender, the signer of a message, has a token bucket of messages, checked after
+//! the message is verified. At most [InboundLimits::max_senders] buckets of senders are kept,
+//! idle ones are dropped first, then the least recently used ones, a tenth of them at once.
+//! Chunks of a large message are buffered per connection until the message is completed, which
+//! is limited by [InboundLimits::max_pending_chunk_bytes] and
+//! [InboundLimits::max_total_pending_chunk_bytes].
+//!
+//! Messages exceeding the limits are dropped. Each dropped message counts a violation against
+//! the connection, and the connection is closed with a
+//! [MeasureCounter::FailedToReceive](crate::measure::MeasureCounter::FailedToReceive) penalty
+//! once [InboundLimits::max_violations] is reached.
+use dashmap::DashMap;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::consts::TRANSPORT_MAX_SIZE;
+use crate::dht::Did;
+use crate::utils::get_epoch_ms;
+
+/// Limits of inbound traffic. Rate limits are disabled by default, while chunk reassembly is
+/// limited to the size of one maximum message per connection.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InboundLimits {
+    /// Maximum messages per second received from a connection.
+    pub connection_messages_per_sec: Option<u32>,
+    /// Maximum bytes per second received from a connection.
+    pub connection_bytes_per_sec: Option<u64>,
+    /// Maximum messages per second signed by a sender, no matter which connection relays them.
+    pub sender_messages_per_sec: Option<u32>,
+    /// Maximum number of senders tracked by [InboundLimits::sender_messages_per_sec].
+    pub max_senders: usize,
+    /// Seconds of traffic a bucket can hold, which allows short bursts.
+    pub burst_secs: u32,
+    /// Maximum bytes of incomplete chunks buffered for a connection.
+    pub max_pending_chunk_bytes: usize,
+    /// Maximum bytes of incomplete chunks buffered for all connections.
+    pub max_total_pending_chunk_bytes: Option<usize>,
+    /// Close the connection once this many messages are dropped, `None` never closes.
+    pub max_violations: Option<u32>,
+}
+
+impl Default for InboundLimits {
+    fn default() -> Self {
+        Self {
+            connection_messages_per_sec: None,
+            connection_bytes_per_sec: None,
+            sender_messages_per_sec: None,
+            max_senders: 10_000,
+            burst_secs: 2,
+            max_pending_chunk_bytes: TRANSPORT_MAX_SIZE,
+            max_total_pending_chunk_bytes: None,
+            max_violations: Some(100),
+        }
+    }
+}
+
+/// A token bucket refilled continuously.
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_ms: f64,
+    last_ms: u128,
+}
+
+impl TokenBucket {
+    fn new(per_sec: u64, burst_secs: u32) -> Self {
+        let capacity = (per_sec * burst_secs.max(1) as u64) as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_ms: per_sec as f64 / 1000.0,
+            last_ms: get_epoch_ms(),
+        }
+    }
+
+    fn try_take(&mut self, cost: u64) -> bool {
+        let now = get_epoch_ms();
+        let elapsed = now.saturating_sub(self.last_ms) as f64;
+        self.tokens = (self.tokens + elapsed * self.refill_per_ms).min(self.capacity);
+        self.last_ms = now;
+
+        if self.tokens < cost as f64 {
+            return false;
+        }
+        self.tokens -= cost as f64;
+        true
+    }
+
+    /// Returns `true` if the bucket is refilled to its capacity at `now`, so dropping it makes
+    /// no difference.
+    fn is_idle(&self, now: u128) -> bool {
+        let elapsed = now.saturating_sub(self.last_ms) as f64;
+        self.tokens + elapsed * self.refill_per_ms >= self.capacity
+    }
+}
+
+#[derive(Debug, Default)]
+struct ConnectionQuota {
+    messages: Option<TokenBucket>,
+    bytes: Option<TokenBucket>,
+    pending_chunk_bytes: usize,
+    violations: u32,
+}
+
+/// States of [InboundLimits], shared by swarm and callbacks of all connections.
+#[derive(Default)]
+pub struct InboundLimiter {
+    limits: InboundLimits,
+    connections: DashMap<Did, ConnectionQuota>,
+    senders: DashMap<Did, TokenBucket>,
+}
+
+impl InboundLimiter {
+    /// Create an [InboundLimiter] with given limits.
+    pub fn new(limits: InboundLimits) -> Self {
+        Self {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    fn connection(&self, did: Did) -> dashmap::mapref::one::RefMut<Did, ConnectionQuota> {
+        self.connections
+            .entry(did)
+            .or_insert_with(|| ConnectionQuota {
+                messages: self
+                    .limits
+                    .connection_messages_per_sec
+                    .map(|n| TokenBucket::new(n as u64, self.limits.burst_secs)),
+                bytes: self
+                    .limits
+                    .connection_bytes_per_sec
+                    .map(|n| TokenBucket::new(n, self.limits.burst_secs)),
+                ..Default::default()
+            })
+    }
+
+    /// Check a message of `len` bytes received from the connection of did.
+    pub fn check_connection(&self, did: Did, len: usize) -> bool {
+        let mut quota = self.connection(did);
+        let messages_ok = quota.messages.as_mut().map_or(true, |b| b.try_take(1));
+        let bytes_ok = quota
+            .bytes
+            .as_mut()
+            .map_or(true, |b| b.try_take(len as u64));
+        messages_ok && bytes_ok
+    }
+
+    /// Check a message signed by sender.
+    pub fn check_sender(&self, sender: Did) -> bool {
+        let Some(per_sec) = self.limits.sender_messages_per_sec else {
+            return true;
+        };
+        if !self.senders.contains_key(&sender) && self.senders.len() >= self.limits.max_senders {
+            self.purge_senders();
+        }
+        self.senders
+            .entry(sender)
+            .or_insert_with(|| TokenBucket::new(per_sec as u64, self.limits.burst_secs))
+            .try_take(1)
+    }
+
+    /// Drop the idle buckets of senders. If there are still too many, drop a tenth of
+    /// [InboundLimits::max_senders] that are least recently used, so that a flood of new
+    /// senders doesn't scan all buckets on every message.
+    fn purge_senders(&self) {
+        let now = get_epoch_ms();
+        self.senders.retain(|_, bucket| !bucket.is_idle(now));
+        if self.senders.len() < self.limits.max_senders {
+            return;
+        }
+        let mut lru: Vec<(u128, Did)> = self
+            .senders
+            .iter()
+            .map(|entry| (entry.last_ms, *entry.key()))
+            .collect();
+        let batch = (self.limits.max_senders / 10).clamp(1, lru.len());
+        lru.select_nth_unstable(batch - 1);
+        for (_, did) in lru.into_iter().take(batch) {
+            self.senders.remove(&did);
+        }
+    }
+
+    /// Check if `pending` bytes of incomplete chunks can be buffered for the connection of did.
+    pub fn check_pending_chunks(&self, did: Did, pending: usize) -> bool {
+        if pending > self.limits.max_pending_chunk_bytes {
+            return false;
+        }
+        if let Some(max_total) = self.limits.max_total_pending_chunk_bytes {
+            let others: usize = self
+                .connections
+                .iter()
+                .filter(|entry| *entry.key() != did)
+                .map(|entry| entry.pending_chunk_bytes)
+                .sum();
+            if others + pending > max_total {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Update the bytes of incomplete chunks buffered for the connection of did.
+    pub fn set_pending_chunks(&self, did: Did, pending: usize) {
+        self.connection(did).pending_chunk_bytes = pending;
+    }
+
+    /// Count a violation of the connection of did.
+    /// Returns `true` if the connection should be closed, and the count is reset.
+    pub fn record_violation(&self, did: Did) -> bool {
+        let mut quota = self.connection(did);
+        quota.violations += 1;
+        match self.limits.max_violations {
+            Some(max) if quota.violations >= max => {
+                quota.violations = 0;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Drop the states of the connection of did.
+    pub fn forget(&self, did: Did) {
+        self.connections.remove(&did);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecc::SecretKey;
+
+    #[test]
+    fn test_connection_rate_limit() {
+        let limiter = InboundLimiter::new(InboundLimits {
+            connection_messages_per_sec: Some(5),
+            burst_secs: 1,
+            ..Default::default()
+        });
+        let did = SecretKey::random().address().into();
+        for _ in 0..5 {
+            assert!(limiter.check_connection(did, 10));
+        }
+        assert!(!limiter.check_connection(did, 10));
+
+        let other = SecretKey::random().address().into();
+        assert!(limiter.check_connection(other, 10));
+    }
+
+    #[test]
+    fn test_sender_buckets_bounded() {
+        let limiter = InboundLimiter::new(InboundLimits {
+            sender_messages_per_sec: Some(1),
+            max_senders: 2,
+            burst_secs: 1,
+            ..Default::default()
+        });
+        let did1 = SecretKey::random().address().into();
+        let did2 = SecretKey::random().address().into();
+        let did3 = SecretKey::random().address().into();
+        assert!(limiter.check_sender(did1));
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        assert!(limiter.check_sender(did2));
+        assert!(!limiter.check_sender(did2));
+
+        // The least recently used bucket is dropped for a new sender.
+        assert!(limiter.check_sender(did3));
+        assert_eq!(limiter.senders.len(), 2);
+        assert!(!limiter.senders.contains_key(&did1));
+        assert!(!limiter.check_sender(did3));
+    }
+
+    #[test]
+    fn test_sender_buckets_purged_in_batch() {
+        let limiter = InboundLimiter::new(InboundLimits {
+            sender_messages_per_sec: Some(1),
+            max_senders: 20,
+            burst_secs: 1,
+            ..Default::default()
+        });
+        let dids: Vec<Did> = (0..21)
+            .map(|_| SecretKey::random().address().into())
+            .collect();
+        for did in dids.iter().take(20) {
+            assert!(limiter.check_sender(*did));
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        // A tenth of buckets, the least recently used ones, are dropped for a new sender.
+        assert!(limiter.check_sender(dids[20]));
+        assert_eq!(limiter.senders.len(), 19);
+        assert!(!limiter.senders.contains_key(&dids[0]));
+        assert!(!limiter.senders.contains_key(&dids[1]));
+        assert!(limiter.senders.contains_key(&dids[2]));
+    }
+
+    #[test]
+    fn test_pending_chunks_quota() {
+        let limiter = InboundLimiter::new(InboundLimits {
+            max_pending_chunk_bytes: 100,
+            max_total_pending_chunk_bytes: Some(150),
+            ..Default::default()
+        });
+        let did1 = SecretKey::random().address().into();
+        let did2 = SecretKey::random().address().into();
+        assert!(limiter.check_pending_chunks(did1, 100));
+        assert!(!limiter.check_pending_chunks(did1, 101));
+        limiter.set_pending_chunks(did1, 100);
+        assert!(!limiter.check_pending_chunks(did2, 60));
+        assert!(limiter.check_pending_chunks(did2, 50));
+    }
+
+    #[test]
+    fn test_record_violation() {
+        let limiter = InboundLimiter::new(InboundLimits {
+            max_violations: Some(2),
+            ..Default::default()
+        });
+        let did = SecretKey::random().address().into();
+        assert!(!limiter.record_violation(did));
+        assert!(limiter.record_violation(did));
+        assert!(!limiter.record_violation(did));
+    }
+}
@@ -0,0 +1,219 @@
+//! Automatic reconnection of dropped peers.
+//!
+//! When the connection of an important peer, which is a successor, the predecessor or a peer
+//! pinned by [Swarm::pin_peer], is closed, the peer is scheduled for reconnecting. The schedule
+//! is driven by [Stabilization](crate::dht::Stabilization), which calls
+//! [Swarm::reconnect_peers] on each round, so the actual delay is rounded up to the
+//! stabilization interval. The delay between attempts follows [ReconnectPolicy].
+use dashmap::DashMap;
+use dashmap::DashSet;
+use rand::Rng;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::dht::Did;
+use crate::message::PayloadSender;
+use crate::swarm::callback::SwarmEvent;
+use crate::swarm::Swarm;
+use crate::utils::get_epoch_ms;
+
+/// Policy of reconnecting dropped peers, with exponential backoff and jitter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReconnectPolicy {
+    /// Give up a peer after this many failed attempts. Set to `0` to disable reconnection.
+    pub max_attempts: u32,
+    /// Delay before the first attempt, doubled after each attempt.
+    pub base_delay_ms: u64,
+    /// Upper bound of the delay between attempts.
+    pub max_delay_ms: u64,
+    /// Ratio of random jitter applied to each delay, in range `[0, 1]`.
+    pub jitter: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 8,
+            base_delay_ms: 1000,
+            max_delay_ms: 60_000,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Delay before the attempt following `attempts` finished ones, jitter included.
+    pub fn delay_ms(&self, attempts: u32) -> u64 {
+        let delay = self
+            .base_delay_ms
+            .saturating_mul(1u64 << attempts.min(32))
+            .min(self.max_delay_ms);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = 1.0 + rand::thread_rng().gen_range(-jitter..=jitter);
+        (delay as f64 * factor) as u64
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ReconnectState {
+    attempts: u32,
+    next_attempt_ms: u128,
+}
+
+/// Pinned peers and the peers waiting for reconnecting.
+#[derive(Default)]
+pub struct Reconnector {
+    policy: ReconnectPolicy,
+    pinned: DashSet<Did>,
+    pending: DashMap<Did, ReconnectState>,
+}
+
+impl Reconnector {
+    /// Create a [Reconnector] with given policy.
+    pub fn new(policy: ReconnectPolicy) -> Self {
+        Self {
+            policy,
+            ..Default::default()
+        }
+    }
+
+    /// Schedule the first attempt of did, if it's not scheduled yet.
+    fn schedule(&self, did: Did) {
+        if self.policy.max_attempts == 0 {
+            return;
+        }
+        self.pending.entry(did).or_insert_with(|| ReconnectState {
+            attempts: 0,
+            next_attempt_ms: get_epoch_ms() + self.policy.delay_ms(0) as u128,
+        });
+    }
+
+    /// Take the peers whose next attempt is due.
+    fn due(&self) -> Vec<(Did, ReconnectState)> {
+        let now = get_epoch_ms();
+        self.pending
+            .iter()
+            .filter(|entry| entry.next_attempt_ms <= now)
+            .map(|entry| (*entry.key(), *entry.value()))
+            .collect()
+    }
+}
+
+impl Swarm {
+    /// Keep reconnecting did whenever its connection is dropped, until [Swarm::unpin_peer].
+    pub fn pin_peer(&self, did: Did) {
+        self.reconnector.pinned.insert(did);
+    }
+
+    /// Stop reconnecting did, and cancel the scheduled attempt of it.
+    pub fn unpin_peer(&self, did: Did) {
+        self.reconnector.pinned.remove(&did);
+        self.reconnector.pending.remove(&did);
+    }
+
+    /// List the pinned peers.
+    pub fn pinned_peers(&self) -> Vec<Did> {
+        self.reconnector.pinned.iter().map(|did| *did).collect()
+    }
+
+    /// List the peers waiting for reconnecting, with the number of attempts made.
+    pub fn reconnecting_peers(&self) -> Vec<(Did, u32)> {
+        self.reconnector
+            .pending
+            .iter()
+            .map(|entry| (*entry.key(), entry.attempts))
+            .collect()
+    }
+
+    /// Schedule reconnecting did if it's pinned, a successor or the predecessor.
+    /// Should be called before did is removed from DHT.
+    pub(crate) fn schedule_reconnect(&self, did: Did) {
+        let (successors, predecessor) = self.dht_snapshot();
+        if self.reconnector.pinned.contains(&did)
+            || successors.contains(&did)
+            || predecessor == Some(did)
+        {
+            tracing::debug!("schedule reconnecting {}", did);
+            self.reconnector.schedule(did);
+        }
+    }
+
+    /// Cancel the scheduled attempt of did, since it's connected again.
+    pub(crate) fn cancel_reconnect(&self, did: Did) {
+        self.reconnector.pending.remove(&did);
+    }
+
+    /// Make an attempt for each peer that is due. A [SwarmEvent::ReconnectAttempt] is emitted
+    /// for each attempt, and [SwarmEvent::ReconnectGaveUp] when a peer runs out of attempts.
+    pub async fn reconnect_peers(&self) {
+        for (did, state) in self.reconnector.due() {
+            if self.is_connected(did) {
+                self.cancel_reconnect(did);
+                continue;
+            }
+
+            let attempts = state.attempts + 1;
+            if attempts > self.reconnector.policy.max_attempts {
+                self.cancel_reconnect(did);
+                tracing::info!(
+                    "give up reconnecting {} after {} attempts",
+                    did,
+                    state.attempts
+                );
+                self.emit_event(SwarmEvent::ReconnectGaveUp {
+                    peer: did,
+                    attempts: state.attempts,
+                })
+                .await;
+                continue;
+            }
+
+            let error = self.connect(did).await.err().map(|e| e.to_string());
+            tracing::debug!("reconnect {}, attempt {}: {:?}", did, attempts, error);
+
+            // The peer may be unpinned or connected while connecting.
+            if let Some(mut pending) = self.reconnector.pending.get_mut(&did) {
+                pending.attempts = attempts;
+                pending.next_attempt_ms =
+                    get_epoch_ms() + self.reconnector.policy.delay_ms(attempts) as u128;
+            }
+
+            self.emit_event(SwarmEvent::ReconnectAttempt {
+                peer: did,
+                attempt: attempts,
+                error,
+            })
+            .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_policy_delay() {
+        let policy = ReconnectPolicy {
+            max_attempts: 8,
+            base_delay_ms: 1000,
+            max_delay_ms: 10_000,
+            jitter: 0.0,
+        };
+        assert_eq!(policy.delay_ms(0), 1000);
+        assert_eq!(policy.delay_ms(1), 2000);
+        assert_eq!(policy.delay_ms(3), 8000);
+        assert_eq!(policy.delay_ms(4), 10_000);
+        assert_eq!(policy.delay_ms(100), 10_000);
+
+        let policy = ReconnectPolicy {
+            jitter: 0.5,
+            ..policy
+        };
+        for _ in 0..100 {
+            let delay = policy.delay_ms(1);
+            assert!((1000..=3000).contains(&delay));
+        }
+    }
+}
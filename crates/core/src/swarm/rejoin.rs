@@ -0,0 +1,241 @@
+//! Warm restart from persisted routing state.
+//!
+//! Swarm remembers the peers it was connected to recently, with the hop it connected them via
+//! and their endpoint if known. Together with successors, predecessor and fingers, they form a
+//! [RoutingState], which is saved to [RoutingStorage] by
+//! [Stabilization](crate::dht::Stabilization) periodically.
+//!
+//! After restart, the node connects to peers with known endpoints first, which is beyond swarm,
+//! then [Swarm::rejoin] connects the rest of peers in routing state through them.
+use dashmap::DashMap;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::dht::Did;
+use crate::error::Result;
+use crate::storage::KvStorageInterface;
+use crate::swarm::Swarm;
+use crate::utils::get_epoch_ms;
+
+/// Key of routing state in [RoutingStorage].
+const ROUTING_STATE_KEY: &str = "routing_state";
+
+/// Maximum number of recently connected peers kept in [RoutingState].
+pub const MAX_KNOWN_PEERS: usize = 64;
+
+/// `RoutingStorage` is the type accepted by [SwarmBuilder::routing_storage](super::SwarmBuilder::routing_storage).
+/// It's used to persist [RoutingState] in a storage media provided by user.
+#[cfg(feature = "wasm")]
+pub type RoutingStorage = Box<dyn KvStorageInterface<RoutingState>>;
+
+/// `RoutingStorage` is the type accepted by [SwarmBuilder::routing_storage](super::SwarmBuilder::routing_storage).
+/// It's used to persist [RoutingState] in a storage media provided by user.
+#[cfg(not(feature = "wasm"))]
+pub type RoutingStorage = Box<dyn KvStorageInterface<RoutingState> + Send + Sync>;
+
+/// A peer connected recently.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownPeer {
+    /// Did of the peer.
+    pub did: Did,
+    /// The next hop the peer was connected via, if connected by DHT.
+    pub via: Option<Did>,
+    /// The endpoint the peer was connected by, such as the url of its rpc server.
+    pub endpoint: Option<String>,
+    /// The last time the peer was seen connected, in unix timestamp milliseconds.
+    pub last_seen_ms: u128,
+}
+
+/// Routing state of swarm, used to rejoin the ring after restart.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoutingState {
+    /// Successors of local DHT.
+    pub successors: Vec<Did>,
+    /// Predecessor of local DHT.
+    pub predecessor: Option<Did>,
+    /// Fingers of local DHT.
+    pub fingers: Vec<Did>,
+    /// Peers connected recently, most recent first.
+    pub peers: Vec<KnownPeer>,
+}
+
+impl RoutingState {
+    /// All dids to connect when rejoining, neighbours first, without duplication.
+    pub fn dids(&self) -> Vec<Did> {
+        let mut dids: Vec<Did> = vec![];
+        let candidates = self
+            .successors
+            .iter()
+            .chain(self.predecessor.iter())
+            .chain(self.fingers.iter())
+            .chain(self.peers.iter().map(|p| &p.did));
+        for did in candidates {
+            if !dids.contains(did) {
+                dids.push(*did);
+            }
+        }
+        dids
+    }
+
+    /// Get the known peer of did.
+    pub fn peer(&self, did: Did) -> Option<&KnownPeer> {
+        self.peers.iter().find(|p| p.did == did)
+    }
+}
+
+/// Peers connected recently, indexed by did.
+#[derive(Default)]
+pub struct KnownPeers(DashMap<Did, KnownPeer>);
+
+impl KnownPeers {
+    fn update(&self, did: Did, f: impl FnOnce(&mut KnownPeer)) {
+        let mut peer = self.0.entry(did).or_insert_with(|| KnownPeer {
+            did,
+            via: None,
+            endpoint: None,
+            last_seen_ms: 0,
+        });
+        f(peer.value_mut());
+    }
+}
+
+impl Swarm {
+    /// Record the next hop did is connecting via.
+    pub(crate) fn record_peer_via(&self, did: Did, via: Did) {
+        self.known_peers.update(did, |p| p.via = Some(via));
+    }
+
+    /// Record the endpoint did is connected by, so it can be connected again after restart
+    /// without a seed.
+    pub fn record_peer_endpoint(&self, did: Did, endpoint: String) {
+        self.known_peers
+            .update(did, |p| p.endpoint = Some(endpoint));
+    }
+
+    /// Take a snapshot of routing state. Connected peers are marked as seen now, and only the
+    /// most recently seen [MAX_KNOWN_PEERS] peers are kept.
+    pub fn routing_state(&self) -> RoutingState {
+        let now = get_epoch_ms();
+        for did in self.get_connection_ids() {
+            self.known_peers.update(did, |p| p.last_seen_ms = now);
+        }
+
+        let mut peers: Vec<KnownPeer> = self
+            .known_peers
+            .0
+            .iter()
+            .map(|p| p.value().clone())
+            .filter(|p| p.last_seen_ms > 0)
+            .collect();
+        peers.sort_by(|a, b| b.last_seen_ms.cmp(&a.last_seen_ms));
+        for peer in peers.split_off(peers.len().min(MAX_KNOWN_PEERS)) {
+            self.known_peers.0.remove(&peer.did);
+        }
+
+        let (successors, predecessor) = self.dht_snapshot();
+        let fingers = self
+            .dht
+            .lock_finger()
+            .map(|finger| {
+                let mut fingers = vec![];
+                for did in finger.list().iter().flatten() {
+                    if !fingers.contains(did) {
+                        fingers.push(*did);
+                    }
+                }
+                fingers
+            })
+            .unwrap_or_default();
+
+        RoutingState {
+            successors,
+            predecessor,
+            fingers,
+            peers,
+        }
+    }
+
+    /// Save routing state to [RoutingStorage] if set. Nothing is saved while disconnected from
+    /// all peers, so the last useful state survives leaving the ring.
+    pub async fn save_routing_state(&self) -> Result<()> {
+        let Some(storage) = &self.routing_storage else {
+            return Ok(());
+        };
+        if self.get_connection_ids().is_empty() {
+            return Ok(());
+        }
+        storage.put(ROUTING_STATE_KEY, &self.routing_state()).await
+    }
+
+    /// Load routing state from [RoutingStorage] if set. The peers in it are remembered again,
+    /// so their endpoints and hops are kept even if not reconnected.
+    pub async fn load_routing_state(&self) -> Result<Option<RoutingState>> {
+        let Some(storage) = &self.routing_storage else {
+            return Ok(None);
+        };
+        let state = storage.get(ROUTING_STATE_KEY).await?;
+        for peer in state.iter().flat_map(|s| s.peers.iter()) {
+            self.known_peers.0.insert(peer.did, peer.clone());
+        }
+        Ok(state)
+    }
+
+    /// Connect the peers in routing state by DHT, through the peers already connected. A peer is
+    /// connected via its recorded hop if that hop is connected, then the mailbox is fetched for
+    /// the messages sent while offline. Returns the number of peers tried.
+    pub async fn rejoin(&self, state: &RoutingState) -> usize {
+        let connected = self.get_connection_ids();
+        let Some(default_hop) = connected.first().copied() else {
+            tracing::warn!("Cannot rejoin the ring without any connected peer");
+            return 0;
+        };
+
+        let mut tried = 0;
+        for did in state.dids() {
+            if did == self.did() || connected.contains(&did) {
+                continue;
+            }
+            let via = state
+                .peer(did)
+                .and_then(|p| p.via)
+                .filter(|via| connected.contains(via))
+                .unwrap_or(default_hop);
+            tried += 1;
+            if let Err(e) = self.connect_via(did, via).await {
+                tracing::warn!("Failed to reconnect {} via {}: {:?}", did, via, e);
+            }
+        }
+        tracing::info!("Rejoin the ring, tried {} peers", tried);
+        if let Err(e) = self.fetch_mailbox().await {
+            tracing::warn!("Failed to fetch mailbox: {:?}", e);
+        }
+        tried
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecc::SecretKey;
+
+    #[test]
+    fn test_routing_state_dids() {
+        let dids: Vec<Did> = (0..4)
+            .map(|_| SecretKey::random().address().into())
+            .collect();
+        let state = RoutingState {
+            successors: vec![dids[0], dids[1]],
+            predecessor: Some(dids[2]),
+            fingers: vec![dids[1], dids[3]],
+            peers: vec![KnownPeer {
+                did: dids[3],
+                via: Some(dids[0]),
+                endpoint: None,
+                last_seen_ms: 1,
+            }],
+        };
+        assert_eq!(state.dids(), dids);
+        assert_eq!(state.peer(dids[3]).and_then(|p| p.via), Some(dids[0]));
+        assert!(state.peer(dids[0]).is_none());
+    }
+}
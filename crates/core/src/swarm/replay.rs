@@ -0,0 +1,181 @@
+//! Protection against replayed messages.
+//!
+//! A signed [Transaction](crate::message::Transaction) stays valid until its `ts_ms + ttl_ms`,
+//! so anyone who captured it can inject it again within that window. [ReplayCache] remembers
+//! the transactions received by swarm until they are expired, and messages carrying a seen
+//! transaction are dropped before [SwarmCallback::on_validate](super::callback::SwarmCallback::on_validate).
+//!
+//! The cache is bounded by [ReplayCache::capacity]. When it's full, the oldest entries are
+//! forgotten first, even if they are not expired yet.
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::ecc::keccak256;
+use crate::error::Result;
+use crate::message::MessagePayload;
+use crate::message::MessageVerificationExt;
+use crate::utils::get_epoch_ms;
+
+/// Default capacity of [ReplayCache].
+pub const DEFAULT_REPLAY_CACHE_CAPACITY: usize = 100_000;
+
+type TransactionKey = [u8; 32];
+
+/// Statistics of [ReplayCache].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayStats {
+    /// Number of transactions in cache.
+    pub cached: u64,
+    /// Number of replayed messages dropped.
+    pub dropped: u64,
+}
+
+#[derive(Default)]
+struct SeenTransactions {
+    expires: HashMap<TransactionKey, u128>,
+    order: VecDeque<TransactionKey>,
+}
+
+/// Transactions received by swarm, each one is kept until it's expired.
+pub struct ReplayCache {
+    capacity: usize,
+    seen: Mutex<SeenTransactions>,
+    dropped: AtomicU64,
+}
+
+impl Default for ReplayCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_REPLAY_CACHE_CAPACITY)
+    }
+}
+
+impl ReplayCache {
+    /// Create a [ReplayCache] holding at most `capacity` transactions.
+    /// Set `capacity` to `0` to disable replay protection.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            seen: Mutex::new(SeenTransactions::default()),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// The maximum number of transactions kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Identify a transaction by its signer, signed data and timestamp. The signature itself
+    /// is not used, since it may be malleable.
+    fn key(payload: &MessagePayload) -> Result<TransactionKey> {
+        let transaction = &payload.transaction;
+        let verification = transaction.verification();
+
+        let mut msg = transaction.verification_data()?;
+        msg.extend_from_slice(transaction.signer().as_bytes());
+        msg.extend_from_slice(&verification.ts_ms.to_be_bytes());
+        msg.extend_from_slice(&verification.ttl_ms.to_be_bytes());
+        Ok(keccak256(&msg))
+    }
+
+    /// Record the transaction of payload. Returns `false` if it was seen and not expired,
+    /// which means payload is a replay.
+    pub fn record(&self, payload: &MessagePayload) -> Result<bool> {
+        if self.capacity == 0 {
+            return Ok(true);
+        }
+
+        let key = Self::key(payload)?;
+        let verification = payload.transaction.verification();
+        let expires_at = verification.ts_ms + verification.ttl_ms as u128;
+        let now = get_epoch_ms();
+
+        let mut seen = self.seen.lock().unwrap();
+        if matches!(seen.expires.get(&key), Some(at) if *at > now) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return Ok(false);
+        }
+
+        while let Some(oldest) = seen.order.front().copied() {
+            let expired = seen.expires.get(&oldest).map_or(true, |at| *at <= now);
+            if !expired && seen.order.len() < self.capacity {
+                break;
+            }
+            seen.order.pop_front();
+            seen.expires.remove(&oldest);
+        }
+
+        if seen.expires.insert(key, expires_at).is_none() {
+            seen.order.push_back(key);
+        }
+        Ok(true)
+    }
+
+    /// Get the statistics of cache.
+    pub fn stats(&self) -> ReplayStats {
+        ReplayStats {
+            cached: self.seen.lock().unwrap().expires.len() as u64,
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecc::SecretKey;
+    use crate::message::Message;
+    use crate::session::SessionSk;
+
+    fn new_test_payload() -> MessagePayload {
+        let session_sk = SessionSk::new_with_seckey(&SecretKey::random()).unwrap();
+        let destination = SecretKey::random().address().into();
+        let msg = Message::custom(b"hello").unwrap();
+        MessagePayload::new_send(msg, &session_sk, destination, destination).unwrap()
+    }
+
+    #[test]
+    fn test_drop_replayed_payload() {
+        let cache = ReplayCache::new(10);
+        let payload = new_test_payload();
+
+        assert!(cache.record(&payload).unwrap());
+        assert!(!cache.record(&payload).unwrap());
+        assert!(!cache.record(&payload).unwrap());
+        assert!(cache.record(&new_test_payload()).unwrap());
+
+        assert_eq!(cache.stats(), ReplayStats {
+            cached: 2,
+            dropped: 2
+        });
+    }
+
+    #[test]
+    fn test_replay_cache_capacity() {
+        let cache = ReplayCache::new(2);
+        let payloads = (0..3).map(|_| new_test_payload()).collect::<Vec<_>>();
+
+        for payload in payloads.iter() {
+            assert!(cache.record(payload).unwrap());
+        }
+        assert_eq!(cache.stats().cached, 2);
+
+        // The oldest one is forgotten.
+        assert!(cache.record(&payloads[0]).unwrap());
+        assert!(!cache.record(&payloads[2]).unwrap());
+    }
+
+    #[test]
+    fn test_replay_cache_disabled() {
+        let cache = ReplayCache::new(0);
+        let payload = new_test_payload();
+        assert!(cache.record(&payload).unwrap());
+        assert!(cache.record(&payload).unwrap());
+    }
+}
@@ -0,0 +1,212 @@
+//! Correlation of request and reply of custom messages.
+//!
+//! A request is a custom message whose tx_id is registered in [PendingRequests].
+//! The remote peer replies by sending a custom message back with the same tx_id,
+//! which is matched before the message reaches
+//! [SwarmCallback::on_inbound](super::callback::SwarmCallback::on_inbound).
+//! A reply is only accepted if it's signed by the destination of request, so that a relay
+//! can't forge it with a tx_id it has seen.
+//!
+//! Handing over virtual nodes when leaving the ring uses the same mechanism, waiting for
+//! [Message::SyncVNodeWithSuccessorReport] instead of a custom reply. So does tracing route,
+//! which also collects the [Message::TraceRouteReport] of each hop before destination replies,
+//! and iterative lookup, waiting for [Message::LookupReport] of each hop queried.
+use std::time::Duration;
+
+use dashmap::DashMap;
+use futures::channel::oneshot;
+use futures::future::select;
+use futures::future::Either;
+use futures::pin_mut;
+use uuid::Uuid;
+
+use crate::dht::Did;
+use crate::error::Error;
+use crate::error::Result;
+use crate::message::CustomMessage;
+use crate::message::Message;
+use crate::message::MessagePayload;
+use crate::message::MessageVerificationExt;
+use crate::message::PayloadSender;
+use crate::swarm::trace::PendingTrace;
+use crate::swarm::Swarm;
+use crate::utils::get_epoch_ms;
+use crate::utils::sleep;
+
+/// Requests waiting for reply, indexed by tx_id of the request.
+#[derive(Default)]
+pub struct PendingRequests {
+    replies: DashMap<Uuid, PendingReply>,
+    traces: DashMap<Uuid, PendingTrace>,
+}
+
+/// A request waiting for reply from responder.
+struct PendingReply {
+    responder: Did,
+    sender: oneshot::Sender<Message>,
+}
+
+impl PendingRequests {
+    fn register(&self, tx_id: Uuid, responder: Did) -> oneshot::Receiver<Message> {
+        let (sender, receiver) = oneshot::channel();
+        self.replies
+            .insert(tx_id, PendingReply { responder, sender });
+        receiver
+    }
+
+    fn cancel(&self, tx_id: &Uuid) {
+        self.replies.remove(tx_id);
+    }
+
+    /// Collect the reports of hops for the trace with tx_id.
+    pub(crate) fn track_trace(&self, tx_id: Uuid, trace: PendingTrace) {
+        self.traces.insert(tx_id, trace);
+    }
+
+    /// Stop collecting reports for the trace with tx_id, and return what's collected.
+    pub(crate) fn take_trace(&self, tx_id: &Uuid) -> Option<PendingTrace> {
+        self.traces.remove(tx_id).map(|(_, trace)| trace)
+    }
+
+    /// Pass the custom message or report in payload to the request with same tx_id, if it's
+    /// signed by the responder of request.
+    /// A trace report of hop is only collected, the request is resolved by the destination.
+    /// Returns false if no request is waiting for it.
+    pub(crate) fn resolve(&self, payload: &MessagePayload) -> bool {
+        let tx_id = payload.transaction.tx_id;
+        let signer = payload.transaction.signer();
+        let mut traced = false;
+        let msg = match payload.transaction.data() {
+            Ok(
+                msg @ (Message::CustomMessage(_)
+                | Message::SyncVNodeWithSuccessorReport(_)
+                | Message::LookupReport(_)),
+            ) => msg,
+            Ok(Message::TraceRouteReport(report)) => {
+                let Some(mut trace) = self.traces.get_mut(&tx_id) else {
+                    return false;
+                };
+                trace.record(signer, report.clone(), get_epoch_ms());
+                if !report.reached {
+                    return true;
+                }
+                traced = true;
+                Message::TraceRouteReport(report)
+            }
+            _ => return false,
+        };
+        match self
+            .replies
+            .remove_if(&tx_id, |_, pending| pending.responder == signer)
+        {
+            Some((_, pending)) => {
+                let _ = pending.sender.send(msg);
+                true
+            }
+            None => {
+                if self.replies.contains_key(&tx_id) {
+                    tracing::warn!("Drop reply of {} from unexpected signer {}", tx_id, signer);
+                }
+                traced
+            }
+        }
+    }
+}
+
+impl Swarm {
+    /// Send a custom message to destination, then wait for the reply with same tx_id.
+    /// Returns [Error::RequestTimeout] if no reply is received within timeout.
+    pub async fn request(
+        &self,
+        destination: Did,
+        msg: &[u8],
+        timeout: Duration,
+    ) -> Result<Vec<u8>> {
+        let next_hop = self.infer_next_hop(None, destination)?;
+        let payload = MessagePayload::new_send(
+            Message::custom(msg)?,
+            self.session_sk(),
+            next_hop,
+            destination,
+        )?;
+        match self.send_and_wait(payload, timeout).await? {
+            Message::CustomMessage(CustomMessage(data)) => Ok(data),
+            _ => Err(Error::InvalidMessage(
+                "Reply of request is not a custom message".to_string(),
+            )),
+        }
+    }
+
+    /// Send payload, then wait for the reply with same tx_id, signed by destination of payload.
+    pub(crate) async fn send_and_wait(
+        &self,
+        payload: MessagePayload,
+        timeout: Duration,
+    ) -> Result<Message> {
+        let tx_id = payload.transaction.tx_id;
+
+        let reply = self
+            .pending_requests
+            .register(tx_id, payload.transaction.destination);
+        if let Err(e) = self.send_payload(payload).await {
+            self.pending_requests.cancel(&tx_id);
+            return Err(e);
+        }
+
+        let timer = sleep(timeout);
+        pin_mut!(timer);
+        match select(reply, timer).await {
+            Either::Left((Ok(data), _)) => Ok(data),
+            _ => {
+                self.pending_requests.cancel(&tx_id);
+                Err(Error::RequestTimeout(tx_id.to_string()))
+            }
+        }
+    }
+
+    /// Reply a custom message to the request in ctx. The reply carries the same tx_id as request.
+    pub async fn reply(&self, ctx: &MessagePayload, msg: &[u8]) -> Result<()> {
+        self.send_report_message(ctx, Message::custom(msg)?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecc::SecretKey;
+    use crate::session::SessionSk;
+
+    fn reply_from(key: &SecretKey, requester: Did) -> MessagePayload {
+        let session_sk = SessionSk::new_with_seckey(key).unwrap();
+        MessagePayload::new_send(
+            Message::custom(b"reply").unwrap(),
+            &session_sk,
+            requester,
+            requester,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_requires_responder() {
+        let requester: Did = SecretKey::random().address().into();
+        let responder = SecretKey::random();
+        let relay = SecretKey::random();
+        let pending = PendingRequests::default();
+
+        let reply = reply_from(&responder, requester);
+        let tx_id = reply.transaction.tx_id;
+        let mut forged = reply_from(&relay, requester);
+        forged.transaction.tx_id = tx_id;
+
+        let mut receiver = pending.register(tx_id, responder.address().into());
+        assert!(!pending.resolve(&forged));
+        assert!(receiver.try_recv().unwrap().is_none());
+
+        assert!(pending.resolve(&reply));
+        assert!(matches!(
+            receiver.try_recv().unwrap(),
+            Some(Message::CustomMessage(_))
+        ));
+    }
+}
@@ -0,0 +1,198 @@
+//! Tracing the route of messages to a node.
+//!
+//! A [TraceRouteSend] probe is sent to destination by the normal routing. Every node handling it,
+//! including the destination, reports to the sender by [TraceRouteReport] with the path the
+//! probe passed and the time it received the probe. The reports are collected by
+//! [PendingRequests](super::PendingRequests) until the destination reports or timeout, so a
+//! broken route is traced until where it breaks.
+//!
+//! The timestamps of hops come from their own clocks, so the latency between hops is affected by
+//! clock skew and can even be negative. The round trip time of each hop is measured by the sender.
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::dht::Did;
+use crate::error::Error;
+use crate::error::Result;
+use crate::message::types::TraceRouteReport;
+use crate::message::types::TraceRouteSend;
+use crate::message::Message;
+use crate::message::MessagePayload;
+use crate::swarm::Swarm;
+use crate::utils::get_epoch_ms;
+
+/// Reports received for a probe, with the time it was sent.
+#[derive(Debug, Clone)]
+pub(crate) struct PendingTrace {
+    sent_at_ms: u128,
+    reports: Vec<(Did, TraceRouteReport, u128)>,
+}
+
+impl PendingTrace {
+    pub(crate) fn new(sent_at_ms: u128) -> Self {
+        Self {
+            sent_at_ms,
+            reports: vec![],
+        }
+    }
+
+    /// Record the report of reporter, received at `reported_at_ms` by the sender.
+    pub(crate) fn record(&mut self, reporter: Did, report: TraceRouteReport, reported_at_ms: u128) {
+        self.reports.push((reporter, report, reported_at_ms));
+    }
+}
+
+/// A node on the traced route.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceHop {
+    /// Did of the node.
+    pub did: Did,
+    /// The time the node received the probe, in unix timestamp milliseconds of its clock.
+    /// It's `None` if the report of node is lost.
+    pub received_at_ms: Option<u128>,
+    /// Milliseconds from the previous hop receiving the probe to this one.
+    pub latency_ms: Option<i64>,
+    /// Milliseconds from sending the probe to receiving the report of node.
+    pub rtt_ms: Option<u128>,
+}
+
+/// Route of messages to a node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceRoute {
+    /// The node traced.
+    pub destination: Did,
+    /// Whether the probe reached destination.
+    pub reached: bool,
+    /// Nodes on the route after the sender, ending at destination if reached.
+    pub hops: Vec<TraceHop>,
+}
+
+impl TraceRoute {
+    fn new(destination: Did, reached: bool, trace: PendingTrace) -> Self {
+        // The farthest reporter knows the whole route before it.
+        let route = trace
+            .reports
+            .iter()
+            .max_by_key(|(_, report, _)| report.path.len())
+            .map(|(did, report, _)| {
+                let mut route = report.path.iter().skip(1).copied().collect::<Vec<_>>();
+                route.push(*did);
+                route
+            })
+            .unwrap_or_default();
+
+        let mut previous = Some(trace.sent_at_ms);
+        let hops = route
+            .into_iter()
+            .map(|did| {
+                let report = trace.reports.iter().find(|(reporter, ..)| *reporter == did);
+                let received_at_ms = report.map(|(_, report, _)| report.received_at_ms);
+                let latency_ms = previous
+                    .zip(received_at_ms)
+                    .map(|(previous, received)| received as i64 - previous as i64);
+                let rtt_ms = report
+                    .map(|(.., reported_at_ms)| reported_at_ms.saturating_sub(trace.sent_at_ms));
+                previous = received_at_ms;
+                TraceHop {
+                    did,
+                    received_at_ms,
+                    latency_ms,
+                    rtt_ms,
+                }
+            })
+            .collect();
+
+        Self {
+            destination,
+            reached,
+            hops,
+        }
+    }
+}
+
+impl Swarm {
+    /// Trace the route of messages to destination, waiting for the reports of nodes on the route
+    /// within timeout. If destination doesn't report in time, the route traced so far is
+    /// returned with `reached` being false.
+    pub async fn trace_route(&self, destination: Did, timeout: Duration) -> Result<TraceRoute> {
+        if destination == self.did() {
+            return Ok(TraceRoute {
+                destination,
+                reached: true,
+                hops: vec![],
+            });
+        }
+
+        let next_hop = self.infer_next_hop(None, destination)?;
+        let payload = MessagePayload::new_send(
+            Message::TraceRouteSend(TraceRouteSend {}),
+            self.session_sk(),
+            next_hop,
+            destination,
+        )?;
+        let tx_id = payload.transaction.tx_id;
+
+        self.pending_requests
+            .track_trace(tx_id, PendingTrace::new(get_epoch_ms()));
+        let result = self.send_and_wait(payload, timeout).await;
+        let trace = self.pending_requests.take_trace(&tx_id);
+
+        match (result, trace) {
+            (Ok(_), Some(trace)) => Ok(TraceRoute::new(destination, true, trace)),
+            (Err(Error::RequestTimeout(_)), Some(trace)) => {
+                Ok(TraceRoute::new(destination, false, trace))
+            }
+            (Err(e), _) => Err(e),
+            (Ok(_), None) => Err(Error::RequestTimeout(tx_id.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecc::SecretKey;
+
+    #[test]
+    fn test_trace_route_with_lost_report() {
+        let dids: Vec<Did> = (0..4)
+            .map(|_| SecretKey::random().address().into())
+            .collect();
+        let (sender, hop1, hop2, destination) = (dids[0], dids[1], dids[2], dids[3]);
+
+        let mut trace = PendingTrace::new(1000);
+        trace.record(
+            hop1,
+            TraceRouteReport {
+                path: vec![sender],
+                received_at_ms: 1010,
+                reached: false,
+            },
+            1020,
+        );
+        // The report of hop2 is lost.
+        trace.record(
+            destination,
+            TraceRouteReport {
+                path: vec![sender, hop1, hop2],
+                received_at_ms: 1030,
+                reached: true,
+            },
+            1060,
+        );
+
+        let route = TraceRoute::new(destination, true, trace);
+        assert_eq!(route.hops.iter().map(|h| h.did).collect::<Vec<_>>(), vec![
+            hop1,
+            hop2,
+            destination
+        ]);
+        assert_eq!(route.hops[0].latency_ms, Some(10));
+        assert_eq!(route.hops[0].rtt_ms, Some(20));
+        assert_eq!(route.hops[1].received_at_ms, None);
+        assert_eq!(route.hops[2].latency_ms, None);
+        assert_eq!(route.hops[2].rtt_ms, Some(60));
+    }
+}
@@ -9,15 +9,31 @@ use crate::storage::MemStorage;
 use crate::swarm::Swarm;
 use crate::swarm::SwarmBuilder;
 
+mod test_admission;
+mod test_connection_limits;
+mod test_leave;
+mod test_lookup;
+mod test_mailbox;
 mod test_message_handler;
+mod test_protocol;
+mod test_rejoin;
 mod test_stabilization;
+mod test_trace;
 
 pub async fn prepare_node(key: SecretKey) -> Arc<Swarm> {
+    prepare_node_with(key, |builder| builder)
+}
+
+/// Prepare a node whose builder is configured by `configure`.
+pub fn prepare_node_with(
+    key: SecretKey,
+    configure: impl FnOnce(SwarmBuilder) -> SwarmBuilder,
+) -> Arc<Swarm> {
     let stun = "stun://stun.l.google.com:19302";
     let storage = Box::new(MemStorage::new());
 
     let session_sk = SessionSk::new_with_seckey(&key).unwrap();
-    let swarm = Arc::new(SwarmBuilder::new(stun, storage, session_sk).build());
+    let swarm = Arc::new(configure(SwarmBuilder::new(stun, storage, session_sk)).build());
 
     println!("key: {:?}", key.to_string());
     println!("did: {:?}", swarm.did());
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use crate::ecc::SecretKey;
+use crate::error::Error;
+use crate::error::Result;
+use crate::swarm::impls::ConnectionHandshake;
+use crate::swarm::impls::Judegement;
+use crate::swarm::AdmissionPolicy;
+use crate::swarm::Swarm;
+use crate::tests::default::prepare_node_with;
+
+fn prepare_admission_node(key: SecretKey, policy: AdmissionPolicy) -> Arc<Swarm> {
+    prepare_node_with(key, |builder| builder.admission(policy))
+}
+
+fn private_network(key: &str) -> AdmissionPolicy {
+    AdmissionPolicy {
+        network_key: Some(key.to_string()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_handshake_in_same_network() -> Result<()> {
+    let swarm1 = prepare_admission_node(SecretKey::random(), private_network("staging"));
+    let swarm2 = prepare_admission_node(SecretKey::random(), private_network("staging"));
+
+    let (_, offer) = swarm1.create_offer(swarm2.did()).await?;
+    let (_, answer) = swarm2.answer_offer(offer).await?;
+    let (did, _) = swarm1.accept_answer(answer).await?;
+    assert_eq!(did, swarm2.did());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reject_peer_from_other_network() -> Result<()> {
+    let swarm1 = prepare_admission_node(SecretKey::random(), private_network("staging"));
+    let swarm2 = prepare_admission_node(SecretKey::random(), private_network("production"));
+    let swarm3 = prepare_admission_node(SecretKey::random(), AdmissionPolicy::default());
+
+    let (_, offer) = swarm1.create_offer(swarm2.did()).await?;
+    assert!(matches!(
+        swarm2.answer_offer(offer).await,
+        Err(Error::NetworkMismatch(did)) if did == swarm1.did()
+    ));
+    assert!(swarm2.get_connection(swarm1.did()).is_none());
+
+    // The answer from public network is rejected, and the pending connection is closed.
+    let (_, offer) = swarm1.create_offer(swarm3.did()).await?;
+    let (_, answer) = swarm3.answer_offer(offer).await?;
+    assert!(matches!(
+        swarm1.accept_answer(answer).await,
+        Err(Error::NetworkMismatch(did)) if did == swarm3.did()
+    ));
+    assert!(swarm1.get_connection(swarm3.did()).is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reject_peer_by_lists() -> Result<()> {
+    let key1 = SecretKey::random();
+    let key2 = SecretKey::random();
+    let key3 = SecretKey::random();
+
+    let swarm1 = prepare_admission_node(key1, AdmissionPolicy::default());
+    let swarm2 = prepare_admission_node(key2, AdmissionPolicy {
+        allowlist: Some([key1.address().into(), key3.address().into()].into()),
+        denylist: [key3.address().into()].into(),
+        ..Default::default()
+    });
+    let swarm3 = prepare_admission_node(key3, AdmissionPolicy::default());
+
+    assert!(swarm2.should_connect(swarm1.did()).await);
+    assert!(!swarm2.should_connect(swarm3.did()).await);
+    assert!(matches!(
+        swarm2.connect(swarm3.did()).await,
+        Err(Error::NodeBehaviourBad(_))
+    ));
+
+    let (_, offer) = swarm3.create_offer(swarm2.did()).await?;
+    assert!(matches!(
+        swarm2.answer_offer(offer).await,
+        Err(Error::PeerNotAdmitted(did)) if did == swarm3.did()
+    ));
+
+    let (_, offer) = swarm1.create_offer(swarm2.did()).await?;
+    swarm2.answer_offer(offer).await?;
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use crate::ecc::SecretKey;
+use crate::error::Error;
+use crate::error::Result;
+use crate::swarm::ConnectionLimits;
+use crate::swarm::EvictionPolicy;
+use crate::swarm::Swarm;
+use crate::tests::default::prepare_node_with;
+
+fn prepare_limited_node(limits: ConnectionLimits) -> Arc<Swarm> {
+    prepare_node_with(SecretKey::random(), |builder| {
+        builder.connection_limits(limits)
+    })
+}
+
+#[tokio::test]
+async fn test_evict_least_recently_used() -> Result<()> {
+    let swarm = prepare_limited_node(ConnectionLimits {
+        max_connections: Some(2),
+        reserved_slots: 0,
+        eviction: EvictionPolicy::LeastRecentlyUsed,
+    });
+    let did1 = SecretKey::random().address().into();
+    let did2 = SecretKey::random().address().into();
+    let did3 = SecretKey::random().address().into();
+
+    swarm.new_connection(did1).await?;
+    swarm.new_connection(did2).await?;
+    swarm.touch_connection(did1);
+    swarm.new_connection(did3).await?;
+    // Nothing is evicted until the new connection is established.
+    assert_eq!(swarm.get_connection_ids().len(), 3);
+
+    swarm.ensure_connection_slot(did3).await?;
+    let mut ids = swarm.get_connection_ids();
+    ids.sort();
+    let mut expected = vec![did1, did3];
+    expected.sort();
+    assert_eq!(ids, expected);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reject_when_limit_reached() -> Result<()> {
+    let swarm = prepare_limited_node(ConnectionLimits {
+        max_connections: Some(1),
+        reserved_slots: 0,
+        eviction: EvictionPolicy::Reject,
+    });
+    let did1 = SecretKey::random().address().into();
+    let did2 = SecretKey::random().address().into();
+
+    swarm.new_connection(did1).await?;
+    assert!(matches!(
+        swarm.new_connection(did2).await,
+        Err(Error::ConnectionLimitReached(1))
+    ));
+    assert_eq!(swarm.get_connection_ids(), vec![did1]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reserved_slots_for_critical_peers() -> Result<()> {
+    let swarm = prepare_limited_node(ConnectionLimits {
+        max_connections: Some(2),
+        reserved_slots: 1,
+        eviction: EvictionPolicy::Reject,
+    });
+    let did1 = SecretKey::random().address().into();
+    let did2 = SecretKey::random().address().into();
+    let did3 = SecretKey::random().address().into();
+
+    swarm.new_connection(did1).await?;
+    assert!(swarm.new_connection(did2).await.is_err());
+
+    // Pinned peer is critical, so it can take the reserved slot.
+    swarm.pin_peer(did3);
+    swarm.new_connection(did3).await?;
+    assert_eq!(swarm.get_connection_ids().len(), 2);
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use tokio::time::sleep;
+
+use crate::dht::successor::SuccessorReader;
+use crate::dht::vnode::VirtualNode;
+use crate::ecc::SecretKey;
+use crate::error::Error;
+use crate::error::Result;
+use crate::tests::default::prepare_node;
+use crate::tests::manually_establish_connection;
+
+#[tokio::test]
+async fn test_leave_with_handover() -> Result<()> {
+    let swarm1 = prepare_node(SecretKey::random()).await;
+    let swarm2 = prepare_node(SecretKey::random()).await;
+    manually_establish_connection(&swarm1, &swarm2).await;
+
+    tokio::select! {
+        _ = async {
+            futures::join!(
+                async { swarm1.clone().listen().await },
+                async { swarm2.clone().listen().await },
+            );
+        } => { unreachable!(); }
+        res = async {
+            sleep(Duration::from_millis(1000)).await;
+            assert!(swarm2.dht().successors().list()?.contains(&swarm1.did()));
+
+            let mut vids = vec![];
+            for topic in ["leave1", "leave2", "leave3"] {
+                let vnode = VirtualNode::try_from(topic.to_string())?;
+                swarm1.dht().storage.put(&vnode.did.to_string(), &vnode).await?;
+                vids.push(vnode.did.to_string());
+            }
+
+            assert_eq!(swarm1.leave(Duration::from_secs(5)).await?, 3);
+            assert_eq!(swarm1.dht().storage.count().await?, 0);
+            for vid in vids {
+                assert!(swarm2.dht().storage.get(&vid).await?.is_some());
+            }
+
+            sleep(Duration::from_millis(1000)).await;
+            assert!(!swarm2.dht().successors().list()?.contains(&swarm1.did()));
+            assert!(swarm2.get_connection(swarm1.did()).is_none());
+
+            Ok::<(), Error>(())
+        } => res?
+    }
+
+    Ok(())
+}
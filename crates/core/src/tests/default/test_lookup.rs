@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use num_bigint::BigUint;
+use tokio::time::sleep;
+
+use crate::dht::vnode::VirtualNode;
+use crate::dht::Did;
+use crate::ecc::tests::gen_ordered_keys;
+use crate::ecc::SecretKey;
+use crate::error::Error;
+use crate::error::Result;
+use crate::message::ChordStorageInterface;
+use crate::message::PayloadSender;
+use crate::swarm::LookupMode;
+use crate::swarm::LookupPolicy;
+use crate::swarm::Swarm;
+use crate::tests::default::prepare_node;
+use crate::tests::default::prepare_node_with;
+use crate::tests::manually_establish_connection;
+
+fn prepare_iterative_node(key: SecretKey) -> Arc<Swarm> {
+    let policy = LookupPolicy {
+        mode: LookupMode::Iterative,
+        ..Default::default()
+    };
+    prepare_node_with(key, |builder| builder.lookup_policy(policy))
+}
+
+#[tokio::test]
+async fn test_iterative_lookup() -> Result<()> {
+    let keys = gen_ordered_keys(2);
+    let swarm1 = prepare_node(keys[0]).await;
+    let swarm2 = prepare_node(keys[1]).await;
+    manually_establish_connection(&swarm1, &swarm2).await;
+
+    tokio::select! {
+        _ = async {
+            futures::join!(
+                async { swarm1.clone().listen().await },
+                async { swarm2.clone().listen().await },
+            );
+        } => { unreachable!(); }
+        res = async {
+            sleep(Duration::from_millis(1000)).await;
+
+            // Successor of swarm2 is known by swarm1 locally.
+            assert_eq!(swarm1.lookup(swarm2.did()).await?, swarm2.did());
+
+            // The did right before swarm1 is beyond the successor of swarm1,
+            // so swarm1 queries swarm2, which knows swarm1 is the successor.
+            let did = swarm1.did() - Did::from(BigUint::from(1u16));
+            assert_eq!(swarm1.lookup(did).await?, swarm1.did());
+
+            Ok::<(), Error>(())
+        } => res?
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_iterative_storage_and_connect() -> Result<()> {
+    // swarm1 and swarm3 are connected via swarm2 only. Joins are looked up by stabilization,
+    // which is not running, so no more connections are made.
+    let keys = gen_ordered_keys(3);
+    let swarm1 = prepare_iterative_node(keys[0]);
+    let swarm2 = prepare_iterative_node(keys[1]);
+    let swarm3 = prepare_iterative_node(keys[2]);
+    manually_establish_connection(&swarm1, &swarm2).await;
+    manually_establish_connection(&swarm2, &swarm3).await;
+
+    tokio::select! {
+        _ = async {
+            futures::join!(
+                async { swarm1.clone().listen().await },
+                async { swarm2.clone().listen().await },
+                async { swarm3.clone().listen().await },
+            );
+        } => { unreachable!(); }
+        res = async {
+            sleep(Duration::from_millis(1000)).await;
+            assert!(!swarm1.is_connected(swarm3.did()));
+
+            // The did right after swarm3 is kept by swarm3.
+            let vid = swarm3.did() + Did::from(BigUint::from(1u16));
+            let vnode = VirtualNode {
+                did: vid,
+                ..VirtualNode::try_from("hello".to_string())?
+            };
+            <Swarm as ChordStorageInterface<1>>::storage_store(&swarm1, vnode).await?;
+            sleep(Duration::from_millis(1000)).await;
+            assert!(swarm3.dht().storage.get(&vid.to_string()).await?.is_some());
+            assert!(swarm2.dht().storage.get(&vid.to_string()).await?.is_none());
+
+            let fetched = swarm1.storage_fetch_and_wait::<1>(vid).await?;
+            assert_eq!(fetched.map(|v| v.did), Some(vid));
+
+            // The offer is sent via swarm2, which reports swarm3 as a successor.
+            swarm1.connect(swarm3.did()).await?;
+            sleep(Duration::from_millis(1000)).await;
+            assert!(swarm1.is_connected(swarm3.did()));
+
+            Ok::<(), Error>(())
+        } => res?
+    }
+
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+use crate::dht::vnode::VirtualNode;
+use crate::dht::Did;
+use crate::ecc::SecretKey;
+use crate::error::Error;
+use crate::error::Result;
+use crate::message::Message;
+use crate::message::MessagePayload;
+use crate::swarm::callback::SwarmCallback;
+use crate::swarm::Swarm;
+use crate::tests::default::prepare_node;
+use crate::tests::manually_establish_connection;
+
+#[derive(Default)]
+struct InboundMessages(Mutex<Vec<(Did, Vec<u8>)>>);
+
+#[async_trait]
+impl SwarmCallback for InboundMessages {
+    async fn on_inbound(
+        &self,
+        payload: &MessagePayload,
+    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        if let Message::CustomMessage(msg) = payload.transaction.data().map_err(Box::new)? {
+            self.0
+                .lock()
+                .await
+                .push((payload.transaction.signer(), msg.0));
+        }
+        Ok(())
+    }
+}
+
+/// Store a custom message from `sender` to `destination` into the mailbox, as if
+/// `destination` is offline.
+async fn send_to_mailbox(sender: &Swarm, destination: Did) -> Result<()> {
+    let payload = MessagePayload::new_send(
+        Message::custom(b"hello")?,
+        sender.session_sk(),
+        sender.did(),
+        destination,
+    )?;
+    sender.store_in_mailbox(&payload).await
+}
+
+#[tokio::test]
+async fn test_fetch_mailbox_after_join() -> Result<()> {
+    let swarm1 = prepare_node(SecretKey::random()).await;
+    let swarm2 = prepare_node(SecretKey::random()).await;
+    let inbound = Arc::new(InboundMessages::default());
+    swarm2.set_callback(inbound.clone())?;
+
+    // swarm1 is alone, so it keeps the mailbox of swarm2.
+    send_to_mailbox(&swarm1, swarm2.did()).await?;
+    let key = VirtualNode::mailbox_did(swarm2.did()).to_string();
+    assert!(swarm1.dht().storage.get(&key).await?.is_some());
+
+    manually_establish_connection(&swarm1, &swarm2).await;
+
+    tokio::select! {
+        _ = async {
+            futures::join!(
+                async { swarm1.clone().listen().await },
+                async { swarm2.clone().listen().await },
+            );
+        } => { unreachable!(); }
+        res = async {
+            sleep(Duration::from_millis(2000)).await;
+            // Handed over and fetched, but delivered only once.
+            assert_eq!(inbound.0.lock().await.as_slice(), &[(
+                swarm1.did(),
+                b"hello".to_vec()
+            )]);
+            Ok::<(), Error>(())
+        } => res?
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_mailbox_on_rejoin() -> Result<()> {
+    let swarm1 = prepare_node(SecretKey::random()).await;
+    let swarm2 = prepare_node(SecretKey::random()).await;
+    let inbound = Arc::new(InboundMessages::default());
+    swarm2.set_callback(inbound.clone())?;
+
+    manually_establish_connection(&swarm1, &swarm2).await;
+
+    tokio::select! {
+        _ = async {
+            futures::join!(
+                async { swarm1.clone().listen().await },
+                async { swarm2.clone().listen().await },
+            );
+        } => { unreachable!(); }
+        res = async {
+            sleep(Duration::from_millis(1000)).await;
+            // swarm1 is the successor of swarm2, which keeps the mailbox of swarm2.
+            send_to_mailbox(&swarm1, swarm2.did()).await?;
+            sleep(Duration::from_millis(1000)).await;
+            assert!(inbound.0.lock().await.is_empty());
+            let key = VirtualNode::mailbox_did(swarm2.did()).to_string();
+            assert!(swarm1.dht().storage.get(&key).await?.is_some());
+
+            swarm2.rejoin(&swarm2.routing_state()).await;
+            sleep(Duration::from_millis(1000)).await;
+            assert_eq!(inbound.0.lock().await.as_slice(), &[(
+                swarm1.did(),
+                b"hello".to_vec()
+            )]);
+            // The mailbox is cleared once it's acknowledged.
+            assert!(swarm1.dht().storage.get(&key).await?.is_none());
+            Ok::<(), Error>(())
+        } => res?
+    }
+
+    Ok(())
+}
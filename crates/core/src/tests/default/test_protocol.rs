@@ -0,0 +1,125 @@
+use crate::ecc::SecretKey;
+use crate::error::Error;
+use crate::error::Result;
+use crate::message::Message;
+use crate::message::MessagePayload;
+use crate::message::ProtocolInfo;
+use crate::message::CAPABILITY_ENCRYPTED;
+use crate::message::CAPABILITY_TOPIC;
+use crate::message::PROTOCOL_VERSION;
+use crate::swarm::impls::ConnectionHandshake;
+use crate::tests::default::prepare_node;
+
+#[tokio::test]
+async fn test_refuse_incompatible_protocol() -> Result<()> {
+    let swarm1 = prepare_node(SecretKey::random()).await;
+    let swarm2 = prepare_node(SecretKey::random()).await;
+
+    let (_, mut offer) = swarm1.prepare_connection_offer(swarm2.did()).await?;
+    offer.handshake.as_mut().unwrap().protocol = ProtocolInfo {
+        version: PROTOCOL_VERSION + 2,
+        min_version: PROTOCOL_VERSION + 1,
+        capabilities: vec![],
+    };
+
+    assert!(matches!(
+        swarm2.answer_remote_connection(swarm1.did(), &offer).await,
+        Err(Error::IncompatibleProtocol(did, version))
+            if did == swarm1.did() && version == PROTOCOL_VERSION + 2
+    ));
+    assert!(swarm2.get_connection(swarm1.did()).is_none());
+    assert!(swarm2.peer_protocol(swarm1.did()).is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_downgrade_by_capabilities() -> Result<()> {
+    let swarm1 = prepare_node(SecretKey::random()).await;
+    let swarm2 = prepare_node(SecretKey::random()).await;
+
+    let (_, offer) = swarm1.prepare_connection_offer(swarm2.did()).await?;
+    let (_, mut answer) = swarm2
+        .answer_remote_connection(swarm1.did(), &offer)
+        .await?;
+    assert_eq!(
+        swarm2.peer_protocol(swarm1.did()),
+        Some(ProtocolInfo::default())
+    );
+
+    // Pretend swarm2 is an older node that doesn't know encrypted messages.
+    answer.handshake.as_mut().unwrap().protocol = ProtocolInfo {
+        capabilities: vec![CAPABILITY_TOPIC.to_string()],
+        ..Default::default()
+    };
+    swarm1
+        .accept_remote_connection(swarm2.did(), &answer)
+        .await?;
+    assert!(swarm1.peer_supports(swarm2.did(), CAPABILITY_TOPIC));
+    assert!(!swarm1.peer_supports(swarm2.did(), CAPABILITY_ENCRYPTED));
+
+    let session_sk = swarm1.session_sk();
+    let encrypted = MessagePayload::new_send(
+        Message::encrypted(b"hello", &session_sk.session_pubkey())?,
+        session_sk,
+        swarm2.did(),
+        swarm2.did(),
+    )?;
+    assert!(matches!(
+        swarm1.check_capability(swarm2.did(), &encrypted),
+        Err(Error::UnsupportedByPeer(did, _)) if did == swarm2.did()
+    ));
+
+    let custom = MessagePayload::new_send(
+        Message::custom(b"hello")?,
+        session_sk,
+        swarm2.did(),
+        swarm2.did(),
+    )?;
+    assert!(swarm1.check_capability(swarm2.did(), &custom).is_ok());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_legacy_peer_without_handshake_info() -> Result<()> {
+    let swarm1 = prepare_node(SecretKey::random()).await;
+    let swarm2 = prepare_node(SecretKey::random()).await;
+
+    // Pretend swarm1 is a legacy node, which doesn't send handshake info.
+    let (_, mut offer) = swarm1.prepare_connection_offer(swarm2.did()).await?;
+    offer.handshake = None;
+    swarm2
+        .answer_remote_connection(swarm1.did(), &offer)
+        .await?;
+    assert_eq!(
+        swarm2.peer_protocol(swarm1.did()),
+        Some(ProtocolInfo::legacy())
+    );
+    assert!(!swarm2.peer_supports(swarm1.did(), CAPABILITY_TOPIC));
+
+    let session_sk = swarm2.session_sk();
+    let custom = MessagePayload::new_send(
+        Message::custom(b"hello")?,
+        session_sk,
+        swarm1.did(),
+        swarm1.did(),
+    )?;
+    assert!(swarm2.check_capability(swarm1.did(), &custom).is_ok());
+    let encrypted = MessagePayload::new_send(
+        Message::encrypted(b"hello", &session_sk.session_pubkey())?,
+        session_sk,
+        swarm1.did(),
+        swarm1.did(),
+    )?;
+    assert!(matches!(
+        swarm2.check_capability(swarm1.did(), &encrypted),
+        Err(Error::UnsupportedByPeer(did, _)) if did == swarm1.did()
+    ));
+
+    // A peer not recorded is legacy as well.
+    let stranger = SecretKey::random().address().into();
+    assert!(!swarm2.peer_supports(stranger, CAPABILITY_TOPIC));
+
+    Ok(())
+}
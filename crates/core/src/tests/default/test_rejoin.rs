@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use crate::ecc::SecretKey;
+use crate::error::Result;
+use crate::storage::MemStorage;
+use crate::swarm::Swarm;
+use crate::tests::default::prepare_node;
+use crate::tests::default::prepare_node_with;
+use crate::tests::manually_establish_connection;
+
+fn prepare_persistent_node(key: SecretKey) -> Arc<Swarm> {
+    prepare_node_with(key, |builder| {
+        builder.routing_storage(Box::new(MemStorage::new()))
+    })
+}
+
+#[tokio::test]
+async fn test_save_and_load_routing_state() -> Result<()> {
+    let swarm1 = prepare_persistent_node(SecretKey::random());
+    let swarm2 = prepare_node(SecretKey::random()).await;
+
+    // Nothing is saved before connected to any peer.
+    swarm1.save_routing_state().await?;
+    assert!(swarm1.load_routing_state().await?.is_none());
+
+    manually_establish_connection(&swarm1, &swarm2).await;
+    swarm1.record_peer_endpoint(swarm2.did(), "http://127.0.0.1:50000".to_string());
+    swarm1.save_routing_state().await?;
+
+    let state = swarm1.load_routing_state().await?.unwrap();
+    let peer = state.peer(swarm2.did()).unwrap();
+    assert_eq!(peer.endpoint.as_deref(), Some("http://127.0.0.1:50000"));
+    assert!(peer.last_seen_ms > 0);
+    assert!(state.dids().contains(&swarm2.did()));
+
+    // Swarm without routing storage doesn't persist anything.
+    swarm2.save_routing_state().await?;
+    assert!(swarm2.load_routing_state().await?.is_none());
+
+    Ok(())
+}
@@ -166,3 +166,31 @@ async fn test_online_stabilization() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_reconnect_dropped_successor() -> Result<()> {
+    let swarm1 = prepare_node(SecretKey::random()).await;
+    let swarm2 = prepare_node(SecretKey::random()).await;
+    manually_establish_connection(&swarm1, &swarm2).await;
+
+    let swarm11 = swarm1.clone();
+    let swarm22 = swarm2.clone();
+    tokio::spawn(async move { swarm11.listen().await });
+    tokio::spawn(async move { swarm22.listen().await });
+    sleep(Duration::from_secs(1)).await;
+    assert!(swarm1.dht().successors().list()?.contains(&swarm2.did()));
+
+    // swarm2 drops the connection on purpose, so only swarm1 should reconnect.
+    swarm2.disconnect(swarm1.did()).await?;
+    sleep(Duration::from_secs(5)).await;
+    assert!(swarm2.reconnecting_peers().is_empty());
+    assert_eq!(swarm1.reconnecting_peers(), vec![(swarm2.did(), 0)]);
+
+    swarm1.reconnect_peers().await;
+    assert_eq!(swarm1.reconnecting_peers(), vec![(swarm2.did(), 1)]);
+
+    swarm1.unpin_peer(swarm2.did());
+    assert!(swarm1.reconnecting_peers().is_empty());
+
+    Ok(())
+}
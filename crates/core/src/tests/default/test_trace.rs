@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use tokio::time::sleep;
+
+use crate::ecc::SecretKey;
+use crate::error::Error;
+use crate::error::Result;
+use crate::tests::default::prepare_node;
+use crate::tests::manually_establish_connection;
+
+#[tokio::test]
+async fn test_trace_route_to_neighbour() -> Result<()> {
+    let swarm1 = prepare_node(SecretKey::random()).await;
+    let swarm2 = prepare_node(SecretKey::random()).await;
+    manually_establish_connection(&swarm1, &swarm2).await;
+
+    tokio::select! {
+        _ = async {
+            futures::join!(
+                async { swarm1.clone().listen().await },
+                async { swarm2.clone().listen().await },
+            );
+        } => { unreachable!(); }
+        res = async {
+            sleep(Duration::from_millis(1000)).await;
+
+            let route = swarm1.trace_route(swarm2.did(), Duration::from_secs(5)).await?;
+            assert!(route.reached);
+            assert_eq!(route.hops.len(), 1);
+            assert_eq!(route.hops[0].did, swarm2.did());
+            assert!(route.hops[0].received_at_ms.is_some());
+            assert!(route.hops[0].rtt_ms.is_some());
+
+            let route = swarm1.trace_route(swarm1.did(), Duration::from_secs(5)).await?;
+            assert!(route.reached);
+            assert!(route.hops.is_empty());
+
+            Ok::<(), Error>(())
+        } => res?
+    }
+
+    Ok(())
+}
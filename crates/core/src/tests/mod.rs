@@ -1,5 +1,6 @@
 use rings_transport::core::transport::ConnectionInterface;
 
+use crate::swarm::impls::ConnectionHandshake;
 use crate::swarm::Swarm;
 
 #[cfg(feature = "wasm")]
@@ -21,12 +22,16 @@ pub async fn manually_establish_connection(swarm1: &Swarm, swarm2: &Swarm) {
     assert!(swarm1.get_connection(swarm2.did()).is_none());
     assert!(swarm2.get_connection(swarm1.did()).is_none());
 
-    let conn1 = swarm1.new_connection(swarm2.did()).await.unwrap();
-    let conn2 = swarm2.new_connection(swarm1.did()).await.unwrap();
-
-    let offer = conn1.webrtc_create_offer().await.unwrap();
-    let answer = conn2.webrtc_answer_offer(offer).await.unwrap();
-    conn1.webrtc_accept_answer(answer).await.unwrap();
+    // Exchange handshake info as well, otherwise they are legacy to each other.
+    let (_, offer) = swarm1.prepare_connection_offer(swarm2.did()).await.unwrap();
+    let (_, answer) = swarm2
+        .answer_remote_connection(swarm1.did(), &offer)
+        .await
+        .unwrap();
+    swarm1
+        .accept_remote_connection(swarm2.did(), &answer)
+        .await
+        .unwrap();
 
     assert!(swarm1.get_connection(swarm2.did()).is_some());
     assert!(swarm2.get_connection(swarm1.did()).is_some());
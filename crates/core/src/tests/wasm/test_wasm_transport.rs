@@ -1,4 +1,3 @@
-use std::str::FromStr;
 use std::sync::Arc;
 
 use rings_transport::core::transport::ConnectionInterface;
@@ -10,11 +9,14 @@ use wasm_bindgen_test::*;
 
 use super::prepare_node;
 use crate::channels::Channel as CbChannel;
-use crate::dht::Did;
 use crate::ecc::SecretKey;
 use crate::error::Result;
+use crate::session::SessionSk;
 use crate::swarm::callback::InnerSwarmCallback;
 use crate::swarm::callback::SwarmCallback;
+use crate::swarm::InboundLimiter;
+use crate::swarm::PendingRequests;
+use crate::swarm::ReplayCache;
 use crate::tests::manually_establish_connection;
 use crate::types::channel::Channel;
 use crate::types::channel::TransportEvent;
@@ -43,9 +45,12 @@ async fn prepare_transport(channel: Option<Arc<CbChannel<TransportEvent>>>) -> T
     };
     let trans = Transport::new("stun://stun.l.google.com:19302", None);
     let callback = InnerSwarmCallback::new(
-        Did::from_str("0x11E807fcc88dD319270493fB2e822e388Fe36ab0").unwrap(),
+        SessionSk::new_with_seckey(&SecretKey::random()).unwrap(),
         ch.sender(),
         Arc::new(DefaultCallback {}),
+        Arc::new(PendingRequests::default()),
+        Arc::new(InboundLimiter::default()),
+        Arc::new(ReplayCache::default()),
     );
     trans
         .new_connection("test", Box::new(callback))
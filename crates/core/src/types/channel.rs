@@ -10,6 +10,7 @@ pub enum TransportEvent {
     Connected(Did),
     DataChannelMessage(Vec<u8>),
     Closed(Did),
+    Abused(Did),
 }
 
 /// Channel trant implement methods.
@@ -1,10 +1,23 @@
 //! Utils for ring-core
+use std::time::Duration;
+
 use chrono::Utc;
 /// Get local utc timestamp (millisecond)
 pub fn get_epoch_ms() -> u128 {
     Utc::now().timestamp_millis() as u128
 }
 
+#[cfg(not(feature = "wasm"))]
+pub(crate) async fn sleep(duration: Duration) {
+    futures_timer::Delay::new(duration).await
+}
+
+#[cfg(feature = "wasm")]
+pub(crate) async fn sleep(duration: Duration) {
+    let millis = duration.as_millis().try_into().unwrap_or(i32::MAX);
+    let _ = js_utils::window_sleep(millis).await;
+}
+
 #[cfg(feature = "wasm")]
 /// Toolset for wasm
 pub mod js_value {
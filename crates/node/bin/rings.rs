@@ -1,3 +1,4 @@
+use std::net::SocketAddr;
 use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -12,6 +13,8 @@ use futures::pin_mut;
 use futures::select;
 use futures::StreamExt;
 use futures_timer::Delay;
+use rings_node::backend::native::service::ServiceForwardConfig;
+use rings_node::backend::native::service::ServiceForwarder;
 use rings_node::backend::native::BackendBehaviour;
 use rings_node::backend::native::BackendConfig;
 use rings_node::backend::Backend;
@@ -22,6 +25,8 @@ use rings_node::native::cli::Client;
 use rings_node::native::config;
 use rings_node::native::endpoint::run_external_api;
 use rings_node::native::endpoint::run_internal_api;
+use rings_node::native::event::EventCallback;
+use rings_node::native::event::EventHub;
 use rings_node::prelude::rings_core::dht::Did;
 use rings_node::prelude::rings_core::ecc::SecretKey;
 use rings_node::prelude::rings_core::storage::sled::SledStorage;
@@ -64,6 +69,11 @@ enum Command {
     Send(SendCommand),
     #[command(about = "Registers or looks up a service on the network.", subcommand)]
     Service(ServiceCommand),
+    #[command(
+        about = "Manages subring membership and broadcasts to subrings.",
+        subcommand
+    )]
+    Subring(SubringCommand),
     #[command(
         about = "Show information of swarm. Include transport table, successors, predecessor, and finger table."
     )]
@@ -141,6 +151,20 @@ struct RunCommand {
     #[arg(long, help = "external ip address", env)]
     pub external_ip: Option<String>,
 
+    #[arg(
+        long,
+        help = "Local address to accept websocket connections on, such as 0.0.0.0:50001",
+        env
+    )]
+    pub websocket_listen: Option<String>,
+
+    #[arg(
+        long,
+        help = "Public url of websocket listener. If not provided, use ws://{websocket_listen}",
+        env
+    )]
+    pub websocket_url: Option<String>,
+
     #[arg(
         long,
         help = "Storage files location. If not provided, use storage.path in config file or ~/.local/share/rings",
@@ -268,6 +292,12 @@ struct ConnectWithDidCommand {
     client_args: ClientArgs,
 
     did: String,
+
+    #[arg(
+        long,
+        help = "connect over websocket, the peer should have a websocket listener"
+    )]
+    websocket: bool,
 }
 
 #[derive(Args, Debug)]
@@ -362,6 +392,12 @@ struct SendCustomMessageCommand {
     to_did: String,
     message_type: u16,
     data: String,
+
+    #[arg(long, help = "encrypt the message to the session of destination")]
+    encrypted: bool,
+
+    #[arg(long, help = "relay the message only by members of the subring")]
+    subring: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -369,6 +405,8 @@ struct SendCustomMessageCommand {
 enum ServiceCommand {
     Register(ServiceRegisterCommand),
     Lookup(ServiceLookupCommand),
+    #[command(about = "Starts a node daemon which forwards a local address to a remote service.")]
+    Forward(ServiceForwardCommand),
 }
 
 #[derive(Args, Debug)]
@@ -388,14 +426,96 @@ struct ServiceLookupCommand {
 }
 
 #[derive(Args, Debug)]
+struct ServiceForwardCommand {
+    #[command(flatten)]
+    run_args: RunCommand,
+
+    #[arg(long, help = "Local address to listen on, e.g. 127.0.0.1:8080")]
+    local: SocketAddr,
+
+    #[arg(long, help = "Service name on the remote peer")]
+    service: String,
+
+    #[arg(
+        long,
+        help = "Did of the remote peer. If not provided, lookup the service name in DHT"
+    )]
+    did: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+#[command(rename_all = "kebab-case")]
+enum SubringCommand {
+    #[command(about = "Joins a subring.")]
+    Join(SubringNameCommand),
+    #[command(about = "Leaves a subring.")]
+    Leave(SubringNameCommand),
+    #[command(about = "Syncs the local view of a subring to route messages within it.")]
+    Sync(SubringNameCommand),
+    #[command(about = "Lists members of a subring.")]
+    Members(SubringNameCommand),
+    #[command(about = "Sends a custom message to all members of a subring.")]
+    Broadcast(SubringBroadcastCommand),
+}
+
+#[derive(Args, Debug)]
+struct SubringNameCommand {
+    #[command(flatten)]
+    client_args: ClientArgs,
+
+    name: String,
+}
+
+#[derive(Args, Debug)]
+struct SubringBroadcastCommand {
+    #[command(flatten)]
+    client_args: ClientArgs,
+
+    name: String,
+
+    #[arg(help = "base64 encoded data")]
+    data: String,
+}
+
+#[derive(Args, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
 struct InspectCommand {
     #[command(flatten)]
     client_args: ClientArgs,
+
+    #[command(subcommand)]
+    command: Option<InspectSubcommand>,
+}
+
+#[derive(Subcommand, Debug)]
+#[command(rename_all = "kebab-case")]
+enum InspectSubcommand {
+    #[command(about = "Traces the route of messages to a peer, with latency of each hop.")]
+    Route(InspectRouteCommand),
+}
+
+#[derive(Args, Debug)]
+struct InspectRouteCommand {
+    #[command(flatten)]
+    client_args: ClientArgs,
+
+    did: String,
+
+    #[arg(
+        long,
+        default_value_t = 10000,
+        help = "Milliseconds to wait for the destination to report"
+    )]
+    timeout_ms: u64,
 }
 
 #[allow(clippy::too_many_arguments)]
-async fn daemon_run(args: RunCommand) -> anyhow::Result<()> {
+async fn daemon_run(
+    args: RunCommand,
+    service_forwards: Vec<ServiceForwardConfig>,
+) -> anyhow::Result<()> {
     let mut c = config::Config::read_fs(args.config_args.config)?;
+    c.service_forwards.extend(service_forwards);
 
     if let Some(ice_servers) = args.ice_servers {
         c.ice_servers = ice_servers;
@@ -403,6 +523,12 @@ async fn daemon_run(args: RunCommand) -> anyhow::Result<()> {
     if let Some(external_ip) = args.external_ip {
         c.external_ip = Some(external_ip);
     }
+    if let Some(websocket_listen) = args.websocket_listen {
+        c.websocket_listen = Some(websocket_listen);
+    }
+    if let Some(websocket_url) = args.websocket_url {
+        c.websocket_url = Some(websocket_url);
+    }
     if let Some(stabilize_timeout) = args.stabilize_timeout {
         c.stabilize_timeout = stabilize_timeout;
     }
@@ -431,9 +557,9 @@ async fn daemon_run(args: RunCommand) -> anyhow::Result<()> {
         (c.data_storage, c.measure_storage)
     };
 
-    let per_data_storage = Box::new(
-        SledStorage::new_with_cap_and_path(data_storage.capacity, data_storage.path).await?,
-    );
+    let per_data_storage =
+        SledStorage::new_with_cap_and_path(data_storage.capacity, data_storage.path).await?;
+    let per_routing_storage = Box::new(per_data_storage.open_tree("routing")?);
     let per_measure_storage = Box::new(
         SledStorage::new_with_cap_and_path(measure_storage.capacity, measure_storage.path).await?,
     );
@@ -442,34 +568,76 @@ async fn daemon_run(args: RunCommand) -> anyhow::Result<()> {
 
     let processor = Arc::new(
         ProcessorBuilder::from_config(&pc)?
-            .storage(per_data_storage)
+            .storage(Box::new(per_data_storage))
+            .routing_storage(per_routing_storage)
             .measure(measure)
             .build()?,
     );
     println!("Did: {}", processor.swarm.did());
     let backend_behaviour = BackendBehaviour::new(bc).await?;
     let backend_service_names = backend_behaviour.service_names();
+    let service_forwarder = backend_behaviour.service_forwarder();
     let provider = Arc::new(Provider::from_processor(processor.clone()));
-    let backend = Arc::new(Backend::new(provider, Box::new(backend_behaviour)));
-    processor.swarm.set_callback(backend).unwrap();
+    let backend = Arc::new(Backend::new(provider.clone(), Box::new(backend_behaviour)));
+    let events = Arc::new(EventHub::new());
+    let callback = Arc::new(EventCallback::new(events.clone(), backend));
+    processor.swarm.set_callback(callback).unwrap();
 
     let processor_clone1 = processor.clone();
     let processor_clone2 = processor.clone();
-    let _ = futures::join!(
-        processor.listen(),
-        service_loop_register(&processor, backend_service_names),
-        run_internal_api(c.internal_api_port, processor_clone2),
-        run_external_api(c.external_api_addr, processor_clone1),
-    );
+    let running = async {
+        futures::join!(
+            processor.listen(),
+            rejoin(&processor),
+            service_loop_register(&processor, backend_service_names),
+            service_forward(&service_forwarder, provider),
+            run_internal_api(c.internal_api_port, processor_clone2, events),
+            run_external_api(c.external_api_addr, processor_clone1),
+        )
+    };
+
+    tokio::select! {
+        _ = running => {}
+        _ = shutdown_signal() => {
+            tracing::info!("Shutting down, leaving the ring");
+            if let Err(e) = processor.shutdown().await {
+                tracing::error!("Failed to leave the ring gracefully: {}", e);
+            }
+        }
+    }
 
     Ok(())
 }
 
+/// Rejoin the ring by the routing state persisted before restart.
+async fn rejoin(processor: &Processor) {
+    if let Err(e) = processor.rejoin().await {
+        tracing::error!("Failed to rejoin the ring: {}", e);
+    }
+}
+
+/// Wait for ctrl-c, or SIGTERM on unix.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::signal;
+        use tokio::signal::unix::SignalKind;
+
+        let mut terminate = signal(SignalKind::terminate()).expect("Failed to listen SIGTERM");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
 async fn pubsub_run(client_args: ClientArgs, topic: String) -> anyhow::Result<()> {
     let mut stdin = io::BufReader::new(io::stdin()).lines();
 
     let client = client_args.new_client().await?;
-    let stream = client.subscribe_topic(topic.clone()).await;
+    let stream = client.subscribe_topic(topic.clone()).await?;
     pin_mut!(stream);
 
     loop {
@@ -494,7 +662,7 @@ async fn main() -> anyhow::Result<()> {
     init_logging(cli.log_level);
 
     match cli.command {
-        Command::Run(args) => daemon_run(args).await,
+        Command::Run(args) => daemon_run(args, vec![]).await,
         Command::Pubsub(args) => pubsub_run(args.client_args, args.topic).await,
         Command::Connect(ConnectCommand::Node(args)) => {
             args.client_args
@@ -509,7 +677,7 @@ async fn main() -> anyhow::Result<()> {
             args.client_args
                 .new_client()
                 .await?
-                .connect_with_did(args.did.as_str())
+                .connect_with_did(args.did.as_str(), args.websocket)
                 .await?
                 .display();
             Ok(())
@@ -584,7 +752,12 @@ async fn main() -> anyhow::Result<()> {
             args.client_args
                 .new_client()
                 .await?
-                .send_custom_message(args.to_did.as_str(), args.data.as_str())
+                .send_custom_message(
+                    args.to_did.as_str(),
+                    args.data.as_str(),
+                    args.encrypted,
+                    args.subring,
+                )
                 .await?
                 .display();
             Ok(())
@@ -607,26 +780,90 @@ async fn main() -> anyhow::Result<()> {
                 .display();
             Ok(())
         }
-        Command::Init(args) => {
-            let session_sk_path = args.session_args.new_session_then_write_to_fs()?;
-            let config = config::Config::new(session_sk_path);
-            let p = config.write_fs(&args.location)?;
-            println!("Your config file has saved to: {}", p);
+        Command::Service(ServiceCommand::Forward(args)) => {
+            let forward = ServiceForwardConfig {
+                service: args.service,
+                did: args.did,
+                local: args.local,
+            };
+            daemon_run(args.run_args, vec![forward]).await
+        }
+        Command::Subring(SubringCommand::Join(args)) => {
+            args.client_args
+                .new_client()
+                .await?
+                .join_subring(args.name.as_str())
+                .await?
+                .display();
             Ok(())
         }
-        Command::NewSession(args) => {
-            args.session_args.new_session_then_write_to_fs()?;
+        Command::Subring(SubringCommand::Leave(args)) => {
+            args.client_args
+                .new_client()
+                .await?
+                .leave_subring(args.name.as_str())
+                .await?
+                .display();
+            Ok(())
+        }
+        Command::Subring(SubringCommand::Sync(args)) => {
+            args.client_args
+                .new_client()
+                .await?
+                .sync_subring(args.name.as_str())
+                .await?
+                .display();
+            Ok(())
+        }
+        Command::Subring(SubringCommand::Members(args)) => {
+            args.client_args
+                .new_client()
+                .await?
+                .list_subring_members(args.name.as_str())
+                .await?
+                .display();
             Ok(())
         }
-        Command::Inspect(args) => {
+        Command::Subring(SubringCommand::Broadcast(args)) => {
             args.client_args
                 .new_client()
                 .await?
-                .inspect()
+                .broadcast_subring_message(args.name.as_str(), args.data.as_str())
                 .await?
                 .display();
             Ok(())
         }
+        Command::Init(args) => {
+            let session_sk_path = args.session_args.new_session_then_write_to_fs()?;
+            let config = config::Config::new(session_sk_path);
+            let p = config.write_fs(&args.location)?;
+            println!("Your config file has saved to: {}", p);
+            Ok(())
+        }
+        Command::NewSession(args) => {
+            args.session_args.new_session_then_write_to_fs()?;
+            Ok(())
+        }
+        Command::Inspect(args) => match args.command {
+            None => {
+                args.client_args
+                    .new_client()
+                    .await?
+                    .inspect()
+                    .await?
+                    .display();
+                Ok(())
+            }
+            Some(InspectSubcommand::Route(args)) => {
+                args.client_args
+                    .new_client()
+                    .await?
+                    .trace_route(&args.did, args.timeout_ms)
+                    .await?
+                    .display();
+                Ok(())
+            }
+        },
     }
 }
 
@@ -652,3 +889,9 @@ async fn service_loop_register(processor: &Processor, names: Vec<String>) {
         }
     }
 }
+
+async fn service_forward(forwarder: &ServiceForwarder, provider: Arc<Provider>) {
+    if let Err(e) = forwarder.run(provider).await {
+        tracing::error!("service forward error: {}", e);
+    }
+}
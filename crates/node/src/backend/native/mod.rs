@@ -24,6 +24,8 @@ use rings_core::message::MessageVerificationExt;
 use crate::backend::native::extension::Extension;
 use crate::backend::native::extension::ExtensionConfig;
 use crate::backend::native::service::ServiceConfig;
+use crate::backend::native::service::ServiceForwardConfig;
+use crate::backend::native::service::ServiceForwarder;
 use crate::backend::native::service::ServiceProvider;
 use crate::backend::types::BackendMessage;
 use crate::backend::types::MessageHandler;
@@ -34,6 +36,8 @@ use crate::provider::Provider;
 pub struct BackendConfig {
     /// Config of services
     pub services: Vec<ServiceConfig>,
+    /// Config of forwarding local addresses to remote services
+    pub service_forwards: Vec<ServiceForwardConfig>,
     /// Config of extensions
     pub extensions: ExtensionConfig,
 }
@@ -41,6 +45,7 @@ pub struct BackendConfig {
 /// BackendBehaviour is a Context holder of backend message handler
 pub struct BackendBehaviour {
     server: ServiceProvider,
+    service_forwards: Vec<ServiceForwardConfig>,
     extension: Extension,
 }
 
@@ -62,6 +67,7 @@ impl BackendBehaviour {
     pub async fn new(config: BackendConfig) -> Result<Self, Error> {
         Ok(Self {
             server: ServiceProvider::new(config.services),
+            service_forwards: config.service_forwards,
             extension: Extension::new(&config.extensions).await?,
         })
     }
@@ -75,6 +81,11 @@ impl BackendBehaviour {
             .collect()
     }
 
+    /// Create a ServiceForwarder of configured forwards, which shares tunnels with the service provider
+    pub fn service_forwarder(&self) -> ServiceForwarder {
+        self.server.forwarder(self.service_forwards.clone())
+    }
+
     async fn handle_backend_message(
         &self,
         provider: Arc<Provider>,
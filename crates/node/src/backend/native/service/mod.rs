@@ -14,6 +14,14 @@
 //! A Rings Service Provider is a structure that serves Rings Service. Sometimes referred to as
 //! "hidden-services," the Rings Service Provider exclusively handles the ServiceMessage type
 //! of BackendMessage. This component is crucial for managing the flow of messages within decentralized networks.
+//!
+//! # Service Forwarder
+//!
+//! A Service Forwarder is the consumer side of Rings Service. It listens on local TCP addresses,
+//! resolves the remote service by DID or by looking up its name in DHT, and tunnels every accepted
+//! connection to the service. It shares tunnels with the Service Provider, so that the packages
+//! sent back by the remote peer can be written to the local connection. The did resolved by
+//! name is cached, and looked up again after dialing it failed.
 mod tcp_proxy;
 use std::collections::HashMap;
 use std::net::SocketAddr;
@@ -22,11 +30,16 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use dashmap::DashMap;
+use rings_core::dht::Did;
 use rings_core::message::MessagePayload;
 use rings_core::message::MessageVerificationExt;
 use rings_rpc::method::Method;
+use rings_rpc::protos::rings_node::LookupServiceRequest;
+use rings_rpc::protos::rings_node::LookupServiceResponse;
 use serde::Deserialize;
 use serde::Serialize;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
 
 use crate::backend::native::service::tcp_proxy::tcp_connect_with_timeout;
 use crate::backend::native::service::tcp_proxy::Tunnel;
@@ -37,6 +50,8 @@ use crate::backend::types::HttpResponse;
 use crate::backend::types::ServiceMessage;
 use crate::backend::types::TunnelId;
 use crate::consts::TCP_SERVER_TIMEOUT;
+use crate::consts::VNODE_FETCH_INTERVAL_MS;
+use crate::consts::VNODE_FETCH_TIMEOUT_MS;
 use crate::error::Error;
 use crate::error::Result;
 use crate::provider::Provider;
@@ -54,12 +69,36 @@ pub struct ServiceConfig {
     pub addr: SocketAddr,
 }
 
+/// Service Forward Config for forwarding a local address to a remote service
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ServiceForwardConfig {
+    /// service name on remote peer
+    pub service: String,
+
+    /// did of remote peer, will lookup service name in dht storage if not provided
+    pub did: Option<String>,
+
+    /// local address to listen on
+    pub local: SocketAddr,
+}
+
 /// Service Provider, which hold tunnel and a list of service
 pub struct ServiceProvider {
     /// Service configs
     pub services: Vec<ServiceConfig>,
     /// Services tunnel, which is a HashMap of tunnel Id and Tunnel instance
-    pub tunnels: DashMap<TunnelId, Tunnel>,
+    pub tunnels: Arc<DashMap<TunnelId, Tunnel>>,
+}
+
+/// Service Forwarder, which hold forward configs and share tunnels with [ServiceProvider]
+#[derive(Clone)]
+pub struct ServiceForwarder {
+    /// Forward configs
+    pub forwards: Vec<ServiceForwardConfig>,
+    /// Services tunnel, shared with [ServiceProvider]
+    pub tunnels: Arc<DashMap<TunnelId, Tunnel>>,
+    /// Did of services resolved by name, indexed by service name
+    resolved: Arc<DashMap<String, Did>>,
 }
 
 impl ServiceProvider {
@@ -67,7 +106,16 @@ impl ServiceProvider {
     pub fn new(services: Vec<ServiceConfig>) -> Self {
         Self {
             services,
-            tunnels: DashMap::new(),
+            tunnels: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Create a ServiceForwarder which shares tunnels with this provider
+    pub fn forwarder(&self, forwards: Vec<ServiceForwardConfig>) -> ServiceForwarder {
+        ServiceForwarder {
+            forwards,
+            tunnels: self.tunnels.clone(),
+            resolved: Arc::new(DashMap::new()),
         }
     }
 
@@ -153,6 +201,113 @@ impl MessageHandler<ServiceMessage> for ServiceProvider {
     }
 }
 
+impl ServiceForwarder {
+    /// Listen on all local addresses, and forward accepted connections to remote services
+    pub async fn run(&self, provider: Arc<Provider>) -> Result<()> {
+        let jobs = self
+            .forwards
+            .iter()
+            .map(|forward| self.listen(provider.clone(), forward));
+        futures::future::try_join_all(jobs).await?;
+        Ok(())
+    }
+
+    /// Accept local connections, each is forwarded in its own task, so that a slow lookup or
+    /// dial doesn't block accepting others.
+    async fn listen(&self, provider: Arc<Provider>, forward: &ServiceForwardConfig) -> Result<()> {
+        let listener = TcpListener::bind(forward.local)
+            .await
+            .map_err(|e| Error::BindError(e.to_string()))?;
+        tracing::info!("Forward {} to service {}", forward.local, forward.service);
+
+        loop {
+            let local_stream = match listener.accept().await {
+                Ok((stream, addr)) => {
+                    tracing::debug!("Accept {} for service {}", addr, forward.service);
+                    stream
+                }
+                Err(e) => {
+                    tracing::error!("Accept local connection failed: {e:?}");
+                    continue;
+                }
+            };
+
+            let this = self.clone();
+            let provider = provider.clone();
+            let forward = forward.clone();
+            tokio::spawn(async move {
+                if let Err(e) = this.dial(provider, &forward, local_stream).await {
+                    tracing::error!("Forward to service {} failed: {e}", forward.service);
+                }
+            });
+        }
+    }
+
+    async fn dial(
+        &self,
+        provider: Arc<Provider>,
+        forward: &ServiceForwardConfig,
+        local_stream: TcpStream,
+    ) -> Result<()> {
+        let peer_did = match forward.did {
+            Some(ref did) => Did::from_str(did).map_err(|_| Error::InvalidDid(did.clone()))?,
+            None => self.resolve(provider.clone(), &forward.service).await?,
+        };
+
+        let tid = TunnelId::new_v4();
+        let msg = ServiceMessage::TcpDial {
+            tid,
+            service: forward.service.clone(),
+        };
+        let backend_message: BackendMessage = msg.into();
+        let params = backend_message.into_send_backend_message_request(peer_did)?;
+        // Insert the tunnel before dialing, so that the packages of a service speaking first are
+        // buffered instead of dropped. It listens the local stream after dialing, so that the
+        // packages of local client can't reach the service before the tunnel is dialed.
+        self.tunnels.insert(tid, Tunnel::new(tid));
+        if let Err(e) = provider.request(Method::SendBackendMessage, params).await {
+            self.resolved.remove(&forward.service);
+            self.tunnels.remove(&tid);
+            return Err(e);
+        }
+
+        // The tunnel is removed if the service failed to connect.
+        let mut tunnel = self.tunnels.get_mut(&tid).ok_or(Error::TunnelNotFound)?;
+        tunnel
+            .listen(provider.clone(), local_stream, peer_did)
+            .await;
+        Ok(())
+    }
+
+    /// Get did of service from cache, or look it up in DHT.
+    async fn resolve(&self, provider: Arc<Provider>, name: &str) -> Result<Did> {
+        if let Some(did) = self.resolved.get(name) {
+            return Ok(*did);
+        }
+        let did = lookup_service(provider, name).await?;
+        self.resolved.insert(name.to_string(), did);
+        Ok(did)
+    }
+}
+
+/// Look up did of service in DHT. On a cold cache, the vnode is fetched from remote replicas
+/// asynchronously, so the lookup is retried until it's found or timeout.
+async fn lookup_service(provider: Arc<Provider>, name: &str) -> Result<Did> {
+    let interval = Duration::from_millis(VNODE_FETCH_INTERVAL_MS);
+    for _ in 0..=VNODE_FETCH_TIMEOUT_MS / VNODE_FETCH_INTERVAL_MS {
+        let params = LookupServiceRequest {
+            name: name.to_string(),
+        };
+        let resp = provider.request(Method::LookupService, params).await?;
+        let resp: LookupServiceResponse = serde_json::from_value(resp)?;
+        if let Some(did) = resp.dids.first() {
+            return Did::from_str(did).map_err(|_| Error::InvalidDid(did.clone()));
+        }
+        tokio::time::sleep(interval).await;
+    }
+    Err(Error::ServiceNotFound(name.to_string()))
+}
+
 async fn handle_http_request(addr: SocketAddr, req: &HttpRequest) -> Result<HttpResponse> {
     let url = format!("http://{}/{}", addr, req.path.trim_start_matches('/'));
     tracing::info!("Handle http request on url: {:?} start", url);
@@ -23,7 +23,8 @@ use crate::provider::Provider;
 /// Abstract Tcp Tunnel
 pub struct Tunnel {
     tid: TunnelId,
-    remote_stream_tx: Option<mpsc::Sender<Bytes>>,
+    remote_stream_tx: mpsc::Sender<Bytes>,
+    remote_stream_rx: Option<mpsc::Receiver<Bytes>>,
     listener_cancel_token: Option<CancellationToken>,
     listener: Option<tokio::task::JoinHandle<()>>,
 }
@@ -32,7 +33,6 @@ pub struct Tunnel {
 pub struct TunnelListener {
     tid: TunnelId,
     local_stream: TcpStream,
-    remote_stream_tx: mpsc::Sender<Bytes>,
     remote_stream_rx: mpsc::Receiver<Bytes>,
     peer_did: Did,
     cancel_token: CancellationToken,
@@ -56,11 +56,14 @@ impl Drop for Tunnel {
 }
 
 impl Tunnel {
-    /// Create a new tunnel with a given tunnel Id
+    /// Create a new tunnel with a given tunnel Id.
+    /// Bytes sent to the tunnel are buffered until it starts listening.
     pub fn new(tid: TunnelId) -> Self {
+        let (remote_stream_tx, remote_stream_rx) = mpsc::channel(1024);
         Self {
             tid,
-            remote_stream_tx: None,
+            remote_stream_tx,
+            remote_stream_rx: Some(remote_stream_rx),
             listener: None,
             listener_cancel_token: None,
         }
@@ -68,10 +71,8 @@ impl Tunnel {
 
     /// Send bytes to tunnel via channel
     pub async fn send(&self, bytes: Bytes) {
-        if let Some(ref tx) = self.remote_stream_tx {
-            let _ = tx.send(bytes).await;
-        } else {
-            tracing::error!("Tunnel {} remote stream tx is none", self.tid);
+        if self.remote_stream_tx.send(bytes).await.is_err() {
+            tracing::error!("Tunnel {} remote stream rx is closed", self.tid);
         }
     }
 
@@ -83,17 +84,16 @@ impl Tunnel {
         local_stream: TcpStream,
         peer_did: Did,
     ) {
-        if self.listener.is_some() {
+        let Some(remote_stream_rx) = self.remote_stream_rx.take() else {
             return;
-        }
+        };
         let provider = provider.clone();
-        let mut listener = TunnelListener::new(self.tid, local_stream, peer_did).await;
+        let mut listener =
+            TunnelListener::new(self.tid, local_stream, remote_stream_rx, peer_did).await;
         let listener_cancel_token = listener.cancel_token();
-        let remote_stream_tx = listener.remote_stream_tx.clone();
         let listener_handler =
             tokio::spawn(Box::pin(async move { listener.listen(provider).await }));
 
-        self.remote_stream_tx = Some(remote_stream_tx);
         self.listener = Some(listener_handler);
         self.listener_cancel_token = Some(listener_cancel_token);
     }
@@ -101,12 +101,15 @@ impl Tunnel {
 
 impl TunnelListener {
     /// Create a new listener instance with TcpStream, tunnel id, and did of a target peer
-    async fn new(tid: TunnelId, local_stream: TcpStream, peer_did: Did) -> Self {
-        let (remote_stream_tx, remote_stream_rx) = mpsc::channel(1024);
+    async fn new(
+        tid: TunnelId,
+        local_stream: TcpStream,
+        remote_stream_rx: mpsc::Receiver<Bytes>,
+        peer_did: Did,
+    ) -> Self {
         Self {
             tid,
             local_stream,
-            remote_stream_tx,
             remote_stream_rx,
             peer_did,
             cancel_token: CancellationToken::new(),
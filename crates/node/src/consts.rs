@@ -11,3 +11,13 @@ pub const MSG_SEND_FAILED_LIMIT: i64 = 10;
 pub const MSG_RECV_FAILED_LIMIT: i64 = 10;
 /// Timeout for proxied TCP connections
 pub const TCP_SERVER_TIMEOUT: u64 = 30;
+/// Default timeout of waiting for the reply of custom request, in milliseconds
+pub const CUSTOM_REQUEST_TIMEOUT_MS: u64 = 30000;
+/// Max timeout of waiting for the reply of custom request, in milliseconds
+pub const CUSTOM_REQUEST_MAX_TIMEOUT_MS: u64 = 120000;
+/// Timeout of waiting successor to acknowledge each batch of handed over storage on shutdown, in milliseconds
+pub const SHUTDOWN_HANDOVER_TIMEOUT_MS: u64 = 10000;
+/// Default timeout of waiting for the destination of trace route to report, in milliseconds
+pub const TRACE_ROUTE_TIMEOUT_MS: u64 = 10000;
+/// Max timeout of waiting for the destination of trace route to report, in milliseconds
+pub const TRACE_ROUTE_MAX_TIMEOUT_MS: u64 = 60000;
@@ -120,6 +120,10 @@ pub enum Error {
     TunnelNotFound = 1303,
     #[error("Tunnel error: {0:?}")]
     TunnelError(TunnelDefeat) = 1304,
+    #[error("Service not found: {0}")]
+    ServiceNotFound(String) = 1305,
+    #[error("Bind local address error: {0}")]
+    BindError(String) = 1306,
 }
 
 impl Error {
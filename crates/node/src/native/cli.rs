@@ -16,20 +16,19 @@
 //! - Send HTTP requests to remote peers.
 //! - Load a seed file to establish a connection with a remote peer.
 
-use std::time::Duration;
-
 use async_stream::stream;
-use futures::pin_mut;
-use futures::select;
-use futures::FutureExt;
 use futures::Stream;
-use futures_timer::Delay;
+use futures::StreamExt;
+use rings_core::dht::vnode::VirtualNode;
 use rings_rpc::jsonrpc::Client as RpcClient;
 use rings_rpc::protos::rings_node::*;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
 
 use crate::backend::types::BackendMessage;
 use crate::backend::types::HttpRequest;
 use crate::backend::types::ServiceMessage;
+use crate::native::event::NodeEvent;
+use crate::native::event::NODE_EVENT_METHOD;
 use crate::seed::Seed;
 use crate::util::loader::ResourceLoader;
 
@@ -39,6 +38,7 @@ type Output<T> = anyhow::Result<ClientOutput<T>>;
 /// Wrap json_client send request between nodes or browsers.
 pub struct Client {
     client: RpcClient,
+    endpoint_url: String,
 }
 
 /// Wrap client output contain raw result and humanreadable display.
@@ -52,7 +52,10 @@ impl Client {
     /// Creates a new Client instance with the specified endpoint URL and signature.
     pub fn new(endpoint_url: &str) -> anyhow::Result<Self> {
         let rpc_client = RpcClient::new(endpoint_url);
-        Ok(Self { client: rpc_client })
+        Ok(Self {
+            client: rpc_client,
+            endpoint_url: endpoint_url.to_string(),
+        })
     }
 
     /// Establishes a WebRTC connection with a remote peer using HTTP as the signaling channel.
@@ -92,10 +95,12 @@ impl Client {
     }
 
     /// Attempts to connect to a peer using a DID stored in a Distributed Hash Table (DHT).
-    pub async fn connect_with_did(&mut self, did: &str) -> Output<()> {
+    /// If `websocket` is true, the connection is made over websocket instead of webrtc.
+    pub async fn connect_with_did(&mut self, did: &str, websocket: bool) -> Output<()> {
         self.client
             .connect_with_did(&ConnectWithDidRequest {
                 did: did.to_string(),
+                websocket: Some(websocket),
             })
             .await
             .map_err(|e| anyhow::anyhow!("{}", e))?;
@@ -114,11 +119,26 @@ impl Client {
             .peers;
 
         let mut display = String::new();
-        display.push_str("Did, TransportId, Status\n");
+        display.push_str("Did, TransportId, Status, RTT(ms), Candidates\n");
         display.push_str(
             peers
                 .iter()
-                .map(|peer| format!("{}, {}, {}", peer.did, peer.did, peer.state))
+                .map(|peer| {
+                    let stats = peer.stats.clone().unwrap_or_default();
+                    let rtt = stats
+                        .rtt_ms
+                        .map(|rtt| format!("{:.1}", rtt))
+                        .unwrap_or("-".to_string());
+                    format!(
+                        "{}, {}, {}, {}, {}/{}",
+                        peer.did,
+                        peer.did,
+                        peer.state,
+                        rtt,
+                        stats.local_candidate_type,
+                        stats.remote_candidate_type
+                    )
+                })
                 .collect::<Vec<_>>()
                 .join("\n")
                 .as_str(),
@@ -140,11 +160,21 @@ impl Client {
     }
 
     /// Sends a custom message to the specified peer.
-    pub async fn send_custom_message(&self, did: &str, data: &str) -> Output<()> {
+    /// If `encrypted` is true, the message is encrypted to the session of the peer.
+    /// If `subring` is provided, the message is relayed only by members of that subring.
+    pub async fn send_custom_message(
+        &self,
+        did: &str,
+        data: &str,
+        encrypted: bool,
+        subring: Option<String>,
+    ) -> Output<()> {
         self.client
             .send_custom_message(&SendCustomMessageRequest {
                 destination_did: did.to_string(),
                 data: data.to_string(),
+                encrypted: Some(encrypted),
+                subring,
             })
             .await
             .map_err(|e| anyhow::anyhow!("{}", e))?;
@@ -231,6 +261,7 @@ impl Client {
             .publish_message_to_topic(&PublishMessageToTopicRequest {
                 topic: topic.to_string(),
                 data: data.to_string(),
+                ttl_ms: None,
             })
             .await
             .map_err(|e| anyhow::anyhow!("{}", e))?;
@@ -238,43 +269,134 @@ impl Client {
     }
 
     /// Subscribes to the specified topic and returns a stream of messages published to the topic.
-    pub async fn subscribe_topic<'a, 'b>(
-        &'a self,
+    ///
+    /// Messages are pushed by the node through its websocket endpoint, no polling is involved.
+    pub async fn subscribe_topic(
+        &self,
         topic: String,
-    ) -> impl Stream<Item = String> + 'b
-    where
-        'a: 'b,
-    {
-        let mut skip = 0usize;
-
-        stream! {
-            loop {
-                let timeout = Delay::new(Duration::from_secs(5)).fuse();
-                pin_mut!(timeout);
-
-                select! {
-                    _ = timeout => {
-                        let result = self
-                            .client
-                            .fetch_topic_messages(&FetchTopicMessagesRequest {
-                                topic: topic.clone(),
-                                skip: skip as i64,
-                            })
-                            .await;
-
-                        if let Err(e) = result {
-                            tracing::error!("Failed to fetch messages of topic: {}, {}", topic, e);
-                            continue;
-                        }
-                        let messages = result.unwrap().data;
-                        for msg in messages.iter().cloned() {
-                            yield msg
-                        }
-                        skip += messages.len();
+    ) -> anyhow::Result<impl Stream<Item = String>> {
+        let vid = VirtualNode::gen_did(&topic)
+            .map_err(|e| anyhow::anyhow!("{}", e))?
+            .to_string();
+
+        // Connect before subscribing, so that no message is missed.
+        let (mut ws, _) = tokio_tungstenite::connect_async(self.ws_url()).await?;
+
+        self.client
+            .subscribe_topic(&SubscribeTopicRequest { topic })
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        Ok(stream! {
+            while let Some(msg) = ws.next().await {
+                let text = match msg {
+                    Ok(WsMessage::Text(text)) => text,
+                    Ok(WsMessage::Close(_)) => break,
+                    Ok(_) => continue,
+                    Err(e) => {
+                        tracing::error!("Failed to receive events of node: {}", e);
+                        break;
+                    }
+                };
+                let Ok(notification) = serde_json::from_str::<serde_json::Value>(&text) else {
+                    continue;
+                };
+                if notification["method"] != NODE_EVENT_METHOD {
+                    continue;
+                }
+                if let Ok(NodeEvent::TopicMessage { topic, data }) =
+                    serde_json::from_value(notification["params"].clone())
+                {
+                    if topic != vid {
+                        continue;
+                    }
+                    for msg in data {
+                        yield msg
                     }
                 }
             }
-        }
+        })
+    }
+
+    /// Stops receiving messages of the specified topic.
+    pub async fn unsubscribe_topic(&self, topic: &str) -> Output<()> {
+        self.client
+            .unsubscribe_topic(&UnsubscribeTopicRequest {
+                topic: topic.to_string(),
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        ClientOutput::ok("Done.".into(), ())
+    }
+
+    /// Joins the subring with the given name.
+    pub async fn join_subring(&self, name: &str) -> Output<()> {
+        self.client
+            .join_subring(&JoinSubringRequest {
+                name: name.to_string(),
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        ClientOutput::ok("Done.".into(), ())
+    }
+
+    /// Leaves the subring with the given name.
+    pub async fn leave_subring(&self, name: &str) -> Output<()> {
+        self.client
+            .leave_subring(&LeaveSubringRequest {
+                name: name.to_string(),
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        ClientOutput::ok("Done.".into(), ())
+    }
+
+    /// Syncs the local view of the subring with the given name.
+    /// Messages can only be routed within subrings that are synced.
+    pub async fn sync_subring(&self, name: &str) -> Output<()> {
+        self.client
+            .sync_subring(&SyncSubringRequest {
+                name: name.to_string(),
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        ClientOutput::ok("Done.".into(), ())
+    }
+
+    /// Lists the DIDs of members of the subring with the given name.
+    pub async fn list_subring_members(&self, name: &str) -> Output<()> {
+        let members = self
+            .client
+            .list_subring_members(&ListSubringMembersRequest {
+                name: name.to_string(),
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?
+            .members;
+
+        ClientOutput::ok(members.join("\n"), ())
+    }
+
+    /// Sends a custom message to all members of the subring with the given name.
+    /// Displays the DIDs of members the message was sent to.
+    pub async fn broadcast_subring_message(&self, name: &str, data: &str) -> Output<()> {
+        let members = self
+            .client
+            .broadcast_subring_message(&BroadcastSubringMessageRequest {
+                name: name.to_string(),
+                data: data.to_string(),
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?
+            .members;
+
+        ClientOutput::ok(members.join("\n"), ())
+    }
+
+    /// Websocket url of the node, derived from the endpoint url.
+    fn ws_url(&self) -> String {
+        let url = format!("{}/ws", self.endpoint_url.trim_end_matches('/'));
+        url.replacen("http", "ws", 1)
     }
 
     /// Query for swarm inspect info.
@@ -292,6 +414,40 @@ impl Client {
 
         ClientOutput::ok(display, swarm_info)
     }
+
+    /// Trace the route of messages to a did, and print latency of each hop.
+    pub async fn trace_route(&self, did: &str, timeout_ms: u64) -> Output<()> {
+        let route = self
+            .client
+            .trace_route(&TraceRouteRequest {
+                did: did.to_string(),
+                timeout_ms,
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut display = String::new();
+        display.push_str("Hop, Did, Latency(ms), RTT(ms)\n");
+        for (i, hop) in route.hops.iter().enumerate() {
+            let latency = hop
+                .latency_ms
+                .map(|t| t.to_string())
+                .unwrap_or("-".to_string());
+            let rtt = hop.rtt_ms.map(|t| t.to_string()).unwrap_or("-".to_string());
+            display.push_str(&format!("{}, {}, {}, {}\n", i + 1, hop.did, latency, rtt));
+        }
+        if route.reached {
+            display.push_str(&format!("Reached {} in {} hops", did, route.hops.len()));
+        } else {
+            display.push_str(&format!(
+                "Not reached {}, traced {} hops",
+                did,
+                route.hops.len()
+            ));
+        }
+
+        ClientOutput::ok(display, ())
+    }
 }
 
 impl<T> ClientOutput<T> {
@@ -8,10 +8,17 @@ use serde::Serialize;
 
 use crate::backend::native::extension::ExtensionConfig;
 use crate::backend::native::service::ServiceConfig;
+use crate::backend::native::service::ServiceForwardConfig;
 use crate::backend::native::BackendConfig;
 use crate::error::Error;
 use crate::error::Result;
+use crate::prelude::rings_core::dht::Did;
 use crate::prelude::rings_core::ecc::SecretKey;
+use crate::prelude::rings_core::swarm::AdmissionPolicy;
+use crate::prelude::rings_core::swarm::EvictionPolicy;
+use crate::prelude::rings_core::swarm::InboundLimits;
+use crate::prelude::rings_core::swarm::LookupPolicy;
+use crate::prelude::rings_core::swarm::ReconnectPolicy;
 use crate::prelude::SessionSk;
 use crate::processor::ProcessorConfig;
 use crate::processor::ProcessorConfigSerialized;
@@ -60,10 +67,45 @@ pub struct Config {
     pub stabilize_timeout: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub external_ip: Option<String>,
+    /// Local address to accept websocket connections on, such as `0.0.0.0:50001`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub websocket_listen: Option<String>,
+    /// Public url of the websocket listener, such as `ws://example.com:50001`.
+    /// Defaults to `ws://{websocket_listen}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub websocket_url: Option<String>,
+    /// The maximum number of connections, unlimited if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<usize>,
+    /// Number of connection slots reserved for successors, predecessor and fingers.
+    #[serde(default)]
+    pub reserved_connections: usize,
+    /// How to make room for a new connection when the maximum is reached, see [EvictionPolicy].
+    #[serde(default)]
+    pub eviction: EvictionPolicy,
+    /// Rate limits and quotas of inbound traffic, see [InboundLimits].
+    #[serde(default)]
+    pub inbound_limits: InboundLimits,
+    /// Policy of admitting peers, see [AdmissionPolicy].
+    #[serde(default)]
+    pub admission: AdmissionPolicy,
+    /// Policy of iterative lookup, see [LookupPolicy].
+    #[serde(default)]
+    pub lookup: LookupPolicy,
+    /// Policy of reconnecting dropped peers, see [ReconnectPolicy].
+    #[serde(default)]
+    pub reconnect: ReconnectPolicy,
+    /// Dids of peers to reconnect whenever their connections are dropped.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned_peers: Vec<Did>,
     /// When there is no configuration in the YAML file,
     /// its deserialization is equivalent to `vec![]` in Rust.
     #[serde(default)]
     pub services: Vec<ServiceConfig>,
+    /// When there is no configuration in the YAML file,
+    /// its deserialization is equivalent to `vec![]` in Rust.
+    #[serde(default)]
+    pub service_forwards: Vec<ServiceForwardConfig>,
     pub data_storage: StorageConfig,
     pub measure_storage: StorageConfig,
     /// When there is no configuration in the YAML file,
@@ -94,20 +136,36 @@ impl TryFrom<Config> for ProcessorConfigSerialized {
             })
         };
 
-        if let Some(ext_ip) = config.external_ip {
-            Ok(Self::new_with_ext_addr(
+        let mut serialized = if let Some(ext_ip) = config.external_ip {
+            Self::new_with_ext_addr(
                 config.ice_servers,
                 session_sk,
                 config.stabilize_timeout,
                 ext_ip,
-            ))
+            )
         } else {
-            Ok(Self::new(
-                config.ice_servers,
-                session_sk,
-                config.stabilize_timeout,
-            ))
+            Self::new(config.ice_servers, session_sk, config.stabilize_timeout)
+        };
+
+        if let Some(listen) = config.websocket_listen {
+            let url = config
+                .websocket_url
+                .unwrap_or_else(|| format!("ws://{}", listen));
+            serialized = serialized.with_websocket(listen, url);
         }
+
+        if let Some(max) = config.max_connections {
+            serialized = serialized
+                .with_connection_limits(max, config.reserved_connections)
+                .with_eviction_policy(config.eviction);
+        }
+
+        Ok(serialized
+            .with_inbound_limits(config.inbound_limits)
+            .with_admission(config.admission)
+            .with_lookup_policy(config.lookup)
+            .with_reconnect_policy(config.reconnect)
+            .with_pinned_peers(config.pinned_peers))
     }
 }
 
@@ -122,6 +180,7 @@ impl From<Config> for BackendConfig {
     fn from(config: Config) -> Self {
         Self {
             services: config.services,
+            service_forwards: config.service_forwards,
             extensions: config.extension,
         }
     }
@@ -141,7 +200,18 @@ impl Config {
             ice_servers: DEFAULT_ICE_SERVERS.to_string(),
             stabilize_timeout: DEFAULT_STABILIZE_TIMEOUT,
             external_ip: None,
+            websocket_listen: None,
+            websocket_url: None,
+            max_connections: None,
+            reserved_connections: 0,
+            eviction: EvictionPolicy::default(),
+            inbound_limits: InboundLimits::default(),
+            admission: AdmissionPolicy::default(),
+            lookup: LookupPolicy::default(),
+            reconnect: ReconnectPolicy::default(),
+            pinned_peers: vec![],
             services: vec![],
+            service_forwards: vec![],
             data_storage: DEFAULT_DATA_STORAGE_CONFIG.clone(),
             measure_storage: DEFAULT_MEASURE_STORAGE_CONFIG.clone(),
             extension: ExtensionConfig::default(),
@@ -208,5 +278,39 @@ measure_storage:
         let cfg: Config = serde_yaml::from_str(yaml).unwrap();
         assert_eq!(cfg.extension, ExtensionConfig::default());
         assert_eq!(cfg.services, vec![]);
+        assert_eq!(cfg.service_forwards, vec![]);
+    }
+
+    #[test]
+    fn test_deserialization_with_service_forwards() {
+        let yaml = r#"
+session_sk: session_sk
+internal_api_port: 50000
+external_api_addr: 127.0.0.1:50001
+endpoint_url: http://127.0.0.1:50000
+ice_servers: stun://stun.l.google.com:19302
+stabilize_timeout: 3
+external_ip: null
+service_forwards:
+  - service: ssh
+    local: 127.0.0.1:2222
+  - service: web
+    did: "0x11E807fcc88dD319270493fB2e822e388Fe36ab0"
+    local: 127.0.0.1:8080
+data_storage:
+  path: /Users/foo/.rings/data
+  capacity: 200000000
+measure_storage:
+  path: /Users/foo/.rings/measure
+  capacity: 200000000
+"#;
+        let cfg: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(cfg.service_forwards.len(), 2);
+        assert_eq!(cfg.service_forwards[0].service, "ssh");
+        assert_eq!(cfg.service_forwards[0].did, None);
+        assert_eq!(
+            cfg.service_forwards[1].local,
+            "127.0.0.1:8080".parse().unwrap()
+        );
     }
 }
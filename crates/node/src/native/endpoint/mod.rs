@@ -18,6 +18,7 @@ use rings_rpc::protos::rings_node::NodeInfoResponse;
 use tower_http::cors::CorsLayer;
 
 use self::http_error::HttpError;
+use crate::native::event::EventHub;
 use crate::processor::Processor;
 
 /// JSON-RPC state
@@ -31,9 +32,9 @@ where M: jsonrpc_core::Middleware<Arc<Processor>>
 
 /// websocket state
 #[derive(Clone)]
-#[allow(dead_code)]
 pub struct WsState {
-    processor: Arc<Processor>,
+    jsonrpc: Arc<JsonRpcState<InternalRpcMiddleware>>,
+    events: Arc<EventHub>,
 }
 
 /// Status state
@@ -45,8 +46,13 @@ pub struct StatusState {
 struct ExternalRpcMiddleware;
 struct InternalRpcMiddleware;
 
-/// Run a web server to handle jsonrpc request locally
-pub async fn run_internal_api(port: u16, processor: Arc<Processor>) -> anyhow::Result<()> {
+/// Run a web server to handle jsonrpc request locally.
+/// Events published to `events` will be pushed to websocket clients connected to `/ws`.
+pub async fn run_internal_api(
+    port: u16,
+    processor: Arc<Processor>,
+    events: Arc<EventHub>,
+) -> anyhow::Result<()> {
     let binding_addr = SocketAddr::from(([127, 0, 0, 1], port));
 
     let jsonrpc_handler = MetaIoHandler::with_middleware(InternalRpcMiddleware);
@@ -56,7 +62,8 @@ pub async fn run_internal_api(port: u16, processor: Arc<Processor>) -> anyhow::R
     });
 
     let ws_state = Arc::new(WsState {
-        processor: processor.clone(),
+        jsonrpc: jsonrpc_state.clone(),
+        events,
     });
 
     let status_state = Arc::new(StatusState { processor });
@@ -1,25 +1,80 @@
 use std::sync::Arc;
 
+use axum::extract::ws::Message as WsMessage;
 use axum::extract::ws::WebSocket;
+use futures::SinkExt;
 use futures::StreamExt;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
 
 use super::WsState;
+use crate::native::event::NODE_EVENT_METHOD;
 
 /// Actual websocket statemachine (one will be spawned per connection)
-pub async fn handle_socket(_ws_state: Arc<WsState>, socket: WebSocket) {
-    let (_, mut receiver) = socket.split();
+///
+/// Events of node are pushed as json-rpc notifications with method [NODE_EVENT_METHOD].
+/// Text frames received are handled as json-rpc requests, the responses are sent back
+/// through the same socket.
+pub async fn handle_socket(ws_state: Arc<WsState>, socket: WebSocket) {
+    let (mut sender, mut receiver) = socket.split();
+    let (resp_tx, mut resp_rx) = mpsc::unbounded_channel::<String>();
+    let mut events = ws_state.events.subscribe();
 
     let mut send_task = tokio::spawn(async move {
+        let mut cnt = 0;
         loop {
-            tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+            let text = tokio::select! {
+                ev = events.recv() => match ev {
+                    Ok(ev) => {
+                        let notification = serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "method": NODE_EVENT_METHOD,
+                            "params": ev,
+                        });
+                        notification.to_string()
+                    }
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!("WS subscriber lagged, {} events skipped", n);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                resp = resp_rx.recv() => match resp {
+                    Some(resp) => resp,
+                    None => break,
+                },
+            };
+            if let Err(e) = sender.send(WsMessage::Text(text)).await {
+                tracing::debug!("WS send failed: {:?}", e);
+                break;
+            }
+            cnt += 1;
         }
+        cnt
     });
+
+    let jsonrpc_state = ws_state.jsonrpc.clone();
     let mut recv_task = tokio::spawn(async move {
         let mut cnt = 0;
         while let Some(Ok(msg)) = receiver.next().await {
             cnt += 1;
-            // print message and break if instructed to do so
             tracing::debug!("recv message: {:?}", msg);
+            let req = match msg {
+                WsMessage::Text(text) => text,
+                WsMessage::Close(_) => break,
+                _ => continue,
+            };
+            let state = jsonrpc_state.clone();
+            let resp_tx = resp_tx.clone();
+            tokio::spawn(async move {
+                let resp = state
+                    .io_handler
+                    .handle_request(&req, state.processor.clone())
+                    .await;
+                if let Some(resp) = resp {
+                    let _ = resp_tx.send(resp);
+                }
+            });
         }
         cnt
     });
@@ -0,0 +1,258 @@
+#![warn(missing_docs)]
+//! Live events of node, which can be subscribed by websocket clients.
+//!
+//! [EventCallback] wraps the [SharedSwarmCallback] of the node, forwards everything
+//! to it, and publishes a [NodeEvent] to the [EventHub] at the same time.
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use rings_core::dht::vnode::VirtualNode;
+use rings_core::message::CustomMessage;
+use rings_core::message::Message;
+use rings_core::message::MessagePayload;
+use rings_core::message::MessageVerificationExt;
+use rings_core::swarm::callback::SharedSwarmCallback;
+use rings_core::swarm::callback::SwarmCallback;
+use rings_core::swarm::callback::SwarmEvent;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::backend::types::BackendMessage;
+
+/// Method name of the json-rpc notification which carries a [NodeEvent].
+pub const NODE_EVENT_METHOD: &str = "event";
+
+/// Capacity of the event channel. Subscribers lagging behind more than this will miss events.
+pub const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Event pushed to subscribers, serialized as json object with a `type` tag.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum NodeEvent {
+    /// A custom message sent to this node.
+    CustomMessage {
+        /// Transaction id of the message.
+        tx_id: String,
+        /// Did of the sender.
+        from: String,
+        /// Base64 encoded raw data.
+        data: String,
+        /// Decoded backend message, if data is a valid one.
+        backend: Option<BackendMessage>,
+    },
+    /// New data of a subscribed topic.
+    TopicMessage {
+        /// The virtual id of topic.
+        topic: String,
+        /// New data of the topic.
+        data: Vec<String>,
+    },
+    /// The connection state of a peer has changed.
+    ConnectionStateChange {
+        /// Did of remote peer.
+        peer: String,
+        /// The final state of the connection.
+        state: String,
+    },
+    /// The successor list of local DHT has changed.
+    SuccessorsChange {
+        /// The latest successor list.
+        successors: Vec<String>,
+    },
+    /// The predecessor of local DHT has changed.
+    PredecessorChange {
+        /// The latest predecessor.
+        predecessor: Option<String>,
+    },
+    /// Virtual nodes were fetched from DHT.
+    StorageHit {
+        /// The virtual nodes that were found.
+        data: Vec<VirtualNode>,
+    },
+    /// A compare-and-swap of virtual node was rejected by a remote replica.
+    StorageConflict {
+        /// The did of target virtual node.
+        did: String,
+        /// The version expected by the operation.
+        expected: u64,
+        /// The current virtual node stored on the replica.
+        current: Option<VirtualNode>,
+    },
+    /// An attempt of reconnecting a dropped peer was made.
+    ReconnectAttempt {
+        /// Did of remote peer.
+        peer: String,
+        /// The number of attempts made, starting from 1.
+        attempt: u32,
+        /// The error raised when starting the attempt.
+        error: Option<String>,
+    },
+    /// A connection was closed to make room for a new one.
+    ConnectionEvicted {
+        /// Did of remote peer.
+        peer: String,
+    },
+    /// Reconnecting a dropped peer was given up.
+    ReconnectGaveUp {
+        /// Did of remote peer.
+        peer: String,
+        /// The number of attempts made.
+        attempts: u32,
+    },
+}
+
+/// Broadcaster of [NodeEvent].
+pub struct EventHub {
+    sender: broadcast::Sender<NodeEvent>,
+}
+
+impl Default for EventHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventHub {
+    /// Create a new EventHub.
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    /// Subscribe events published after this call.
+    pub fn subscribe(&self) -> broadcast::Receiver<NodeEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Publish an event to all subscribers. The event is dropped if there is no subscriber.
+    pub fn publish(&self, event: NodeEvent) {
+        let _ = self.sender.send(event);
+    }
+}
+
+impl NodeEvent {
+    /// Convert [SwarmEvent] to [NodeEvent], returns None if the event is not interested.
+    pub fn from_swarm_event(event: &SwarmEvent) -> Option<Self> {
+        match event {
+            SwarmEvent::ConnectionStateChange { peer, state } => {
+                Some(NodeEvent::ConnectionStateChange {
+                    peer: peer.to_string(),
+                    state: format!("{:?}", state),
+                })
+            }
+            SwarmEvent::SuccessorsChange { successors } => Some(NodeEvent::SuccessorsChange {
+                successors: successors.iter().map(|s| s.to_string()).collect(),
+            }),
+            SwarmEvent::PredecessorChange { predecessor } => Some(NodeEvent::PredecessorChange {
+                predecessor: predecessor.map(|p| p.to_string()),
+            }),
+            SwarmEvent::StorageHit { data } => Some(NodeEvent::StorageHit { data: data.clone() }),
+            SwarmEvent::StorageConflict {
+                did,
+                expected,
+                current,
+            } => Some(NodeEvent::StorageConflict {
+                did: did.to_string(),
+                expected: *expected,
+                current: current.clone(),
+            }),
+            SwarmEvent::ReconnectAttempt {
+                peer,
+                attempt,
+                error,
+            } => Some(NodeEvent::ReconnectAttempt {
+                peer: peer.to_string(),
+                attempt: *attempt,
+                error: error.clone(),
+            }),
+            SwarmEvent::ConnectionEvicted { peer } => Some(NodeEvent::ConnectionEvicted {
+                peer: peer.to_string(),
+            }),
+            SwarmEvent::ReconnectGaveUp { peer, attempts } => Some(NodeEvent::ReconnectGaveUp {
+                peer: peer.to_string(),
+                attempts: *attempts,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// SwarmCallback which publishes events to [EventHub] before calling the inner callback.
+pub struct EventCallback {
+    hub: Arc<EventHub>,
+    inner: SharedSwarmCallback,
+}
+
+impl EventCallback {
+    /// Wrap a callback with event hub.
+    pub fn new(hub: Arc<EventHub>, inner: SharedSwarmCallback) -> Self {
+        Self { hub, inner }
+    }
+}
+
+#[async_trait]
+impl SwarmCallback for EventCallback {
+    async fn on_validate(
+        &self,
+        payload: &MessagePayload,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.inner.on_validate(payload).await
+    }
+
+    async fn on_inbound(&self, payload: &MessagePayload) -> Result<(), Box<dyn std::error::Error>> {
+        match payload.transaction.data() {
+            Ok(Message::CustomMessage(CustomMessage(msg))) => {
+                self.hub.publish(NodeEvent::CustomMessage {
+                    tx_id: payload.transaction.tx_id.to_string(),
+                    from: payload.transaction.signer().to_string(),
+                    data: base64::encode(&msg),
+                    backend: bincode::deserialize(&msg).ok(),
+                });
+            }
+            Ok(Message::TopicMessage(msg)) => {
+                self.hub.publish(NodeEvent::TopicMessage {
+                    topic: msg.vid.to_string(),
+                    data: msg.data.iter().filter_map(|v| v.decode().ok()).collect(),
+                });
+            }
+            _ => {}
+        }
+        self.inner.on_inbound(payload).await
+    }
+
+    async fn on_event(&self, event: &SwarmEvent) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(ev) = NodeEvent::from_swarm_event(event) {
+            self.hub.publish(ev);
+        }
+        self.inner.on_event(event).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rings_core::dht::Did;
+    use rings_transport::core::transport::WebrtcConnectionState;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_publish_swarm_event_to_subscribers() {
+        let hub = EventHub::new();
+        let mut rx = hub.subscribe();
+        let peer = Did::from(1u32);
+
+        let ev = NodeEvent::from_swarm_event(&SwarmEvent::ConnectionStateChange {
+            peer,
+            state: WebrtcConnectionState::Connected,
+        })
+        .unwrap();
+        hub.publish(ev);
+
+        let received = serde_json::to_value(rx.recv().await.unwrap()).unwrap();
+        assert_eq!(received["type"], "connection_state_change");
+        assert_eq!(received["peer"], peer.to_string());
+        assert_eq!(received["state"], "Connected");
+    }
+}
@@ -4,15 +4,19 @@
 
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
+use bytes::Bytes;
 use rings_core::storage::MemStorage;
 use rings_rpc::protos::rings_node::*;
+use rings_rpc::protos::rings_node_handler::HandleRpc;
 use rings_transport::core::transport::ConnectionInterface;
 use serde::Deserialize;
 use serde::Serialize;
 
 use crate::backend::types::BackendMessage;
 use crate::consts::DATA_REDUNDANT;
+use crate::consts::SHUTDOWN_HANDOVER_TIMEOUT_MS;
 use crate::error::Error;
 use crate::error::Result;
 use crate::measure::PeriodicMeasure;
@@ -23,16 +27,26 @@ use crate::prelude::rings_core::dht::VNodeStorage;
 use crate::prelude::rings_core::message::Encoded;
 use crate::prelude::rings_core::message::Encoder;
 use crate::prelude::rings_core::message::Message;
+use crate::prelude::rings_core::message::MessagePayload;
 use crate::prelude::rings_core::message::PayloadSender;
 use crate::prelude::rings_core::prelude::uuid;
+use crate::prelude::rings_core::swarm::AdmissionPolicy;
+use crate::prelude::rings_core::swarm::ConnectionLimits;
+use crate::prelude::rings_core::swarm::EvictionPolicy;
+use crate::prelude::rings_core::swarm::InboundLimits;
+use crate::prelude::rings_core::swarm::LookupPolicy;
 use crate::prelude::rings_core::swarm::MeasureImpl;
+use crate::prelude::rings_core::swarm::ReconnectPolicy;
+use crate::prelude::rings_core::swarm::RoutingStorage;
 use crate::prelude::rings_core::swarm::Swarm;
 use crate::prelude::rings_core::swarm::SwarmBuilder;
+use crate::prelude::rings_core::swarm::TraceRoute;
 use crate::prelude::vnode;
 use crate::prelude::wasm_export;
 use crate::prelude::ChordStorageInterface;
 use crate::prelude::ChordStorageInterfaceCacheChecker;
 use crate::prelude::SessionSk;
+use crate::prelude::SubringInterface;
 
 /// ProcessorConfig is usually serialized as json or yaml.
 /// There is a `from_config` method in [ProcessorBuilder] used to initialize the Builder with a serialized ProcessorConfig.
@@ -47,6 +61,26 @@ pub struct ProcessorConfig {
     session_sk: SessionSk,
     /// Stabilization timeout.
     stabilize_timeout: u64,
+    /// Local address to accept websocket connections on.
+    websocket_listen: Option<String>,
+    /// Public url of the websocket listener, which is dialed by other peers.
+    websocket_url: Option<String>,
+    /// The maximum number of connections, unlimited if not set.
+    max_connections: Option<usize>,
+    /// Number of connection slots reserved for the peers critical to DHT.
+    reserved_connections: usize,
+    /// How to make room for a new connection when the maximum is reached.
+    eviction: EvictionPolicy,
+    /// Rate limits and quotas of inbound traffic.
+    inbound_limits: InboundLimits,
+    /// Policy of admitting peers.
+    admission: AdmissionPolicy,
+    /// Policy of iterative lookup.
+    lookup: LookupPolicy,
+    /// Policy of reconnecting dropped peers.
+    reconnect: ReconnectPolicy,
+    /// Peers to reconnect whenever their connections are dropped.
+    pinned_peers: Vec<Did>,
 }
 
 #[wasm_export]
@@ -58,6 +92,16 @@ impl ProcessorConfig {
             external_address: None,
             session_sk,
             stabilize_timeout,
+            websocket_listen: None,
+            websocket_url: None,
+            max_connections: None,
+            reserved_connections: 0,
+            eviction: EvictionPolicy::default(),
+            inbound_limits: InboundLimits::default(),
+            admission: AdmissionPolicy::default(),
+            lookup: LookupPolicy::default(),
+            reconnect: ReconnectPolicy::default(),
+            pinned_peers: vec![],
         }
     }
 
@@ -73,6 +117,16 @@ impl ProcessorConfig {
             external_address: Some(external_address),
             session_sk,
             stabilize_timeout,
+            websocket_listen: None,
+            websocket_url: None,
+            max_connections: None,
+            reserved_connections: 0,
+            eviction: EvictionPolicy::default(),
+            inbound_limits: InboundLimits::default(),
+            admission: AdmissionPolicy::default(),
+            lookup: LookupPolicy::default(),
+            reconnect: ReconnectPolicy::default(),
+            pinned_peers: vec![],
         }
     }
 
@@ -103,6 +157,36 @@ pub struct ProcessorConfigSerialized {
     session_sk: String,
     /// An unsigned integer representing the stabilization timeout.
     stabilize_timeout: u64,
+    /// An optional string representing the local address of websocket listener.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    websocket_listen: Option<String>,
+    /// An optional string representing the public url of websocket listener.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    websocket_url: Option<String>,
+    /// An optional unsigned integer representing the maximum number of connections.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_connections: Option<usize>,
+    /// An unsigned integer representing the connection slots reserved for DHT.
+    #[serde(default)]
+    reserved_connections: usize,
+    /// How to make room for a new connection, evicting the least recently used one by default.
+    #[serde(default)]
+    eviction: EvictionPolicy,
+    /// Rate limits and quotas of inbound traffic.
+    #[serde(default)]
+    inbound_limits: InboundLimits,
+    /// Policy of admitting peers, including allowlist, denylist and key of private network.
+    #[serde(default)]
+    admission: AdmissionPolicy,
+    /// Policy of iterative lookup, which is disabled by default.
+    #[serde(default)]
+    lookup: LookupPolicy,
+    /// Policy of reconnecting dropped peers, with exponential backoff.
+    #[serde(default)]
+    reconnect: ReconnectPolicy,
+    /// A list of dids to reconnect whenever their connections are dropped.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pinned_peers: Vec<Did>,
 }
 
 impl ProcessorConfigSerialized {
@@ -113,6 +197,16 @@ impl ProcessorConfigSerialized {
             external_address: None,
             session_sk,
             stabilize_timeout,
+            websocket_listen: None,
+            websocket_url: None,
+            max_connections: None,
+            reserved_connections: 0,
+            eviction: EvictionPolicy::default(),
+            inbound_limits: InboundLimits::default(),
+            admission: AdmissionPolicy::default(),
+            lookup: LookupPolicy::default(),
+            reconnect: ReconnectPolicy::default(),
+            pinned_peers: vec![],
         }
     }
 
@@ -128,8 +222,69 @@ impl ProcessorConfigSerialized {
             external_address: Some(external_address),
             session_sk,
             stabilize_timeout,
+            websocket_listen: None,
+            websocket_url: None,
+            max_connections: None,
+            reserved_connections: 0,
+            eviction: EvictionPolicy::default(),
+            inbound_limits: InboundLimits::default(),
+            admission: AdmissionPolicy::default(),
+            lookup: LookupPolicy::default(),
+            reconnect: ReconnectPolicy::default(),
+            pinned_peers: vec![],
         }
     }
+
+    /// Accept websocket connections on `listen` address, which is dialed by other peers as `url`.
+    pub fn with_websocket(mut self, listen: String, url: String) -> Self {
+        self.websocket_listen = Some(listen);
+        self.websocket_url = Some(url);
+        self
+    }
+
+    /// Limit the number of connections to `max`, `reserved` of which are reserved for the peers
+    /// critical to DHT. Connections are evicted by [EvictionPolicy] when the limit is reached.
+    pub fn with_connection_limits(mut self, max: usize, reserved: usize) -> Self {
+        self.max_connections = Some(max);
+        self.reserved_connections = reserved;
+        self
+    }
+
+    /// Set how to make room for a new connection when the limit is reached.
+    pub fn with_eviction_policy(mut self, policy: EvictionPolicy) -> Self {
+        self.eviction = policy;
+        self
+    }
+
+    /// Set the rate limits and quotas of inbound traffic.
+    pub fn with_inbound_limits(mut self, limits: InboundLimits) -> Self {
+        self.inbound_limits = limits;
+        self
+    }
+
+    /// Set the policy of admitting peers.
+    pub fn with_admission(mut self, policy: AdmissionPolicy) -> Self {
+        self.admission = policy;
+        self
+    }
+
+    /// Set the policy of iterative lookup.
+    pub fn with_lookup_policy(mut self, policy: LookupPolicy) -> Self {
+        self.lookup = policy;
+        self
+    }
+
+    /// Set the policy of reconnecting dropped peers.
+    pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
+    /// Keep reconnecting `peers` whenever their connections are dropped.
+    pub fn with_pinned_peers(mut self, peers: Vec<Did>) -> Self {
+        self.pinned_peers = peers;
+        self
+    }
 }
 
 impl TryFrom<ProcessorConfig> for ProcessorConfigSerialized {
@@ -140,6 +295,16 @@ impl TryFrom<ProcessorConfig> for ProcessorConfigSerialized {
             external_address: ins.external_address.clone(),
             session_sk: ins.session_sk.dump()?,
             stabilize_timeout: ins.stabilize_timeout,
+            websocket_listen: ins.websocket_listen.clone(),
+            websocket_url: ins.websocket_url.clone(),
+            max_connections: ins.max_connections,
+            reserved_connections: ins.reserved_connections,
+            eviction: ins.eviction,
+            inbound_limits: ins.inbound_limits.clone(),
+            admission: ins.admission.clone(),
+            lookup: ins.lookup.clone(),
+            reconnect: ins.reconnect.clone(),
+            pinned_peers: ins.pinned_peers.clone(),
         })
     }
 }
@@ -152,6 +317,16 @@ impl TryFrom<ProcessorConfigSerialized> for ProcessorConfig {
             external_address: ins.external_address.clone(),
             session_sk: SessionSk::from_str(&ins.session_sk)?,
             stabilize_timeout: ins.stabilize_timeout,
+            websocket_listen: ins.websocket_listen.clone(),
+            websocket_url: ins.websocket_url.clone(),
+            max_connections: ins.max_connections,
+            reserved_connections: ins.reserved_connections,
+            eviction: ins.eviction,
+            inbound_limits: ins.inbound_limits.clone(),
+            admission: ins.admission.clone(),
+            lookup: ins.lookup.clone(),
+            reconnect: ins.reconnect.clone(),
+            pinned_peers: ins.pinned_peers.clone(),
         })
     }
 }
@@ -192,6 +367,17 @@ pub struct ProcessorBuilder {
     storage: Option<VNodeStorage>,
     measure: Option<MeasureImpl>,
     stabilize_timeout: u64,
+    websocket_listen: Option<String>,
+    websocket_url: Option<String>,
+    max_connections: Option<usize>,
+    reserved_connections: usize,
+    eviction: EvictionPolicy,
+    inbound_limits: InboundLimits,
+    admission: AdmissionPolicy,
+    lookup: LookupPolicy,
+    reconnect: ReconnectPolicy,
+    pinned_peers: Vec<Did>,
+    routing_storage: Option<RoutingStorage>,
 }
 
 /// Processor for rings-node rpc server
@@ -220,6 +406,17 @@ impl ProcessorBuilder {
             storage: None,
             measure: None,
             stabilize_timeout: config.stabilize_timeout,
+            websocket_listen: config.websocket_listen.clone(),
+            websocket_url: config.websocket_url.clone(),
+            max_connections: config.max_connections,
+            reserved_connections: config.reserved_connections,
+            eviction: config.eviction,
+            inbound_limits: config.inbound_limits.clone(),
+            admission: config.admission.clone(),
+            lookup: config.lookup.clone(),
+            reconnect: config.reconnect.clone(),
+            pinned_peers: config.pinned_peers.clone(),
+            routing_storage: None,
         })
     }
 
@@ -235,6 +432,12 @@ impl ProcessorBuilder {
         self
     }
 
+    /// Set the storage to persist routing state, which is used by [Processor::rejoin].
+    pub fn routing_storage(mut self, storage: RoutingStorage) -> Self {
+        self.routing_storage = Some(storage);
+        self
+    }
+
     /// Build the [Processor].
     pub fn build(self) -> Result<Processor> {
         self.session_sk
@@ -253,7 +456,40 @@ impl ProcessorBuilder {
         if let Some(measure) = self.measure {
             swarm_builder = swarm_builder.measure(measure);
         }
+
+        if let Some(routing_storage) = self.routing_storage {
+            swarm_builder = swarm_builder.routing_storage(routing_storage);
+        }
+
+        swarm_builder = swarm_builder.connection_limits(ConnectionLimits {
+            max_connections: self.max_connections,
+            reserved_slots: self.reserved_connections,
+            eviction: self.eviction,
+        });
+        swarm_builder = swarm_builder
+            .inbound_limits(self.inbound_limits)
+            .admission(self.admission)
+            .lookup_policy(self.lookup)
+            .reconnect_policy(self.reconnect);
+
+        #[cfg(feature = "node")]
+        if let Some(listen) = self.websocket_listen {
+            let addr = listen.parse().map_err(|_| Error::InvalidAddress)?;
+            let url = self
+                .websocket_url
+                .unwrap_or_else(|| format!("ws://{}", listen));
+            swarm_builder = swarm_builder.websocket_listener(addr, url);
+        }
+
+        #[cfg(not(feature = "node"))]
+        if self.websocket_listen.is_some() || self.websocket_url.is_some() {
+            tracing::warn!("Websocket listener is not supported in this build, ignored.");
+        }
+
         let swarm = Arc::new(swarm_builder.build());
+        for did in self.pinned_peers {
+            swarm.pin_peer(did);
+        }
         let stabilization = Arc::new(Stabilization::new(swarm.clone(), self.stabilize_timeout));
 
         Ok(Processor {
@@ -318,6 +554,49 @@ impl Processor {
         futures::future::join_all(close_async).await;
     }
 
+    /// Rejoin the ring by the routing state persisted before restart. Peers with known endpoints
+    /// are connected by http first, then the rest are connected by DHT through them.
+    pub async fn rejoin(&self) -> Result<()> {
+        let Some(state) = self
+            .swarm
+            .load_routing_state()
+            .await
+            .map_err(Error::Swarm)?
+        else {
+            return Ok(());
+        };
+
+        let tasks = state
+            .peers
+            .iter()
+            .filter(|p| self.swarm.get_connection(p.did).is_none())
+            .filter_map(|p| p.endpoint.clone())
+            .map(|url| self.handle_rpc(ConnectPeerViaHttpRequest { url }));
+        for result in futures::future::join_all(tasks).await {
+            if let Err(e) = result {
+                tracing::warn!("Failed to reconnect known endpoint: {}", e);
+            }
+        }
+
+        for (did, conn) in self.swarm.get_connections() {
+            if let Err(e) = conn.webrtc_wait_for_data_channel_open().await {
+                tracing::warn!("Connection to {} is not opened: {:?}", did, e);
+            }
+        }
+
+        self.swarm.rejoin(&state).await;
+        Ok(())
+    }
+
+    /// Leave the ring gracefully before exiting. Neighbours are notified, local storage is
+    /// handed over to successor, and all connections are closed. See [Swarm::leave].
+    pub async fn shutdown(&self) -> Result<()> {
+        let timeout = Duration::from_millis(SHUTDOWN_HANDOVER_TIMEOUT_MS);
+        let count = self.swarm.leave(timeout).await.map_err(Error::Swarm)?;
+        tracing::info!("Left the ring, handed over {} virtual nodes", count);
+        Ok(())
+    }
+
     /// Send custom message to a did.
     pub async fn send_message(&self, destination: Did, msg: &[u8]) -> Result<uuid::Uuid> {
         tracing::info!(
@@ -334,6 +613,51 @@ impl Processor {
             .map_err(Error::SendMessage)
     }
 
+    /// Send custom message to a did, the message is encrypted to the session of destination.
+    /// Only the destination can decrypt it, relays on the path can only see the ciphertext.
+    pub async fn send_encrypted_message(&self, destination: Did, msg: &[u8]) -> Result<uuid::Uuid> {
+        tracing::info!(
+            "send_encrypted_message, destination: {}, message size: {:?}",
+            destination,
+            msg.len(),
+        );
+
+        self.swarm
+            .send_encrypted_message(msg, destination)
+            .await
+            .map_err(Error::SendMessage)
+    }
+
+    /// Send custom message to a did, and wait for the reply which carries the same tx_id.
+    pub async fn request(&self, destination: Did, msg: &[u8], timeout: Duration) -> Result<Bytes> {
+        tracing::info!(
+            "request, destination: {}, message size: {:?}",
+            destination,
+            msg.len(),
+        );
+
+        self.swarm
+            .request(destination, msg, timeout)
+            .await
+            .map(Bytes::from)
+            .map_err(Error::SendMessage)
+    }
+
+    /// Reply custom message to the request in ctx.
+    /// This is a helper for [rings_core::swarm::callback::SwarmCallback::on_inbound] handlers.
+    pub async fn reply(&self, ctx: &MessagePayload, msg: &[u8]) -> Result<()> {
+        self.swarm.reply(ctx, msg).await.map_err(Error::SendMessage)
+    }
+
+    /// Trace the route of messages to a did.
+    pub async fn trace_route(&self, destination: Did, timeout: Duration) -> Result<TraceRoute> {
+        tracing::info!("trace_route, destination: {}", destination);
+        self.swarm
+            .trace_route(destination, timeout)
+            .await
+            .map_err(Error::Swarm)
+    }
+
     /// Send custom message to a did.
     pub async fn send_backend_message(
         &self,
@@ -363,17 +687,106 @@ impl Processor {
             .map_err(Error::VNodeError)
     }
 
-    /// append data to a virtual node on DHT
-    pub async fn storage_append_data(&self, topic: &str, data: Encoded) -> Result<()> {
-        <Swarm as ChordStorageInterface<DATA_REDUNDANT>>::storage_append_data(
+    /// store virtual node on DHT if the version of stored one is `expected`
+    pub async fn storage_compare_and_swap(
+        &self,
+        vnode: vnode::VirtualNode,
+        expected: u64,
+    ) -> Result<()> {
+        <Swarm as ChordStorageInterface<DATA_REDUNDANT>>::storage_compare_and_swap(
             &self.swarm,
-            topic,
-            data,
+            vnode,
+            expected,
         )
         .await
         .map_err(Error::VNodeError)
     }
 
+    /// append data to a virtual node on DHT, the virtual node expires after `ttl_ms` if given
+    pub async fn storage_append_data(
+        &self,
+        topic: &str,
+        data: Encoded,
+        ttl_ms: Option<u64>,
+    ) -> Result<()> {
+        let mut vnode: vnode::VirtualNode = (topic.to_string(), data)
+            .try_into()
+            .map_err(Error::VNodeError)?;
+        if let Some(ttl_ms) = ttl_ms {
+            vnode = vnode.with_ttl(ttl_ms);
+        }
+        <Swarm as ChordStorageInterface<DATA_REDUNDANT>>::storage_extend(&self.swarm, vnode)
+            .await
+            .map_err(Error::VNodeError)
+    }
+
+    /// subscribe a topic, new data of the topic will be pushed to this node
+    pub async fn storage_subscribe(&self, topic: &str) -> Result<()> {
+        <Swarm as ChordStorageInterface<DATA_REDUNDANT>>::storage_subscribe(&self.swarm, topic)
+            .await
+            .map_err(Error::VNodeError)
+    }
+
+    /// unsubscribe a topic
+    pub async fn storage_unsubscribe(&self, topic: &str) -> Result<()> {
+        <Swarm as ChordStorageInterface<DATA_REDUNDANT>>::storage_unsubscribe(&self.swarm, topic)
+            .await
+            .map_err(Error::VNodeError)
+    }
+
+    /// join a subring
+    pub async fn subring_join(&self, name: &str) -> Result<()> {
+        <Swarm as SubringInterface<DATA_REDUNDANT>>::subring_join(&self.swarm, name)
+            .await
+            .map_err(Error::VNodeError)
+    }
+
+    /// leave a subring
+    pub async fn subring_leave(&self, name: &str) -> Result<()> {
+        <Swarm as SubringInterface<DATA_REDUNDANT>>::subring_leave(&self.swarm, name)
+            .await
+            .map_err(Error::VNodeError)
+    }
+
+    /// list members of a subring
+    pub async fn subring_members(&self, name: &str) -> Result<Vec<Did>> {
+        <Swarm as SubringInterface<DATA_REDUNDANT>>::subring_members(&self.swarm, name)
+            .await
+            .map_err(Error::VNodeError)
+    }
+
+    /// send a custom message to all members of a subring except this node,
+    /// returns the members the message was sent to
+    pub async fn subring_broadcast(&self, name: &str, msg: &[u8]) -> Result<Vec<Did>> {
+        <Swarm as SubringInterface<DATA_REDUNDANT>>::subring_broadcast(&self.swarm, name, msg)
+            .await
+            .map_err(Error::SendMessage)
+    }
+
+    /// sync local view of a subring, which is required to route messages within it
+    pub async fn subring_sync(&self, name: &str) -> Result<()> {
+        <Swarm as SubringInterface<DATA_REDUNDANT>>::subring_sync(&self.swarm, name)
+            .await
+            .map_err(Error::VNodeError)
+    }
+
+    /// send custom message to destination, relayed only by members of a subring
+    pub async fn send_message_within_subring(
+        &self,
+        subring: &str,
+        destination: Did,
+        msg: &[u8],
+    ) -> Result<uuid::Uuid> {
+        <Swarm as SubringInterface<DATA_REDUNDANT>>::subring_send_message(
+            &self.swarm,
+            subring,
+            msg,
+            destination,
+        )
+        .await
+        .map_err(Error::SendMessage)
+    }
+
     /// register service
     pub async fn register_service(&self, name: &str) -> Result<()> {
         let encoded_did = self
@@ -6,11 +6,13 @@ use std::future::Future;
 use std::pin::Pin;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use js_sys;
 use js_sys::Uint8Array;
 use rings_core::dht::Did;
 use rings_core::ecc::PublicKey;
+use rings_core::message::MessagePayload;
 use rings_core::prelude::vnode;
 use rings_core::prelude::vnode::VirtualNode;
 use rings_core::storage::idb::IdbStorage;
@@ -33,6 +35,7 @@ use crate::backend::types::BackendMessage;
 use crate::backend::types::HttpRequest;
 use crate::backend::types::ServiceMessage;
 use crate::backend::Backend;
+use crate::consts::CUSTOM_REQUEST_TIMEOUT_MS;
 use crate::processor::ProcessorConfig;
 use crate::provider::AsyncSigner;
 use crate::provider::Provider;
@@ -337,6 +340,63 @@ impl Provider {
         })
     }
 
+    /// send custom message to peer, the message is encrypted to the session of peer,
+    /// so that only the peer can read it.
+    pub fn send_encrypted_message(
+        &self,
+        destination: String,
+        msg: js_sys::Uint8Array,
+    ) -> js_sys::Promise {
+        let p = self.processor.clone();
+        future_to_promise(async move {
+            let destination_did = get_did(destination.as_str(), AddressType::DEFAULT)?;
+            p.send_encrypted_message(destination_did, &msg.to_vec())
+                .await
+                .map_err(JsError::from)?;
+            Ok(JsValue::from_bool(true))
+        })
+    }
+
+    /// send custom message to peer, and wait for the reply.
+    /// - destination: A did of destination
+    /// - msg: The request data
+    /// - timeout_ms: Timeout of waiting for reply, default is 30 seconds
+    pub fn send_request(
+        &self,
+        destination: String,
+        msg: js_sys::Uint8Array,
+        timeout_ms: Option<u32>,
+    ) -> js_sys::Promise {
+        let p = self.processor.clone();
+        future_to_promise(async move {
+            let destination_did = get_did(destination.as_str(), AddressType::DEFAULT)?;
+            let timeout_ms = timeout_ms
+                .map(u64::from)
+                .unwrap_or(CUSTOM_REQUEST_TIMEOUT_MS);
+            let reply = p
+                .request(
+                    destination_did,
+                    &msg.to_vec(),
+                    Duration::from_millis(timeout_ms),
+                )
+                .await
+                .map_err(JsError::from)?;
+            Ok(JsValue::from(Uint8Array::from(&reply[..])))
+        })
+    }
+
+    /// reply custom message to the request.
+    /// - ctx: The payload of request, which is passed to message handlers
+    /// - msg: The reply data
+    pub fn reply_message(&self, ctx: JsValue, msg: js_sys::Uint8Array) -> js_sys::Promise {
+        let p = self.processor.clone();
+        future_to_promise(async move {
+            let ctx: MessagePayload = js_value::deserialize(ctx).map_err(JsError::from)?;
+            p.reply(&ctx, &msg.to_vec()).await.map_err(JsError::from)?;
+            Ok(JsValue::from_bool(true))
+        })
+    }
+
     /// get peer by address
     pub fn get_peer(&self, address: String, addr_type: Option<AddressType>) -> js_sys::Promise {
         let p = self.processor.clone();
@@ -420,11 +480,14 @@ impl Provider {
         })
     }
 
-    /// store virtual node on DHT
-    pub fn storage_store(&self, data: String) -> js_sys::Promise {
+    /// store virtual node on DHT, the virtual node expires after `ttl_ms` if given
+    pub fn storage_store(&self, data: String, ttl_ms: Option<u32>) -> js_sys::Promise {
         let p = self.processor.clone();
         future_to_promise(async move {
-            let vnode_info = vnode::VirtualNode::try_from(data).map_err(JsError::from)?;
+            let mut vnode_info = vnode::VirtualNode::try_from(data).map_err(JsError::from)?;
+            if let Some(ttl_ms) = ttl_ms {
+                vnode_info = vnode_info.with_ttl(ttl_ms as u64);
+            }
             p.storage_store(vnode_info).await.map_err(JsError::from)?;
             Ok(JsValue::null())
         })
@@ -7,6 +7,7 @@
 
 use std::collections::HashSet;
 use std::str::FromStr;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use futures::future::join_all;
@@ -20,10 +21,16 @@ use rings_core::message::Encoder;
 use rings_core::message::MessagePayload;
 use rings_core::prelude::vnode::VirtualNode;
 use rings_core::swarm::impls::ConnectionHandshake;
+use rings_core::swarm::Swarm;
 use rings_rpc::protos::rings_node::*;
 use rings_rpc::protos::rings_node_handler::HandleRpc;
 use rings_transport::core::transport::ConnectionInterface;
+use rings_transport::core::transport::ConnectionKind;
 
+use crate::consts::CUSTOM_REQUEST_MAX_TIMEOUT_MS;
+use crate::consts::CUSTOM_REQUEST_TIMEOUT_MS;
+use crate::consts::TRACE_ROUTE_MAX_TIMEOUT_MS;
+use crate::consts::TRACE_ROUTE_TIMEOUT_MS;
 use crate::error::Error as ServerError;
 use crate::processor::Processor;
 use crate::seed::Seed;
@@ -43,7 +50,10 @@ impl HandleRpc<ConnectPeerViaHttpRequest, ConnectPeerViaHttpResponse> for Proces
             .map_err(|e| ServerError::RemoteRpcError(e.to_string()))?
             .did;
 
-        let offer = self.handle_rpc(CreateOfferRequest { did }).await?.offer;
+        let offer = self
+            .handle_rpc(CreateOfferRequest { did: did.clone() })
+            .await?
+            .offer;
 
         let answer = client
             .answer_offer(&AnswerOfferRequest { offer })
@@ -52,6 +62,7 @@ impl HandleRpc<ConnectPeerViaHttpRequest, ConnectPeerViaHttpResponse> for Proces
             .answer;
 
         let peer = self.handle_rpc(AcceptAnswerRequest { answer }).await?.peer;
+        self.swarm.record_peer_endpoint(s2d(&did)?, req.url);
 
         Ok(ConnectPeerViaHttpResponse { peer })
     }
@@ -62,6 +73,14 @@ impl HandleRpc<ConnectPeerViaHttpRequest, ConnectPeerViaHttpResponse> for Proces
 impl HandleRpc<ConnectWithDidRequest, ConnectWithDidResponse> for Processor {
     async fn handle_rpc(&self, req: ConnectWithDidRequest) -> Result<ConnectWithDidResponse> {
         let did = s2d(&req.did)?;
+        if let Some(websocket) = req.websocket {
+            let kind = if websocket {
+                ConnectionKind::Websocket
+            } else {
+                ConnectionKind::Webrtc
+            };
+            self.swarm.set_connection_kind(did, kind);
+        }
         self.connect_with_did(did, true)
             .await
             .map_err(Error::from)?;
@@ -104,7 +123,13 @@ impl HandleRpc<ConnectWithSeedRequest, ConnectWithSeedResponse> for Processor {
 #[cfg_attr(not(feature = "browser"), async_trait)]
 impl HandleRpc<ListPeersRequest, ListPeersResponse> for Processor {
     async fn handle_rpc(&self, _req: ListPeersRequest) -> Result<ListPeersResponse> {
-        let peers = self.swarm.get_connections().into_iter().map(dc2p).collect();
+        let peers = join_all(
+            self.swarm
+                .get_connections()
+                .into_iter()
+                .map(|dc| dc2p(&self.swarm, dc)),
+        )
+        .await;
         Ok(ListPeersResponse { peers })
     }
 }
@@ -180,7 +205,7 @@ impl HandleRpc<AcceptAnswerRequest, AcceptAnswerResponse> for Processor {
             .map_err(ServerError::AcceptAnswer)?;
 
         Ok(AcceptAnswerResponse {
-            peer: Some(dc2p(dc)),
+            peer: Some(dc2p(&self.swarm, dc).await),
         })
     }
 }
@@ -202,7 +227,23 @@ impl HandleRpc<SendCustomMessageRequest, SendCustomMessageResponse> for Processo
         let destination = s2d(&req.destination_did)?;
         let data = base64::decode(req.data)
             .map_err(|_| Error::invalid_params("Base64 decode data failed"))?;
-        self.send_message(destination, &data).await?;
+        match (req.encrypted.unwrap_or(false), req.subring) {
+            (true, Some(_)) => {
+                return Err(Error::invalid_params(
+                    "Encrypted message cannot be routed within subring",
+                ));
+            }
+            (true, None) => {
+                self.send_encrypted_message(destination, &data).await?;
+            }
+            (false, Some(subring)) => {
+                self.send_message_within_subring(&subring, destination, &data)
+                    .await?;
+            }
+            (false, None) => {
+                self.send_message(destination, &data).await?;
+            }
+        }
         Ok(SendCustomMessageResponse {})
     }
 }
@@ -222,6 +263,26 @@ impl HandleRpc<SendBackendMessageRequest, SendBackendMessageResponse> for Proces
     }
 }
 
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+#[cfg_attr(not(feature = "browser"), async_trait)]
+impl HandleRpc<SendCustomRequestRequest, SendCustomRequestResponse> for Processor {
+    async fn handle_rpc(&self, req: SendCustomRequestRequest) -> Result<SendCustomRequestResponse> {
+        let destination = s2d(&req.destination_did)?;
+        let data = base64::decode(req.data)
+            .map_err(|_| Error::invalid_params("Base64 decode data failed"))?;
+        let timeout_ms = match req.timeout_ms {
+            0 => CUSTOM_REQUEST_TIMEOUT_MS,
+            t => t.min(CUSTOM_REQUEST_MAX_TIMEOUT_MS),
+        };
+        let reply = self
+            .request(destination, &data, Duration::from_millis(timeout_ms))
+            .await?;
+        Ok(SendCustomRequestResponse {
+            data: base64::encode(reply),
+        })
+    }
+}
+
 #[cfg_attr(feature = "browser", async_trait(?Send))]
 #[cfg_attr(not(feature = "browser"), async_trait)]
 impl HandleRpc<PublishMessageToTopicRequest, PublishMessageToTopicResponse> for Processor {
@@ -233,7 +294,8 @@ impl HandleRpc<PublishMessageToTopicRequest, PublishMessageToTopicResponse> for
             .data
             .encode()
             .map_err(|e| Error::invalid_params(format!("Failed to encode data: {e:?}")))?;
-        self.storage_append_data(&req.topic, encoded).await?;
+        self.storage_append_data(&req.topic, encoded, req.ttl_ms)
+            .await?;
         Ok(PublishMessageToTopicResponse {})
     }
 }
@@ -267,6 +329,24 @@ impl HandleRpc<FetchTopicMessagesRequest, FetchTopicMessagesResponse> for Proces
     }
 }
 
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+#[cfg_attr(not(feature = "browser"), async_trait)]
+impl HandleRpc<SubscribeTopicRequest, SubscribeTopicResponse> for Processor {
+    async fn handle_rpc(&self, req: SubscribeTopicRequest) -> Result<SubscribeTopicResponse> {
+        self.storage_subscribe(&req.topic).await?;
+        Ok(SubscribeTopicResponse {})
+    }
+}
+
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+#[cfg_attr(not(feature = "browser"), async_trait)]
+impl HandleRpc<UnsubscribeTopicRequest, UnsubscribeTopicResponse> for Processor {
+    async fn handle_rpc(&self, req: UnsubscribeTopicRequest) -> Result<UnsubscribeTopicResponse> {
+        self.storage_unsubscribe(&req.topic).await?;
+        Ok(UnsubscribeTopicResponse {})
+    }
+}
+
 #[cfg_attr(feature = "browser", async_trait(?Send))]
 #[cfg_attr(not(feature = "browser"), async_trait)]
 impl HandleRpc<RegisterServiceRequest, RegisterServiceResponse> for Processor {
@@ -301,6 +381,63 @@ impl HandleRpc<LookupServiceRequest, LookupServiceResponse> for Processor {
     }
 }
 
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+#[cfg_attr(not(feature = "browser"), async_trait)]
+impl HandleRpc<JoinSubringRequest, JoinSubringResponse> for Processor {
+    async fn handle_rpc(&self, req: JoinSubringRequest) -> Result<JoinSubringResponse> {
+        self.subring_join(&req.name).await?;
+        Ok(JoinSubringResponse {})
+    }
+}
+
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+#[cfg_attr(not(feature = "browser"), async_trait)]
+impl HandleRpc<LeaveSubringRequest, LeaveSubringResponse> for Processor {
+    async fn handle_rpc(&self, req: LeaveSubringRequest) -> Result<LeaveSubringResponse> {
+        self.subring_leave(&req.name).await?;
+        Ok(LeaveSubringResponse {})
+    }
+}
+
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+#[cfg_attr(not(feature = "browser"), async_trait)]
+impl HandleRpc<SyncSubringRequest, SyncSubringResponse> for Processor {
+    async fn handle_rpc(&self, req: SyncSubringRequest) -> Result<SyncSubringResponse> {
+        self.subring_sync(&req.name).await?;
+        Ok(SyncSubringResponse {})
+    }
+}
+
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+#[cfg_attr(not(feature = "browser"), async_trait)]
+impl HandleRpc<ListSubringMembersRequest, ListSubringMembersResponse> for Processor {
+    async fn handle_rpc(
+        &self,
+        req: ListSubringMembersRequest,
+    ) -> Result<ListSubringMembersResponse> {
+        let members = self.subring_members(&req.name).await?;
+        Ok(ListSubringMembersResponse {
+            members: members.iter().map(|did| did.to_string()).collect(),
+        })
+    }
+}
+
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+#[cfg_attr(not(feature = "browser"), async_trait)]
+impl HandleRpc<BroadcastSubringMessageRequest, BroadcastSubringMessageResponse> for Processor {
+    async fn handle_rpc(
+        &self,
+        req: BroadcastSubringMessageRequest,
+    ) -> Result<BroadcastSubringMessageResponse> {
+        let data = base64::decode(req.data)
+            .map_err(|_| Error::invalid_params("Base64 decode data failed"))?;
+        let members = self.subring_broadcast(&req.name, &data).await?;
+        Ok(BroadcastSubringMessageResponse {
+            members: members.iter().map(|did| did.to_string()).collect(),
+        })
+    }
+}
+
 #[cfg_attr(feature = "browser", async_trait(?Send))]
 #[cfg_attr(not(feature = "browser"), async_trait)]
 impl HandleRpc<NodeInfoRequest, NodeInfoResponse> for Processor {
@@ -322,11 +459,41 @@ impl HandleRpc<NodeDidRequest, NodeDidResponse> for Processor {
     }
 }
 
-/// Convert did and connection to Peer
-fn dc2p((did, conn): (Did, impl ConnectionInterface)) -> PeerInfo {
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+#[cfg_attr(not(feature = "browser"), async_trait)]
+impl HandleRpc<TraceRouteRequest, TraceRouteResponse> for Processor {
+    async fn handle_rpc(&self, req: TraceRouteRequest) -> Result<TraceRouteResponse> {
+        let destination = s2d(&req.did)?;
+        let timeout_ms = match req.timeout_ms {
+            0 => TRACE_ROUTE_TIMEOUT_MS,
+            t => t.min(TRACE_ROUTE_MAX_TIMEOUT_MS),
+        };
+        let route = self
+            .trace_route(destination, Duration::from_millis(timeout_ms))
+            .await?;
+        Ok(TraceRouteResponse {
+            reached: route.reached,
+            hops: route
+                .hops
+                .into_iter()
+                .map(|hop| TraceHop {
+                    did: hop.did.to_string(),
+                    received_at_ms: hop.received_at_ms.map(|t| t as u64),
+                    latency_ms: hop.latency_ms,
+                    rtt_ms: hop.rtt_ms.map(|t| t as u64),
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Convert did and connection to Peer, with the protocol declared by peer in swarm
+async fn dc2p(swarm: &Swarm, (did, conn): (Did, impl ConnectionInterface)) -> PeerInfo {
     PeerInfo {
         did: did.to_string(),
         state: format!("{:?}", conn.webrtc_connection_state()),
+        stats: Some(conn.get_stats().await.into()),
+        protocol_version: swarm.peer_protocol(did).map(|p| p.version),
     }
 }
 
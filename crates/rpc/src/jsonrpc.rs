@@ -172,6 +172,14 @@ impl Client {
         self.call_method(Method::SendBackendMessage, req).await
     }
 
+    /// Send custom message to destination and wait for the reply.
+    pub async fn send_custom_request(
+        &self,
+        req: &SendCustomRequestRequest,
+    ) -> Result<SendCustomRequestResponse> {
+        self.call_method(Method::SendCustomRequest, req).await
+    }
+
     /// Publishes a message to the specified topic.
     pub async fn publish_message_to_topic(
         &self,
@@ -187,6 +195,22 @@ impl Client {
         self.call_method(Method::FetchTopicMessages, req).await
     }
 
+    /// Subscribes the specified topic, new messages will be pushed to the node.
+    pub async fn subscribe_topic(
+        &self,
+        req: &SubscribeTopicRequest,
+    ) -> Result<SubscribeTopicResponse> {
+        self.call_method(Method::SubscribeTopic, req).await
+    }
+
+    /// Unsubscribes the specified topic.
+    pub async fn unsubscribe_topic(
+        &self,
+        req: &UnsubscribeTopicRequest,
+    ) -> Result<UnsubscribeTopicResponse> {
+        self.call_method(Method::UnsubscribeTopic, req).await
+    }
+
     /// Registers a new service with the given name.
     pub async fn register_service(
         &self,
@@ -203,6 +227,37 @@ impl Client {
         self.call_method(Method::LookupService, req).await
     }
 
+    /// Join a subring
+    pub async fn join_subring(&self, req: &JoinSubringRequest) -> Result<JoinSubringResponse> {
+        self.call_method(Method::JoinSubring, req).await
+    }
+
+    /// Leave a subring
+    pub async fn leave_subring(&self, req: &LeaveSubringRequest) -> Result<LeaveSubringResponse> {
+        self.call_method(Method::LeaveSubring, req).await
+    }
+
+    /// Sync local view of a subring to route messages within it
+    pub async fn sync_subring(&self, req: &SyncSubringRequest) -> Result<SyncSubringResponse> {
+        self.call_method(Method::SyncSubring, req).await
+    }
+
+    /// List members of a subring
+    pub async fn list_subring_members(
+        &self,
+        req: &ListSubringMembersRequest,
+    ) -> Result<ListSubringMembersResponse> {
+        self.call_method(Method::ListSubringMembers, req).await
+    }
+
+    /// Send a custom message to all members of a subring
+    pub async fn broadcast_subring_message(
+        &self,
+        req: &BroadcastSubringMessageRequest,
+    ) -> Result<BroadcastSubringMessageResponse> {
+        self.call_method(Method::BroadcastSubringMessage, req).await
+    }
+
     /// Query for swarm inspect info.
     pub async fn node_info(&self, req: &NodeInfoRequest) -> Result<NodeInfoResponse> {
         self.call_method(Method::NodeInfo, req).await
@@ -211,4 +266,9 @@ impl Client {
     pub async fn node_did(&self, req: &NodeDidRequest) -> Result<NodeDidResponse> {
         self.call_method(Method::NodeDid, req).await
     }
+
+    /// Trace the route of messages to a node.
+    pub async fn trace_route(&self, req: &TraceRouteRequest) -> Result<TraceRouteResponse> {
+        self.call_method(Method::TraceRoute, req).await
+    }
 }
@@ -28,18 +28,36 @@ pub enum Method {
     SendCustomMessage,
     /// SendBackendMessage
     SendBackendMessage,
+    /// Send custom message and wait for the reply
+    SendCustomRequest,
     /// Append data to topic
     PublishMessageToTopic,
     /// Fetch data of topic
     FetchTopicMessages,
+    /// Subscribe topic, new data of topic will be pushed to node
+    SubscribeTopic,
+    /// Unsubscribe topic
+    UnsubscribeTopic,
     /// Register service
     RegisterService,
     /// Lookup service
     LookupService,
+    /// Join a subring
+    JoinSubring,
+    /// Leave a subring
+    LeaveSubring,
+    /// Sync local view of a subring to route messages within it
+    SyncSubring,
+    /// List members of a subring
+    ListSubringMembers,
+    /// Send a custom message to all members of a subring
+    BroadcastSubringMessage,
     /// Retrieve Node info
     NodeInfo,
     /// Retrieve Node DID
     NodeDid,
+    /// Trace the route of messages to a node
+    TraceRoute,
 }
 
 impl Method {
@@ -56,12 +74,21 @@ impl Method {
             Method::AcceptAnswer => "acceptAnswer",
             Method::SendCustomMessage => "sendCustomMessage",
             Method::SendBackendMessage => "sendBackendMessage",
+            Method::SendCustomRequest => "sendCustomRequest",
             Method::PublishMessageToTopic => "publishMessageToTopic",
             Method::FetchTopicMessages => "fetchTopicMessages",
+            Method::SubscribeTopic => "subscribeTopic",
+            Method::UnsubscribeTopic => "unsubscribeTopic",
             Method::RegisterService => "registerService",
             Method::LookupService => "lookupService",
+            Method::JoinSubring => "joinSubring",
+            Method::LeaveSubring => "leaveSubring",
+            Method::SyncSubring => "syncSubring",
+            Method::ListSubringMembers => "listSubringMembers",
+            Method::BroadcastSubringMessage => "broadcastSubringMessage",
             Method::NodeInfo => "nodeInfo",
             Method::NodeDid => "nodeDid",
+            Method::TraceRoute => "traceRoute",
         }
     }
 }
@@ -86,13 +113,22 @@ impl TryFrom<&str> for Method {
             "disconnect" => Self::Disconnect,
             "acceptAnswer" => Self::AcceptAnswer,
             "sendBackendMessage" => Self::SendBackendMessage,
+            "sendCustomRequest" => Self::SendCustomRequest,
             "sendCustomMessage" => Self::SendCustomMessage,
             "publishMessageToTopic" => Method::PublishMessageToTopic,
             "fetchTopicMessages" => Method::FetchTopicMessages,
+            "subscribeTopic" => Method::SubscribeTopic,
+            "unsubscribeTopic" => Method::UnsubscribeTopic,
             "registerService" => Method::RegisterService,
             "lookupService" => Method::LookupService,
+            "joinSubring" => Method::JoinSubring,
+            "leaveSubring" => Method::LeaveSubring,
+            "syncSubring" => Method::SyncSubring,
+            "listSubringMembers" => Method::ListSubringMembers,
+            "broadcastSubringMessage" => Method::BroadcastSubringMessage,
             "nodeInfo" => Method::NodeInfo,
             "nodeDid" => Method::NodeDid,
+            "traceRoute" => Method::TraceRoute,
             _ => return Err(Error::InvalidMethod),
         })
     }
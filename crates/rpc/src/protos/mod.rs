@@ -1,19 +1,48 @@
 pub mod rings_node;
 pub mod rings_node_handler;
 
+use rings_core::inspect::ConnectionInspect;
 use rings_core::inspect::StorageInspect;
 use rings_core::inspect::SwarmInspect;
+use rings_core::prelude::rings_transport::core::transport::ConnectionStats;
+
+impl From<ConnectionStats> for rings_node::ConnectionStats {
+    fn from(stats: ConnectionStats) -> Self {
+        Self {
+            rtt_ms: stats.rtt_ms,
+            bytes_sent: stats.bytes_sent,
+            bytes_received: stats.bytes_received,
+            messages_sent: stats.messages_sent,
+            messages_received: stats.messages_received,
+            local_candidate_type: format!("{:?}", stats.local_candidate_type),
+            remote_candidate_type: format!("{:?}", stats.remote_candidate_type),
+            buffered_amount: stats.buffered_amount,
+            state_durations: stats
+                .state_durations
+                .into_iter()
+                .map(|(state, duration_ms)| rings_node::StateDuration {
+                    state: format!("{:?}", state),
+                    duration_ms,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<ConnectionInspect> for rings_node::PeerInfo {
+    fn from(conn: ConnectionInspect) -> Self {
+        Self {
+            did: conn.did,
+            state: conn.state,
+            stats: Some(conn.stats.into()),
+            protocol_version: conn.protocol.map(|p| p.version),
+        }
+    }
+}
 
 impl From<SwarmInspect> for rings_node::SwarmInfo {
     fn from(inspect: SwarmInspect) -> Self {
-        let peers = inspect
-            .connections
-            .into_iter()
-            .map(|conn| rings_node::PeerInfo {
-                did: conn.did,
-                state: conn.state,
-            })
-            .collect();
+        let peers = inspect.connections.into_iter().map(Into::into).collect();
 
         let dht = rings_node::DhtInfo {
             did: inspect.dht.did,
@@ -32,6 +61,10 @@ impl From<SwarmInspect> for rings_node::SwarmInfo {
             dht: Some(dht),
             persistence_storage: Some(inspect.persistence_storage.into()),
             cache_storage: Some(inspect.cache_storage.into()),
+            replays: Some(rings_node::ReplayInfo {
+                cached: inspect.replays.cached,
+                dropped: inspect.replays.dropped,
+            }),
         }
     }
 }
@@ -43,12 +43,21 @@ impl InternalRpcHandler {
             + HandleRpc<DisconnectRequest, DisconnectResponse>
             + HandleRpc<SendCustomMessageRequest, SendCustomMessageResponse>
             + HandleRpc<SendBackendMessageRequest, SendBackendMessageResponse>
+            + HandleRpc<SendCustomRequestRequest, SendCustomRequestResponse>
             + HandleRpc<PublishMessageToTopicRequest, PublishMessageToTopicResponse>
             + HandleRpc<FetchTopicMessagesRequest, FetchTopicMessagesResponse>
+            + HandleRpc<SubscribeTopicRequest, SubscribeTopicResponse>
+            + HandleRpc<UnsubscribeTopicRequest, UnsubscribeTopicResponse>
             + HandleRpc<RegisterServiceRequest, RegisterServiceResponse>
             + HandleRpc<LookupServiceRequest, LookupServiceResponse>
+            + HandleRpc<JoinSubringRequest, JoinSubringResponse>
+            + HandleRpc<LeaveSubringRequest, LeaveSubringResponse>
+            + HandleRpc<SyncSubringRequest, SyncSubringResponse>
+            + HandleRpc<ListSubringMembersRequest, ListSubringMembersResponse>
+            + HandleRpc<BroadcastSubringMessageRequest, BroadcastSubringMessageResponse>
             + HandleRpc<NodeInfoRequest, NodeInfoResponse>
-            + HandleRpc<NodeDidRequest, NodeDidResponse>,
+            + HandleRpc<NodeDidRequest, NodeDidResponse>
+            + HandleRpc<TraceRouteRequest, TraceRouteResponse>,
     {
         let method = Method::try_from(method.as_str()).map_err(|_| Error {
             code: ErrorCode::MethodNotFound,
@@ -117,6 +126,12 @@ impl InternalRpcHandler {
                 let resp = processor.handle_rpc(req).await?;
                 serde_json::to_value(resp).map_err(|_| Error::new(ErrorCode::ParseError))
             }
+            Method::SendCustomRequest => {
+                let req = serde_json::from_value::<SendCustomRequestRequest>(params)
+                    .map_err(|e| Error::invalid_params(e.to_string()))?;
+                let resp = processor.handle_rpc(req).await?;
+                serde_json::to_value(resp).map_err(|_| Error::new(ErrorCode::ParseError))
+            }
             Method::PublishMessageToTopic => {
                 let req = serde_json::from_value::<PublishMessageToTopicRequest>(params)
                     .map_err(|e| Error::invalid_params(e.to_string()))?;
@@ -129,6 +144,18 @@ impl InternalRpcHandler {
                 let resp = processor.handle_rpc(req).await?;
                 serde_json::to_value(resp).map_err(|_| Error::new(ErrorCode::ParseError))
             }
+            Method::SubscribeTopic => {
+                let req = serde_json::from_value::<SubscribeTopicRequest>(params)
+                    .map_err(|e| Error::invalid_params(e.to_string()))?;
+                let resp = processor.handle_rpc(req).await?;
+                serde_json::to_value(resp).map_err(|_| Error::new(ErrorCode::ParseError))
+            }
+            Method::UnsubscribeTopic => {
+                let req = serde_json::from_value::<UnsubscribeTopicRequest>(params)
+                    .map_err(|e| Error::invalid_params(e.to_string()))?;
+                let resp = processor.handle_rpc(req).await?;
+                serde_json::to_value(resp).map_err(|_| Error::new(ErrorCode::ParseError))
+            }
             Method::RegisterService => {
                 let req = serde_json::from_value::<RegisterServiceRequest>(params)
                     .map_err(|e| Error::invalid_params(e.to_string()))?;
@@ -141,6 +168,36 @@ impl InternalRpcHandler {
                 let resp = processor.handle_rpc(req).await?;
                 serde_json::to_value(resp).map_err(|_| Error::new(ErrorCode::ParseError))
             }
+            Method::JoinSubring => {
+                let req = serde_json::from_value::<JoinSubringRequest>(params)
+                    .map_err(|e| Error::invalid_params(e.to_string()))?;
+                let resp = processor.handle_rpc(req).await?;
+                serde_json::to_value(resp).map_err(|_| Error::new(ErrorCode::ParseError))
+            }
+            Method::LeaveSubring => {
+                let req = serde_json::from_value::<LeaveSubringRequest>(params)
+                    .map_err(|e| Error::invalid_params(e.to_string()))?;
+                let resp = processor.handle_rpc(req).await?;
+                serde_json::to_value(resp).map_err(|_| Error::new(ErrorCode::ParseError))
+            }
+            Method::SyncSubring => {
+                let req = serde_json::from_value::<SyncSubringRequest>(params)
+                    .map_err(|e| Error::invalid_params(e.to_string()))?;
+                let resp = processor.handle_rpc(req).await?;
+                serde_json::to_value(resp).map_err(|_| Error::new(ErrorCode::ParseError))
+            }
+            Method::ListSubringMembers => {
+                let req = serde_json::from_value::<ListSubringMembersRequest>(params)
+                    .map_err(|e| Error::invalid_params(e.to_string()))?;
+                let resp = processor.handle_rpc(req).await?;
+                serde_json::to_value(resp).map_err(|_| Error::new(ErrorCode::ParseError))
+            }
+            Method::BroadcastSubringMessage => {
+                let req = serde_json::from_value::<BroadcastSubringMessageRequest>(params)
+                    .map_err(|e| Error::invalid_params(e.to_string()))?;
+                let resp = processor.handle_rpc(req).await?;
+                serde_json::to_value(resp).map_err(|_| Error::new(ErrorCode::ParseError))
+            }
             Method::NodeInfo => {
                 let req = serde_json::from_value::<NodeInfoRequest>(params)
                     .map_err(|e| Error::invalid_params(e.to_string()))?;
@@ -153,6 +210,12 @@ impl InternalRpcHandler {
                 let resp = processor.handle_rpc(req).await?;
                 serde_json::to_value(resp).map_err(|_| Error::new(ErrorCode::ParseError))
             }
+            Method::TraceRoute => {
+                let req = serde_json::from_value::<TraceRouteRequest>(params)
+                    .map_err(|e| Error::invalid_params(e.to_string()))?;
+                let resp = processor.handle_rpc(req).await?;
+                serde_json::to_value(resp).map_err(|_| Error::new(ErrorCode::ParseError))
+            }
         }
     }
 }
@@ -1,5 +1,7 @@
 //! This module contains the [InnerTransportCallback] struct.
 
+use std::sync::Mutex;
+
 use bytes::Bytes;
 
 use crate::core::callback::BoxedTransportCallback;
@@ -13,6 +15,53 @@ pub struct InnerTransportCallback {
     pub cid: String,
     callback: BoxedTransportCallback,
     data_channel_open_notifier: Notifier,
+    state_durations: Mutex<StateDurations>,
+}
+
+/// Accumulates how long a connection stays in each [WebrtcConnectionState].
+struct StateDurations {
+    state: WebrtcConnectionState,
+    since_ms: i64,
+    durations: Vec<(WebrtcConnectionState, u64)>,
+}
+
+impl StateDurations {
+    fn new() -> Self {
+        Self {
+            state: WebrtcConnectionState::New,
+            since_ms: chrono::Utc::now().timestamp_millis(),
+            durations: vec![],
+        }
+    }
+
+    fn transit(&mut self, state: WebrtcConnectionState) {
+        if state == self.state {
+            return;
+        }
+        let now = chrono::Utc::now().timestamp_millis();
+        add_duration(&mut self.durations, self.state, now - self.since_ms);
+        self.state = state;
+        self.since_ms = now;
+    }
+
+    fn snapshot(&self) -> Vec<(WebrtcConnectionState, u64)> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut durations = self.durations.clone();
+        add_duration(&mut durations, self.state, now - self.since_ms);
+        durations
+    }
+}
+
+fn add_duration(
+    durations: &mut Vec<(WebrtcConnectionState, u64)>,
+    state: WebrtcConnectionState,
+    ms: i64,
+) {
+    let ms = ms.max(0) as u64;
+    match durations.iter_mut().find(|(s, _)| *s == state) {
+        Some((_, d)) => *d += ms,
+        None => durations.push((state, ms)),
+    }
 }
 
 impl InnerTransportCallback {
@@ -26,9 +75,19 @@ impl InnerTransportCallback {
             cid: cid.to_string(),
             callback,
             data_channel_open_notifier,
+            state_durations: Mutex::new(StateDurations::new()),
         }
     }
 
+    /// Milliseconds the connection spent in each state, see
+    /// [ConnectionStats::state_durations](crate::core::transport::ConnectionStats::state_durations).
+    pub fn state_durations(&self) -> Vec<(WebrtcConnectionState, u64)> {
+        self.state_durations
+            .lock()
+            .map(|d| d.snapshot())
+            .unwrap_or_default()
+    }
+
     /// Notify the data channel is open.
     pub fn on_data_channel_open(&self) {
         self.data_channel_open_notifier.set_result(true)
@@ -51,6 +110,10 @@ impl InnerTransportCallback {
 
     /// This method is invoked when the state of connection has changed.
     pub async fn on_peer_connection_state_change(&self, s: WebrtcConnectionState) {
+        if let Ok(mut d) = self.state_durations.lock() {
+            d.transit(s);
+        }
+
         if let Err(e) = self
             .callback
             .on_peer_connection_state_change(&self.cid, s)
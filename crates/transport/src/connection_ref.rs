@@ -8,6 +8,7 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 use crate::core::transport::ConnectionInterface;
+use crate::core::transport::ConnectionStats;
 use crate::core::transport::TransportMessage;
 use crate::core::transport::WebrtcConnectionState;
 use crate::error::Error;
@@ -67,9 +68,9 @@ where
             .unwrap_or(WebrtcConnectionState::Closed)
     }
 
-    async fn get_stats(&self) -> Vec<String> {
+    async fn get_stats(&self) -> ConnectionStats {
         let Ok(c) = self.upgrade() else {
-            return Vec::new();
+            return ConnectionStats::default();
         };
         c.get_stats().await
     }
@@ -115,9 +116,9 @@ where
             .unwrap_or(WebrtcConnectionState::Closed)
     }
 
-    async fn get_stats(&self) -> Vec<String> {
+    async fn get_stats(&self) -> ConnectionStats {
         let Ok(c) = self.upgrade() else {
-            return Vec::new();
+            return ConnectionStats::default();
         };
         c.get_stats().await
     }
@@ -1,3 +1,5 @@
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Duration;
@@ -12,6 +14,7 @@ use crate::callback::InnerTransportCallback;
 use crate::connection_ref::ConnectionRef;
 use crate::core::callback::BoxedTransportCallback;
 use crate::core::transport::ConnectionInterface;
+use crate::core::transport::ConnectionStats;
 use crate::core::transport::TransportInterface;
 use crate::core::transport::TransportMessage;
 use crate::core::transport::WebrtcConnectionState;
@@ -41,6 +44,10 @@ pub struct DummyConnection {
     pub(crate) rand_id: String,
     remote_rand_id: Arc<Mutex<Option<String>>>,
     webrtc_connection_state: Arc<Mutex<WebrtcConnectionState>>,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    messages_sent: AtomicU64,
+    messages_received: AtomicU64,
 }
 
 /// [DummyTransport] manages all the [DummyConnection] and
@@ -55,6 +62,10 @@ impl DummyConnection {
             rand_id: random(0, 10000000000).to_string(),
             remote_rand_id: Arc::new(Mutex::new(None)),
             webrtc_connection_state: Arc::new(Mutex::new(WebrtcConnectionState::New)),
+            bytes_sent: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            messages_sent: AtomicU64::new(0),
+            messages_received: AtomicU64::new(0),
         }
     }
 
@@ -114,6 +125,18 @@ impl ConnectionInterface for DummyConnection {
         }
         self.webrtc_wait_for_data_channel_open().await?;
         let data = bincode::serialize(&msg).map(Bytes::from)?;
+
+        let remote_conn = self.remote_conn();
+        self.bytes_sent
+            .fetch_add(data.len() as u64, Ordering::Relaxed);
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
+        remote_conn
+            .bytes_received
+            .fetch_add(data.len() as u64, Ordering::Relaxed);
+        remote_conn
+            .messages_received
+            .fetch_add(1, Ordering::Relaxed);
+
         self.remote_callback().on_message(&data).await;
         Ok(())
     }
@@ -122,8 +145,15 @@ impl ConnectionInterface for DummyConnection {
         *self.webrtc_connection_state.lock().unwrap()
     }
 
-    async fn get_stats(&self) -> Vec<String> {
-        Vec::new()
+    async fn get_stats(&self) -> ConnectionStats {
+        ConnectionStats {
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            messages_sent: self.messages_sent.load(Ordering::Relaxed),
+            messages_received: self.messages_received.load(Ordering::Relaxed),
+            state_durations: self.callback().state_durations(),
+            ..Default::default()
+        }
     }
 
     async fn webrtc_create_offer(&self) -> Result<Self::Sdp> {
@@ -0,0 +1,236 @@
+//! A connection that can be either a [WebrtcConnection] or a [WebsocketConnection].
+//!
+//! The kind of a [HybridConnection] is decided on handshake. The offering side uses the kind set
+//! by [TransportInterface::set_connection_kind] for the peer, webrtc by default, and the
+//! answering side follows the kind of offer. So a single transport can mix both kinds.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+
+use crate::connection_ref::ConnectionRef;
+use crate::connections::websocket::WebsocketSdp;
+use crate::connections::WebrtcConnection;
+use crate::connections::WebrtcTransport;
+use crate::connections::WebsocketConnection;
+use crate::connections::WebsocketTransport;
+use crate::core::callback::BoxedTransportCallback;
+use crate::core::transport::ConnectionInterface;
+use crate::core::transport::ConnectionKind;
+use crate::core::transport::ConnectionStats;
+use crate::core::transport::TransportInterface;
+use crate::core::transport::TransportMessage;
+use crate::core::transport::WebrtcConnectionState;
+use crate::error::Error;
+use crate::error::Result;
+use crate::pool::Pool;
+
+enum HybridInner {
+    Webrtc(WebrtcConnection),
+    Websocket(WebsocketConnection),
+}
+
+/// A connection built on webrtc or websocket, see the module document.
+pub struct HybridConnection {
+    cid: String,
+    kind: ConnectionKind,
+    callback: Mutex<Option<BoxedTransportCallback>>,
+    webrtc: Arc<WebrtcTransport>,
+    websocket: Arc<WebsocketTransport>,
+    inner: OnceLock<HybridInner>,
+}
+
+/// [HybridTransport] manages all the [HybridConnection] and
+/// provides methods to create, get and close connections.
+pub struct HybridTransport {
+    webrtc: Arc<WebrtcTransport>,
+    websocket: Arc<WebsocketTransport>,
+    kinds: DashMap<String, ConnectionKind>,
+    pool: Pool<HybridConnection>,
+}
+
+impl HybridConnection {
+    /// The kind of connection, or `None` if handshake is not started.
+    pub fn kind(&self) -> Option<ConnectionKind> {
+        self.inner.get().map(|inner| match inner {
+            HybridInner::Webrtc(_) => ConnectionKind::Webrtc,
+            HybridInner::Websocket(_) => ConnectionKind::Websocket,
+        })
+    }
+
+    fn inner(&self) -> Result<&HybridInner> {
+        self.inner
+            .get()
+            .ok_or_else(|| Error::HandshakeNotStarted(self.cid.clone()))
+    }
+
+    /// Get the inner connection, or build it with given kind if handshake is not started.
+    async fn inner_or_build(&self, kind: ConnectionKind) -> Result<&HybridInner> {
+        if let Some(inner) = self.inner.get() {
+            return Ok(inner);
+        }
+
+        let callback = self
+            .callback
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or_else(|| Error::ConnectionAlreadyExists(self.cid.clone()))?;
+
+        let inner = match kind {
+            ConnectionKind::Webrtc => {
+                HybridInner::Webrtc(self.webrtc.build_connection(&self.cid, callback).await?)
+            }
+            ConnectionKind::Websocket => {
+                HybridInner::Websocket(self.websocket.build_connection(&self.cid, callback))
+            }
+        };
+
+        if self.inner.set(inner).is_err() {
+            return Err(Error::ConnectionAlreadyExists(self.cid.clone()));
+        }
+        self.inner()
+    }
+}
+
+impl HybridTransport {
+    /// Create a new [HybridTransport] instance.
+    /// See [WebsocketTransport::new] for the usage of `websocket_listener`.
+    pub fn new(
+        ice_servers: &str,
+        external_address: Option<String>,
+        websocket_listener: Option<(SocketAddr, String)>,
+    ) -> Self {
+        Self {
+            webrtc: Arc::new(WebrtcTransport::new(ice_servers, external_address)),
+            websocket: Arc::new(WebsocketTransport::new(websocket_listener)),
+            kinds: DashMap::new(),
+            pool: Pool::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl ConnectionInterface for HybridConnection {
+    type Sdp = String;
+    type Error = Error;
+
+    async fn send_message(&self, msg: TransportMessage) -> Result<()> {
+        match self.inner()? {
+            HybridInner::Webrtc(c) => c.send_message(msg).await,
+            HybridInner::Websocket(c) => c.send_message(msg).await,
+        }
+    }
+
+    fn webrtc_connection_state(&self) -> WebrtcConnectionState {
+        match self.inner.get() {
+            Some(HybridInner::Webrtc(c)) => c.webrtc_connection_state(),
+            Some(HybridInner::Websocket(c)) => c.webrtc_connection_state(),
+            None => WebrtcConnectionState::New,
+        }
+    }
+
+    async fn get_stats(&self) -> ConnectionStats {
+        match self.inner.get() {
+            Some(HybridInner::Webrtc(c)) => c.get_stats().await,
+            Some(HybridInner::Websocket(c)) => c.get_stats().await,
+            None => ConnectionStats::default(),
+        }
+    }
+
+    async fn webrtc_create_offer(&self) -> Result<Self::Sdp> {
+        match self.inner_or_build(self.kind).await? {
+            HybridInner::Webrtc(c) => c.webrtc_create_offer().await,
+            HybridInner::Websocket(c) => c.webrtc_create_offer().await,
+        }
+    }
+
+    async fn webrtc_answer_offer(&self, offer: Self::Sdp) -> Result<Self::Sdp> {
+        let kind = match WebsocketSdp::from_sdp(&offer) {
+            Ok(_) => ConnectionKind::Websocket,
+            Err(_) => ConnectionKind::Webrtc,
+        };
+
+        match self.inner_or_build(kind).await? {
+            HybridInner::Webrtc(c) => c.webrtc_answer_offer(offer).await,
+            HybridInner::Websocket(c) => c.webrtc_answer_offer(offer).await,
+        }
+    }
+
+    async fn webrtc_accept_answer(&self, answer: Self::Sdp) -> Result<()> {
+        match self.inner()? {
+            HybridInner::Webrtc(c) => c.webrtc_accept_answer(answer).await,
+            HybridInner::Websocket(c) => c.webrtc_accept_answer(answer).await,
+        }
+    }
+
+    async fn webrtc_wait_for_data_channel_open(&self) -> Result<()> {
+        match self.inner()? {
+            HybridInner::Webrtc(c) => c.webrtc_wait_for_data_channel_open().await,
+            HybridInner::Websocket(c) => c.webrtc_wait_for_data_channel_open().await,
+        }
+    }
+
+    async fn close(&self) -> Result<()> {
+        match self.inner.get() {
+            Some(HybridInner::Webrtc(c)) => c.close().await,
+            Some(HybridInner::Websocket(c)) => c.close().await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl TransportInterface for HybridTransport {
+    type Connection = HybridConnection;
+    type Error = Error;
+
+    async fn new_connection(&self, cid: &str, callback: BoxedTransportCallback) -> Result<()> {
+        if let Ok(existed_conn) = self.pool.connection(cid) {
+            if matches!(
+                existed_conn.webrtc_connection_state(),
+                WebrtcConnectionState::New
+                    | WebrtcConnectionState::Connecting
+                    | WebrtcConnectionState::Connected
+            ) {
+                return Err(Error::ConnectionAlreadyExists(cid.to_string()));
+            }
+        }
+
+        let conn = HybridConnection {
+            cid: cid.to_string(),
+            kind: self.kinds.get(cid).map(|k| *k).unwrap_or_default(),
+            callback: Mutex::new(Some(callback)),
+            webrtc: self.webrtc.clone(),
+            websocket: self.websocket.clone(),
+            inner: OnceLock::new(),
+        };
+
+        self.pool.safely_insert(cid, conn)?;
+        Ok(())
+    }
+
+    async fn close_connection(&self, cid: &str) -> Result<()> {
+        self.pool.safely_remove(cid).await
+    }
+
+    fn connection(&self, cid: &str) -> Result<ConnectionRef<Self::Connection>> {
+        self.pool.connection(cid)
+    }
+
+    fn connections(&self) -> Vec<(String, ConnectionRef<Self::Connection>)> {
+        self.pool.connections()
+    }
+
+    fn connection_ids(&self) -> Vec<String> {
+        self.pool.connection_ids()
+    }
+
+    fn set_connection_kind(&self, cid: &str, kind: ConnectionKind) {
+        self.kinds.insert(cid.to_string(), kind);
+    }
+}
@@ -1,18 +1,28 @@
 //! Default using `WebrtcConnection` for native environment.
 //! Plus a `WebSysWebrtcConnection` for wasm environment.
 //! Also provide a `DummyConnection` for testing.
+//! With `websocket` feature, there is a `WebsocketConnection` for servers that can dial each
+//! other, and a `HybridConnection` that can be either of webrtc and websocket.
 
 #[cfg(feature = "dummy")]
 mod dummy;
+#[cfg(all(feature = "native-webrtc", feature = "websocket"))]
+mod hybrid;
 #[cfg(feature = "native-webrtc")]
 mod native_webrtc;
 #[cfg(feature = "web-sys-webrtc")]
 mod web_sys_webrtc;
+#[cfg(feature = "websocket")]
+mod websocket;
 
 #[cfg(feature = "dummy")]
 pub use crate::connections::dummy::DummyConnection;
 #[cfg(feature = "dummy")]
 pub use crate::connections::dummy::DummyTransport;
+#[cfg(all(feature = "native-webrtc", feature = "websocket"))]
+pub use crate::connections::hybrid::HybridConnection;
+#[cfg(all(feature = "native-webrtc", feature = "websocket"))]
+pub use crate::connections::hybrid::HybridTransport;
 #[cfg(feature = "native-webrtc")]
 pub use crate::connections::native_webrtc::WebrtcConnection;
 #[cfg(feature = "native-webrtc")]
@@ -21,3 +31,9 @@ pub use crate::connections::native_webrtc::WebrtcTransport;
 pub use crate::connections::web_sys_webrtc::WebSysWebrtcConnection;
 #[cfg(feature = "web-sys-webrtc")]
 pub use crate::connections::web_sys_webrtc::WebSysWebrtcTransport;
+#[cfg(feature = "websocket")]
+pub use crate::connections::websocket::WebsocketConnection;
+#[cfg(feature = "websocket")]
+pub use crate::connections::websocket::WebsocketSdp;
+#[cfg(feature = "websocket")]
+pub use crate::connections::websocket::WebsocketTransport;
@@ -5,6 +5,7 @@ use bytes::Bytes;
 use webrtc::data_channel::data_channel_message::DataChannelMessage;
 use webrtc::data_channel::data_channel_state::RTCDataChannelState;
 use webrtc::data_channel::RTCDataChannel;
+use webrtc::ice::candidate::CandidatePairState;
 use webrtc::ice::mdns::MulticastDnsMode;
 use webrtc::ice_transport::ice_candidate_type::RTCIceCandidateType;
 use webrtc::ice_transport::ice_credential_type::RTCIceCredentialType;
@@ -13,11 +14,13 @@ use webrtc::peer_connection::configuration::RTCConfiguration;
 use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
 use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
 use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::stats::StatsReportType;
 
 use crate::callback::InnerTransportCallback;
 use crate::connection_ref::ConnectionRef;
 use crate::core::callback::BoxedTransportCallback;
 use crate::core::transport::ConnectionInterface;
+use crate::core::transport::ConnectionStats;
 use crate::core::transport::TransportInterface;
 use crate::core::transport::TransportMessage;
 use crate::core::transport::WebrtcConnectionState;
@@ -34,6 +37,7 @@ pub struct WebrtcConnection {
     webrtc_conn: RTCPeerConnection,
     webrtc_data_channel: Arc<RTCDataChannel>,
     webrtc_data_channel_open_notifier: Notifier,
+    callback: Arc<InnerTransportCallback>,
 }
 
 /// [WebrtcTransport] manages all the [WebrtcConnection] and
@@ -49,11 +53,13 @@ impl WebrtcConnection {
         webrtc_conn: RTCPeerConnection,
         webrtc_data_channel: Arc<RTCDataChannel>,
         webrtc_data_channel_open_notifier: Notifier,
+        callback: Arc<InnerTransportCallback>,
     ) -> Self {
         Self {
             webrtc_conn,
             webrtc_data_channel,
             webrtc_data_channel_open_notifier,
+            callback,
         }
     }
 
@@ -84,107 +90,13 @@ impl WebrtcTransport {
             pool: Pool::new(),
         }
     }
-}
-
-#[async_trait]
-impl ConnectionInterface for WebrtcConnection {
-    type Sdp = String;
-    type Error = Error;
-
-    async fn send_message(&self, msg: TransportMessage) -> Result<()> {
-        self.webrtc_wait_for_data_channel_open().await?;
-        let data = bincode::serialize(&msg).map(Bytes::from)?;
-        if let Err(e) = self.webrtc_data_channel.send(&data).await {
-            tracing::error!("{:?}, Data size: {:?}", e, data.len());
-            return Err(e.into());
-        }
-        Ok(())
-    }
-
-    async fn get_stats(&self) -> Vec<String> {
-        self.webrtc_conn
-            .get_stats()
-            .await
-            .reports
-            .into_iter()
-            .map(|x| serde_json::to_string(&x).unwrap_or("failed to dump stats entry".to_string()))
-            .collect()
-    }
-
-    fn webrtc_connection_state(&self) -> WebrtcConnectionState {
-        self.webrtc_conn.connection_state().into()
-    }
-
-    async fn webrtc_create_offer(&self) -> Result<Self::Sdp> {
-        let setting_offer = self.webrtc_conn.create_offer(None).await?;
-        self.webrtc_conn
-            .set_local_description(setting_offer.clone())
-            .await?;
-
-        self.webrtc_gather().await
-    }
-
-    async fn webrtc_answer_offer(&self, offer: Self::Sdp) -> Result<Self::Sdp> {
-        tracing::debug!("webrtc_answer_offer, offer: {offer:?}");
-        let offer = RTCSessionDescription::offer(offer)?;
-        self.webrtc_conn.set_remote_description(offer).await?;
-
-        let answer = self.webrtc_conn.create_answer(None).await?;
-        self.webrtc_conn
-            .set_local_description(answer.clone())
-            .await?;
-
-        self.webrtc_gather().await
-    }
-
-    async fn webrtc_accept_answer(&self, answer: Self::Sdp) -> Result<()> {
-        tracing::debug!("webrtc_accept_answer, answer: {answer:?}");
-        let answer = RTCSessionDescription::answer(answer)?;
-        self.webrtc_conn
-            .set_remote_description(answer)
-            .await
-            .map_err(|e| e.into())
-    }
-
-    async fn webrtc_wait_for_data_channel_open(&self) -> Result<()> {
-        if matches!(
-            self.webrtc_connection_state(),
-            WebrtcConnectionState::Failed
-                | WebrtcConnectionState::Closed
-                | WebrtcConnectionState::Disconnected
-        ) {
-            return Err(Error::DataChannelOpen("Connection unavailable".to_string()));
-        }
-
-        if self.webrtc_data_channel.ready_state() == RTCDataChannelState::Open {
-            return Ok(());
-        }
-
-        self.webrtc_data_channel_open_notifier.clone().await
-    }
-
-    async fn close(&self) -> Result<()> {
-        self.webrtc_conn.close().await.map_err(|e| e.into())
-    }
-}
-
-#[async_trait]
-impl TransportInterface for WebrtcTransport {
-    type Connection = WebrtcConnection;
-    type Error = Error;
-
-    async fn new_connection(&self, cid: &str, callback: BoxedTransportCallback) -> Result<()> {
-        if let Ok(existed_conn) = self.pool.connection(cid) {
-            if matches!(
-                existed_conn.webrtc_connection_state(),
-                WebrtcConnectionState::New
-                    | WebrtcConnectionState::Connecting
-                    | WebrtcConnectionState::Connected
-            ) {
-                return Err(Error::ConnectionAlreadyExists(cid.to_string()));
-            }
-        }
 
+    /// Create a [WebrtcConnection] without registering it in the transport.
+    pub(crate) async fn build_connection(
+        &self,
+        cid: &str,
+        callback: BoxedTransportCallback,
+    ) -> Result<WebrtcConnection> {
         //
         // Setup webrtc connection env
         //
@@ -282,8 +194,144 @@ impl TransportInterface for WebrtcTransport {
             webrtc_conn,
             webrtc_data_channel,
             webrtc_data_channel_open_notifier,
+            inner_cb,
         );
 
+        Ok(conn)
+    }
+}
+
+#[async_trait]
+impl ConnectionInterface for WebrtcConnection {
+    type Sdp = String;
+    type Error = Error;
+
+    async fn send_message(&self, msg: TransportMessage) -> Result<()> {
+        self.webrtc_wait_for_data_channel_open().await?;
+        let data = bincode::serialize(&msg).map(Bytes::from)?;
+        if let Err(e) = self.webrtc_data_channel.send(&data).await {
+            tracing::error!("{:?}, Data size: {:?}", e, data.len());
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    async fn get_stats(&self) -> ConnectionStats {
+        let reports = self.webrtc_conn.get_stats().await.reports;
+        let mut stats = ConnectionStats {
+            buffered_amount: self.webrtc_data_channel.buffered_amount().await as u64,
+            state_durations: self.callback.state_durations(),
+            ..Default::default()
+        };
+
+        let mut selected_pair = None;
+        for report in reports.values() {
+            match report {
+                StatsReportType::DataChannel(dc) => {
+                    stats.bytes_sent += dc.bytes_sent as u64;
+                    stats.bytes_received += dc.bytes_received as u64;
+                    stats.messages_sent += dc.messages_sent as u64;
+                    stats.messages_received += dc.messages_received as u64;
+                }
+                StatsReportType::CandidatePair(pair)
+                    if pair.nominated && pair.state == CandidatePairState::Succeeded =>
+                {
+                    if pair.current_round_trip_time > 0.0 {
+                        stats.rtt_ms = Some(pair.current_round_trip_time * 1000.0);
+                    }
+                    selected_pair = Some((&pair.local_candidate_id, &pair.remote_candidate_id));
+                }
+                _ => {}
+            }
+        }
+
+        if let Some((local_id, remote_id)) = selected_pair {
+            if let Some(StatsReportType::LocalCandidate(c)) = reports.get(local_id) {
+                stats.local_candidate_type = c.candidate_type.to_string().as_str().into();
+            }
+            if let Some(StatsReportType::RemoteCandidate(c)) = reports.get(remote_id) {
+                stats.remote_candidate_type = c.candidate_type.to_string().as_str().into();
+            }
+        }
+
+        stats
+    }
+
+    fn webrtc_connection_state(&self) -> WebrtcConnectionState {
+        self.webrtc_conn.connection_state().into()
+    }
+
+    async fn webrtc_create_offer(&self) -> Result<Self::Sdp> {
+        let setting_offer = self.webrtc_conn.create_offer(None).await?;
+        self.webrtc_conn
+            .set_local_description(setting_offer.clone())
+            .await?;
+
+        self.webrtc_gather().await
+    }
+
+    async fn webrtc_answer_offer(&self, offer: Self::Sdp) -> Result<Self::Sdp> {
+        tracing::debug!("webrtc_answer_offer, offer: {offer:?}");
+        let offer = RTCSessionDescription::offer(offer)?;
+        self.webrtc_conn.set_remote_description(offer).await?;
+
+        let answer = self.webrtc_conn.create_answer(None).await?;
+        self.webrtc_conn
+            .set_local_description(answer.clone())
+            .await?;
+
+        self.webrtc_gather().await
+    }
+
+    async fn webrtc_accept_answer(&self, answer: Self::Sdp) -> Result<()> {
+        tracing::debug!("webrtc_accept_answer, answer: {answer:?}");
+        let answer = RTCSessionDescription::answer(answer)?;
+        self.webrtc_conn
+            .set_remote_description(answer)
+            .await
+            .map_err(|e| e.into())
+    }
+
+    async fn webrtc_wait_for_data_channel_open(&self) -> Result<()> {
+        if matches!(
+            self.webrtc_connection_state(),
+            WebrtcConnectionState::Failed
+                | WebrtcConnectionState::Closed
+                | WebrtcConnectionState::Disconnected
+        ) {
+            return Err(Error::DataChannelOpen("Connection unavailable".to_string()));
+        }
+
+        if self.webrtc_data_channel.ready_state() == RTCDataChannelState::Open {
+            return Ok(());
+        }
+
+        self.webrtc_data_channel_open_notifier.clone().await
+    }
+
+    async fn close(&self) -> Result<()> {
+        self.webrtc_conn.close().await.map_err(|e| e.into())
+    }
+}
+
+#[async_trait]
+impl TransportInterface for WebrtcTransport {
+    type Connection = WebrtcConnection;
+    type Error = Error;
+
+    async fn new_connection(&self, cid: &str, callback: BoxedTransportCallback) -> Result<()> {
+        if let Ok(existed_conn) = self.pool.connection(cid) {
+            if matches!(
+                existed_conn.webrtc_connection_state(),
+                WebrtcConnectionState::New
+                    | WebrtcConnectionState::Connecting
+                    | WebrtcConnectionState::Connected
+            ) {
+                return Err(Error::ConnectionAlreadyExists(cid.to_string()));
+            }
+        }
+
+        let conn = self.build_connection(cid, callback).await?;
         self.pool.safely_insert(cid, conn)?;
         Ok(())
     }
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use js_sys::Array;
+use js_sys::Reflect;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
 use wasm_bindgen_futures::spawn_local;
@@ -25,6 +26,8 @@ use crate::callback::InnerTransportCallback;
 use crate::connection_ref::ConnectionRef;
 use crate::core::callback::BoxedTransportCallback;
 use crate::core::transport::ConnectionInterface;
+use crate::core::transport::ConnectionStats;
+use crate::core::transport::IceCandidateType;
 use crate::core::transport::TransportInterface;
 use crate::core::transport::TransportMessage;
 use crate::core::transport::WebrtcConnectionState;
@@ -41,6 +44,7 @@ pub struct WebSysWebrtcConnection {
     webrtc_conn: RtcPeerConnection,
     webrtc_data_channel: RtcDataChannel,
     webrtc_data_channel_open_notifier: Notifier,
+    callback: Arc<InnerTransportCallback>,
 }
 
 /// [WebSysWebrtcTransport] manages all the [WebSysWebrtcConnection] and
@@ -55,11 +59,13 @@ impl WebSysWebrtcConnection {
         webrtc_conn: RtcPeerConnection,
         webrtc_data_channel: RtcDataChannel,
         webrtc_data_channel_open_notifier: Notifier,
+        callback: Arc<InnerTransportCallback>,
     ) -> Self {
         Self {
             webrtc_conn,
             webrtc_data_channel,
             webrtc_data_channel_open_notifier,
+            callback,
         }
     }
 
@@ -118,19 +124,60 @@ impl ConnectionInterface for WebSysWebrtcConnection {
         self.webrtc_conn.connection_state().into()
     }
 
-    async fn get_stats(&self) -> Vec<String> {
+    async fn get_stats(&self) -> ConnectionStats {
+        let mut stats = ConnectionStats {
+            buffered_amount: self.webrtc_data_channel.buffered_amount() as u64,
+            state_durations: self.callback.state_durations(),
+            ..Default::default()
+        };
+
         let promise = self.webrtc_conn.get_stats();
         let Ok(value) = wasm_bindgen_futures::JsFuture::from(promise).await else {
-            return vec![];
+            return stats;
         };
+        let report: RtcStatsReport = value.into();
+        let entries = report
+            .entries()
+            .into_iter()
+            .flatten()
+            .map(|x| Array::from(&x).get(1))
+            .collect::<Vec<_>>();
+
+        let mut selected_pair = None;
+        for entry in entries.iter() {
+            match stats_str(entry, "type").as_deref() {
+                Some("data-channel") => {
+                    stats.bytes_sent += stats_u64(entry, "bytesSent");
+                    stats.bytes_received += stats_u64(entry, "bytesReceived");
+                    stats.messages_sent += stats_u64(entry, "messagesSent");
+                    stats.messages_received += stats_u64(entry, "messagesReceived");
+                }
+                Some("candidate-pair")
+                    if stats_str(entry, "state").as_deref() == Some("succeeded")
+                        && Reflect::get(entry, &"nominated".into())
+                            .ok()
+                            .and_then(|x| x.as_bool())
+                            .unwrap_or(false) =>
+                {
+                    stats.rtt_ms = Reflect::get(entry, &"currentRoundTripTime".into())
+                        .ok()
+                        .and_then(|x| x.as_f64())
+                        .map(|rtt| rtt * 1000.0);
+                    selected_pair = Some((
+                        stats_str(entry, "localCandidateId"),
+                        stats_str(entry, "remoteCandidateId"),
+                    ));
+                }
+                _ => {}
+            }
+        }
 
-        let stats: RtcStatsReport = value.into();
+        if let Some((local_id, remote_id)) = selected_pair {
+            stats.local_candidate_type = candidate_type(&entries, local_id);
+            stats.remote_candidate_type = candidate_type(&entries, remote_id);
+        }
 
         stats
-            .entries()
-            .into_iter()
-            .map(|x| dump_stats_entry(&x.ok()).unwrap_or("failed to dump stats entry".to_string()))
-            .collect::<Vec<_>>()
     }
 
     async fn webrtc_create_offer(&self) -> Result<Self::Sdp> {
@@ -347,6 +394,7 @@ impl TransportInterface for WebSysWebrtcTransport {
             webrtc_conn,
             webrtc_data_channel,
             webrtc_data_channel_open_notifier,
+            inner_cb,
         );
 
         self.pool.safely_insert(cid, conn)?;
@@ -424,8 +472,25 @@ impl From<RtcPeerConnectionState> for WebrtcConnectionState {
     }
 }
 
-fn dump_stats_entry(entry: &Option<JsValue>) -> Option<String> {
-    js_sys::JSON::stringify(entry.as_ref()?)
+fn stats_str(entry: &JsValue, key: &str) -> Option<String> {
+    Reflect::get(entry, &key.into()).ok()?.as_string()
+}
+
+fn stats_u64(entry: &JsValue, key: &str) -> u64 {
+    Reflect::get(entry, &key.into())
         .ok()
-        .and_then(|x| x.as_string())
+        .and_then(|x| x.as_f64())
+        .unwrap_or_default() as u64
+}
+
+fn candidate_type(entries: &[JsValue], id: Option<String>) -> IceCandidateType {
+    let Some(id) = id else {
+        return IceCandidateType::Unspecified;
+    };
+    entries
+        .iter()
+        .find(|entry| stats_str(entry, "id").as_ref() == Some(&id))
+        .and_then(|entry| stats_str(entry, "candidateType"))
+        .map(|t| t.as_str().into())
+        .unwrap_or_default()
 }
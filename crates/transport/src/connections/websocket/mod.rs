@@ -0,0 +1,488 @@
+//! A connection over websocket, used between servers that can reach each other directly.
+//!
+//! The handshake keeps the offer/answer flow of webrtc, but the sdp is replaced by a
+//! [WebsocketSdp], which carries a random token and the dialable url of its generator if it's
+//! listening. The side having a url is dialed by the other side, and the token is sent as the
+//! first frame so that the listener can match the socket with the pending connection.
+
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::Weak;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use dashmap::DashMap;
+use futures::Sink;
+use futures::SinkExt;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::WebSocketStream;
+
+use crate::callback::InnerTransportCallback;
+use crate::connection_ref::ConnectionRef;
+use crate::core::callback::BoxedTransportCallback;
+use crate::core::transport::ConnectionInterface;
+use crate::core::transport::ConnectionStats;
+use crate::core::transport::TransportInterface;
+use crate::core::transport::TransportMessage;
+use crate::core::transport::WebrtcConnectionState;
+use crate::error::Error;
+use crate::error::Result;
+use crate::notifier::Notifier;
+use crate::pool::Pool;
+
+/// Timeout of waiting for the token frame of an inbound socket.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+type WsSink = Pin<Box<dyn Sink<WsMessage, Error = tokio_tungstenite::tungstenite::Error> + Send>>;
+
+/// Connections waiting for an inbound socket, indexed by token.
+type PendingSockets = Arc<DashMap<String, Weak<WebsocketLink>>>;
+
+/// The offer and answer of [WebsocketConnection], exchanged as json string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebsocketSdp {
+    /// Dialable url of the generator, if it's listening.
+    pub url: Option<String>,
+    /// Token used to match the socket with the pending connection.
+    pub token: String,
+}
+
+impl WebsocketSdp {
+    /// Parse a websocket sdp, returns error if it's not one.
+    pub fn from_sdp(sdp: &str) -> Result<Self> {
+        serde_json::from_str(sdp).map_err(|e| Error::WebsocketSdp(e.to_string()))
+    }
+
+    fn to_sdp(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| Error::WebsocketSdp(e.to_string()))
+    }
+}
+
+/// The socket of a [WebsocketConnection], shared with the task reading from it.
+struct WebsocketLink {
+    state: Mutex<WebrtcConnectionState>,
+    sink: tokio::sync::Mutex<Option<WsSink>>,
+    callback: Arc<InnerTransportCallback>,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    messages_sent: AtomicU64,
+    messages_received: AtomicU64,
+}
+
+/// A connection implemented by websocket.
+/// Used for native environment, between nodes that at least one of them is dialable.
+pub struct WebsocketConnection {
+    cid: String,
+    link: Arc<WebsocketLink>,
+    open_notifier: Notifier,
+    public_url: Option<String>,
+    pending: PendingSockets,
+    token: Mutex<Option<String>>,
+}
+
+/// [WebsocketTransport] manages all the [WebsocketConnection] and
+/// provides methods to create, get and close connections.
+pub struct WebsocketTransport {
+    public_url: Option<String>,
+    pending: PendingSockets,
+    accept_task: Option<JoinHandle<()>>,
+    pool: Pool<WebsocketConnection>,
+}
+
+impl WebsocketLink {
+    fn new(callback: Arc<InnerTransportCallback>) -> Self {
+        Self {
+            state: Mutex::new(WebrtcConnectionState::New),
+            sink: tokio::sync::Mutex::new(None),
+            callback,
+            bytes_sent: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            messages_sent: AtomicU64::new(0),
+            messages_received: AtomicU64::new(0),
+        }
+    }
+
+    fn state(&self) -> WebrtcConnectionState {
+        *self.state.lock().unwrap()
+    }
+
+    async fn set_state(&self, state: WebrtcConnectionState) {
+        {
+            let mut current = self.state.lock().unwrap();
+
+            if state == *current {
+                return;
+            }
+
+            *current = state;
+        }
+
+        self.callback.on_peer_connection_state_change(state).await;
+    }
+
+    /// Take over the socket, then keep reading messages from it until it's closed.
+    async fn attach<S>(self: Arc<Self>, ws: WebSocketStream<S>)
+    where S: AsyncRead + AsyncWrite + Unpin + Send + 'static {
+        let (sink, mut stream) = ws.split();
+        *self.sink.lock().await = Some(Box::pin(sink));
+        self.callback.on_data_channel_open();
+        self.set_state(WebrtcConnectionState::Connected).await;
+
+        tokio::spawn(async move {
+            while let Some(msg) = stream.next().await {
+                match msg {
+                    Ok(WsMessage::Binary(data)) => {
+                        self.bytes_received
+                            .fetch_add(data.len() as u64, Ordering::Relaxed);
+                        self.messages_received.fetch_add(1, Ordering::Relaxed);
+                        self.callback.on_message(&Bytes::from(data)).await;
+                    }
+                    Ok(WsMessage::Close(_)) => break,
+                    Ok(_) => {}
+                    Err(e) => {
+                        tracing::warn!("Failed to read websocket: {e:?}");
+                        break;
+                    }
+                }
+            }
+
+            self.sink.lock().await.take();
+            self.callback.on_data_channel_close();
+            if self.state() != WebrtcConnectionState::Closed {
+                self.set_state(WebrtcConnectionState::Disconnected).await;
+                self.set_state(WebrtcConnectionState::Closed).await;
+            }
+        });
+    }
+
+    /// Dial the url and send token as the first frame.
+    async fn dial(self: Arc<Self>, url: String, token: String) {
+        let ws = async {
+            let (mut ws, _) = tokio_tungstenite::connect_async(url.as_str()).await?;
+            ws.send(WsMessage::Text(token)).await?;
+            Ok::<_, Error>(ws)
+        };
+
+        match ws.await {
+            Ok(ws) => self.attach(ws).await,
+            Err(e) => {
+                tracing::error!("Failed to dial websocket {url}: {e:?}");
+                self.callback.on_data_channel_close();
+                self.set_state(WebrtcConnectionState::Failed).await;
+            }
+        }
+    }
+}
+
+impl WebsocketConnection {
+    /// Register the token so that the inbound socket carrying it will be attached.
+    fn wait_for_socket(&self, token: &str) {
+        self.pending
+            .insert(token.to_string(), Arc::downgrade(&self.link));
+        *self.token.lock().unwrap() = Some(token.to_string());
+    }
+
+    fn is_waiting_for_socket(&self) -> bool {
+        self.token.lock().unwrap().is_some()
+    }
+
+    fn dial(&self, url: String, token: String) {
+        tokio::spawn(self.link.clone().dial(url, token));
+    }
+}
+
+impl Drop for WebsocketConnection {
+    fn drop(&mut self) {
+        if let Some(token) = self.token.get_mut().ok().and_then(|t| t.take()) {
+            self.pending.remove(&token);
+        }
+    }
+}
+
+impl WebsocketTransport {
+    /// Create a new [WebsocketTransport] instance.
+    ///
+    /// If `listener` is given as `(address, public_url)`, the transport accepts sockets on the
+    /// address, and offers the public url to remote peers for dialing. Otherwise it can only
+    /// connect to peers that are listening. The accepting task is spawned on current tokio
+    /// runtime.
+    pub fn new(listener: Option<(SocketAddr, String)>) -> Self {
+        let pending = PendingSockets::default();
+
+        let (public_url, accept_task) = match listener {
+            Some((addr, url)) => (
+                Some(url),
+                Some(tokio::spawn(accept_loop(addr, pending.clone()))),
+            ),
+            None => (None, None),
+        };
+
+        Self {
+            public_url,
+            pending,
+            accept_task,
+            pool: Pool::new(),
+        }
+    }
+
+    /// Create a [WebsocketConnection] without registering it in the transport.
+    pub(crate) fn build_connection(
+        &self,
+        cid: &str,
+        callback: BoxedTransportCallback,
+    ) -> WebsocketConnection {
+        let open_notifier = Notifier::default();
+        let callback = Arc::new(InnerTransportCallback::new(
+            cid,
+            callback,
+            open_notifier.clone(),
+        ));
+
+        WebsocketConnection {
+            cid: cid.to_string(),
+            link: Arc::new(WebsocketLink::new(callback)),
+            open_notifier,
+            public_url: self.public_url.clone(),
+            pending: self.pending.clone(),
+            token: Mutex::new(None),
+        }
+    }
+}
+
+impl Drop for WebsocketTransport {
+    fn drop(&mut self) {
+        if let Some(task) = self.accept_task.take() {
+            task.abort();
+        }
+    }
+}
+
+#[async_trait]
+impl ConnectionInterface for WebsocketConnection {
+    type Sdp = String;
+    type Error = Error;
+
+    async fn send_message(&self, msg: TransportMessage) -> Result<()> {
+        self.webrtc_wait_for_data_channel_open().await?;
+        let data = bincode::serialize(&msg)?;
+        let len = data.len() as u64;
+
+        let mut sink = self.link.sink.lock().await;
+        let Some(sink) = sink.as_mut() else {
+            return Err(Error::DataChannelOpen("Connection unavailable".to_string()));
+        };
+        sink.send(WsMessage::Binary(data)).await?;
+
+        self.link.bytes_sent.fetch_add(len, Ordering::Relaxed);
+        self.link.messages_sent.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn webrtc_connection_state(&self) -> WebrtcConnectionState {
+        self.link.state()
+    }
+
+    async fn get_stats(&self) -> ConnectionStats {
+        ConnectionStats {
+            bytes_sent: self.link.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.link.bytes_received.load(Ordering::Relaxed),
+            messages_sent: self.link.messages_sent.load(Ordering::Relaxed),
+            messages_received: self.link.messages_received.load(Ordering::Relaxed),
+            state_durations: self.link.callback.state_durations(),
+            ..Default::default()
+        }
+    }
+
+    async fn webrtc_create_offer(&self) -> Result<Self::Sdp> {
+        let token = format!("{:032x}", rand::random::<u128>());
+        if self.public_url.is_some() {
+            self.wait_for_socket(&token);
+        }
+        self.link.set_state(WebrtcConnectionState::Connecting).await;
+
+        WebsocketSdp {
+            url: self.public_url.clone(),
+            token,
+        }
+        .to_sdp()
+    }
+
+    async fn webrtc_answer_offer(&self, offer: Self::Sdp) -> Result<Self::Sdp> {
+        tracing::debug!("webrtc_answer_offer, offer: {offer:?}");
+        let offer = WebsocketSdp::from_sdp(&offer)?;
+        self.link.set_state(WebrtcConnectionState::Connecting).await;
+
+        let answer = match (offer.url, &self.public_url) {
+            (Some(url), _) => {
+                self.dial(url, offer.token.clone());
+                WebsocketSdp {
+                    url: None,
+                    token: offer.token,
+                }
+            }
+            (None, Some(public_url)) => {
+                self.wait_for_socket(&offer.token);
+                WebsocketSdp {
+                    url: Some(public_url.clone()),
+                    token: offer.token,
+                }
+            }
+            (None, None) => return Err(Error::WebsocketNotDialable(self.cid.clone())),
+        };
+
+        answer.to_sdp()
+    }
+
+    async fn webrtc_accept_answer(&self, answer: Self::Sdp) -> Result<()> {
+        tracing::debug!("webrtc_accept_answer, answer: {answer:?}");
+        let answer = WebsocketSdp::from_sdp(&answer)?;
+
+        match answer.url {
+            Some(url) => self.dial(url, answer.token),
+            // The remote peer is dialing the url in offer.
+            None if self.is_waiting_for_socket() => {}
+            None => return Err(Error::WebsocketNotDialable(self.cid.clone())),
+        }
+
+        Ok(())
+    }
+
+    async fn webrtc_wait_for_data_channel_open(&self) -> Result<()> {
+        if matches!(
+            self.webrtc_connection_state(),
+            WebrtcConnectionState::Failed
+                | WebrtcConnectionState::Closed
+                | WebrtcConnectionState::Disconnected
+        ) {
+            return Err(Error::DataChannelOpen("Connection unavailable".to_string()));
+        }
+
+        if self.link.sink.lock().await.is_some() {
+            return Ok(());
+        }
+
+        self.open_notifier.clone().await
+    }
+
+    async fn close(&self) -> Result<()> {
+        self.link.set_state(WebrtcConnectionState::Closed).await;
+        if let Some(mut sink) = self.link.sink.lock().await.take() {
+            sink.close().await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TransportInterface for WebsocketTransport {
+    type Connection = WebsocketConnection;
+    type Error = Error;
+
+    async fn new_connection(&self, cid: &str, callback: BoxedTransportCallback) -> Result<()> {
+        if let Ok(existed_conn) = self.pool.connection(cid) {
+            if matches!(
+                existed_conn.webrtc_connection_state(),
+                WebrtcConnectionState::New
+                    | WebrtcConnectionState::Connecting
+                    | WebrtcConnectionState::Connected
+            ) {
+                return Err(Error::ConnectionAlreadyExists(cid.to_string()));
+            }
+        }
+
+        let conn = self.build_connection(cid, callback);
+        self.pool.safely_insert(cid, conn)?;
+        Ok(())
+    }
+
+    async fn close_connection(&self, cid: &str) -> Result<()> {
+        self.pool.safely_remove(cid).await
+    }
+
+    fn connection(&self, cid: &str) -> Result<ConnectionRef<Self::Connection>> {
+        self.pool.connection(cid)
+    }
+
+    fn connections(&self) -> Vec<(String, ConnectionRef<Self::Connection>)> {
+        self.pool.connections()
+    }
+
+    fn connection_ids(&self) -> Vec<String> {
+        self.pool.connection_ids()
+    }
+}
+
+async fn accept_loop(addr: SocketAddr, pending: PendingSockets) {
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!("Failed to listen websocket on {addr}: {e:?}");
+            return;
+        }
+    };
+    tracing::info!("Websocket transport is listening on {addr}");
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, remote_addr)) => {
+                let pending = pending.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = accept(stream, pending).await {
+                        tracing::warn!("Rejected websocket from {remote_addr}: {e:?}");
+                    }
+                });
+            }
+            Err(e) => tracing::error!("Failed to accept websocket: {e:?}"),
+        }
+    }
+}
+
+/// Read the token from an inbound socket, then attach it to the pending connection.
+async fn accept(stream: TcpStream, pending: PendingSockets) -> Result<()> {
+    let mut ws = tokio_tungstenite::accept_async(stream).await?;
+
+    let token = match tokio::time::timeout(HANDSHAKE_TIMEOUT, ws.next()).await {
+        Ok(Some(Ok(WsMessage::Text(token)))) => token,
+        _ => {
+            return Err(Error::WebsocketHandshake(
+                "token frame is missing".to_string(),
+            ))
+        }
+    };
+
+    let link = pending
+        .remove(&token)
+        .and_then(|(_, link)| link.upgrade())
+        .ok_or_else(|| Error::WebsocketHandshake(format!("unknown token {token}")))?;
+    link.attach(ws).await;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_websocket_sdp() {
+        let sdp = WebsocketSdp {
+            url: Some("ws://127.0.0.1:50001".to_string()),
+            token: "token".to_string(),
+        };
+        assert_eq!(WebsocketSdp::from_sdp(&sdp.to_sdp().unwrap()).unwrap(), sdp);
+        assert!(WebsocketSdp::from_sdp("v=0\r\no=- 0 0 IN IP4 127.0.0.1").is_err());
+    }
+}
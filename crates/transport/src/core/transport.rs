@@ -22,7 +22,7 @@ pub enum TransportMessage {
 
 /// The state of the WebRTC connection.
 /// This enum is used to define a same interface for all the platforms.
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum WebrtcConnectionState {
     /// Unspecified
     #[default]
@@ -58,6 +58,93 @@ pub enum WebrtcConnectionState {
     Closed,
 }
 
+/// The kind of link a connection is built on.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionKind {
+    /// Data channel of webrtc, which can traverse NAT by ICE.
+    #[default]
+    Webrtc,
+
+    /// Websocket to a dialable url, used between servers reachable from each other.
+    Websocket,
+}
+
+/// The type of ICE candidate, defined by RFC 8445.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IceCandidateType {
+    /// Unknown, or no candidate is selected yet.
+    #[default]
+    Unspecified,
+
+    /// A candidate obtained from a local network interface.
+    Host,
+
+    /// A server reflexive candidate, which is the address mapped by a NAT and discovered by STUN.
+    Srflx,
+
+    /// A peer reflexive candidate, which is the address learned from the remote peer during
+    /// connectivity checks.
+    Prflx,
+
+    /// A relayed candidate allocated on a TURN server.
+    Relay,
+}
+
+/// Statistics of a connection, collected by [ConnectionInterface::get_stats].
+#[derive(Default, Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ConnectionStats {
+    /// Round trip time in milliseconds of the selected candidate pair, if it's measured.
+    pub rtt_ms: Option<f64>,
+
+    /// Bytes sent over data channels.
+    pub bytes_sent: u64,
+
+    /// Bytes received over data channels.
+    pub bytes_received: u64,
+
+    /// Messages sent over data channels.
+    pub messages_sent: u64,
+
+    /// Messages received over data channels.
+    pub messages_received: u64,
+
+    /// Candidate type of the local side of selected candidate pair.
+    pub local_candidate_type: IceCandidateType,
+
+    /// Candidate type of the remote side of selected candidate pair.
+    pub remote_candidate_type: IceCandidateType,
+
+    /// Bytes queued on the data channel but not yet sent.
+    pub buffered_amount: u64,
+
+    /// Milliseconds spent in each connection state, ordered by the time the state was first
+    /// entered. The duration of current state is counted until now.
+    pub state_durations: Vec<(WebrtcConnectionState, u64)>,
+}
+
+impl ConnectionStats {
+    /// Returns `true` if the selected candidate pair goes through a TURN server.
+    pub fn is_relayed(&self) -> bool {
+        self.local_candidate_type == IceCandidateType::Relay
+            || self.remote_candidate_type == IceCandidateType::Relay
+    }
+}
+
+impl From<&str> for IceCandidateType {
+    /// Parse the `candidateType` field of webrtc stats.
+    fn from(s: &str) -> Self {
+        match s {
+            "host" => Self::Host,
+            "srflx" => Self::Srflx,
+            "prflx" => Self::Prflx,
+            "relay" => Self::Relay,
+            _ => Self::Unspecified,
+        }
+    }
+}
+
 /// The [ConnectionInterface] trait defines how to
 /// make webrtc ice handshake with a remote peer and then send data channel message to it.
 #[cfg_attr(feature = "web-sys-webrtc", async_trait(?Send))]
@@ -74,8 +161,8 @@ pub trait ConnectionInterface {
     /// Get current webrtc connection state.
     fn webrtc_connection_state(&self) -> WebrtcConnectionState;
 
-    /// This is a debug method to dump the stats of webrtc connection.
-    async fn get_stats(&self) -> Vec<String>;
+    /// Collect the statistics of webrtc connection.
+    async fn get_stats(&self) -> ConnectionStats;
 
     /// Create a webrtc offer to start handshake.
     async fn webrtc_create_offer(&self) -> Result<Self::Sdp, Self::Error>;
@@ -150,6 +237,11 @@ pub trait TransportInterface {
 
     /// Get all the connection ids in the transport.
     fn connection_ids(&self) -> Vec<String>;
+
+    /// Set the kind of connection used when offering to the peer of cid.
+    /// It takes effect on connections created afterwards. The answering side always follows
+    /// the kind of offer. Transports supporting only one kind ignore it.
+    fn set_connection_kind(&self, _cid: &str, _kind: ConnectionKind) {}
 }
 
 /// Used to store a boxed [TransportInterface] trait object.
@@ -27,6 +27,22 @@ pub enum Error {
     #[error("WebSysWebRTC error: {}", dump_js_value(.0))]
     WebSysWebrtc(wasm_bindgen::JsValue),
 
+    #[cfg(feature = "websocket")]
+    #[error("Websocket error: {0}")]
+    Websocket(#[from] tokio_tungstenite::tungstenite::Error),
+
+    #[error("Invalid websocket sdp: {0}")]
+    WebsocketSdp(String),
+
+    #[error("Neither side of websocket connection {0} is dialable")]
+    WebsocketNotDialable(String),
+
+    #[error("Websocket handshake failed: {0}")]
+    WebsocketHandshake(String),
+
+    #[error("Connection {0} has not started handshake")]
+    HandshakeNotStarted(String),
+
     #[error("Bincode error: {0}")]
     Bincode(#[from] bincode::Error),
 